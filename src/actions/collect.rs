@@ -18,6 +18,8 @@ pub fn links_mut_doc<'a>(doc: &'a mut Doc, res: &mut Vec<&'a mut Link>, include_
         match item {
             DocItem::Section(section) => links_mut_section(section, res),
             DocItem::Paragraph(par) => links_mut_par(par, res),
+            DocItem::Quote(quote) => links_mut_quote(quote, res),
+            DocItem::Footnote(footnote) => links_mut_footnote(footnote, res),
         }
     }
 }
@@ -38,10 +40,32 @@ pub fn links_mut_section<'a>(section: &'a mut Section, res: &mut Vec<&'a mut Lin
         match item {
             SectionItem::Paragraph(par) => links_mut_par(par, res),
             SectionItem::Section(section) => links_mut_section(section, res),
+            SectionItem::Quote(quote) => links_mut_quote(quote, res),
+            SectionItem::Footnote(footnote) => links_mut_footnote(footnote, res),
         }
     }
 }
 
+/// Collect a mutable reference to all links in the quote, its source included.
+pub fn links_mut_quote<'a>(quote: &'a mut Quote, res: &mut Vec<&'a mut Link>) {
+    for item in &mut quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => links_mut_par(par, res),
+            QuoteItem::Quote(quote) => links_mut_quote(quote, res),
+        }
+    }
+    if let Some(source) = &mut quote.source {
+        res.push(source);
+    }
+}
+
+/// Collect a mutable reference to all links in the footnote.
+pub fn links_mut_footnote<'a>(footnote: &'a mut FootnoteDef, res: &mut Vec<&'a mut Link>) {
+    for par in &mut footnote.items {
+        links_mut_par(par, res);
+    }
+}
+
 /// Collect a mutable reference to all links in the paragraph.
 pub fn links_mut_par<'a>(par: &'a mut Paragraph, res: &mut Vec<&'a mut Link>) {
     for item in &mut par.items {
@@ -49,6 +73,7 @@ pub fn links_mut_par<'a>(par: &'a mut Paragraph, res: &mut Vec<&'a mut Link>) {
             ParagraphItem::Link(link) => res.push(link),
             ParagraphItem::List(list) => links_mut_list(list, res),
             ParagraphItem::Table(table) => links_mut_table(table, res),
+            ParagraphItem::Quote(InlineQuote { source: Some(source), .. }) => res.push(source),
             _ => { },
         }
     }
@@ -12,6 +12,12 @@ impl DeEmphasise for Emphasis {
 }
 
 impl DeEmphasise for Vec<EmOrText> {
+    fn deemphasise(&self) -> String {
+        self.as_slice().deemphasise()
+    }
+}
+
+impl DeEmphasise for [EmOrText] {
     fn deemphasise(&self) -> String {
         let mut res = String::new();
         for item in self {
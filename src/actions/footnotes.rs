@@ -0,0 +1,233 @@
+use crate::*;
+
+/// The footnotes of a document, numbered.
+/// Footnotes are numbered in the order they are first referenced, starting at 1. Footnotes that
+/// are never referenced are numbered after those, in the order they are defined.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct Footnotes<'a> {
+    /// Definitions in the order of their numbers: footnote `n` is at index `n - 1`.
+    pub definitions: Vec<&'a FootnoteDef>,
+    /// Labels that are referenced but never defined, in the order they are first referenced.
+    pub dangling: Vec<String>,
+    /// Labels that are defined but never referenced, in the order they are defined.
+    pub unused: Vec<String>,
+    /// Labels that are defined more than once. Only the first definition counts.
+    pub duplicates: Vec<String>,
+    numbers: HashMap<String, usize>,
+}
+
+impl<'a> Footnotes<'a> {
+    /// Number of the footnote with the label, if it is defined.
+    pub fn number(&self, label: &str) -> Option<usize> {
+        self.numbers.get(label).copied()
+    }
+
+    /// Definition of the footnote with the label.
+    pub fn definition(&self, label: &str) -> Option<&'a FootnoteDef> {
+        self.number(label).map(|number| self.definitions[number - 1])
+    }
+}
+
+/// Where footnote definitions go, see [`Doc::place_footnotes`].
+#[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq)]
+pub enum FootnotePlacement {
+    /// Where they are defined.
+    #[default]
+    Defined,
+    /// At the end of the top level section that first references them. Footnotes that are first
+    /// referenced outside of a section, or never, go to the end of the document.
+    Section,
+    /// At the end of the document.
+    End,
+}
+
+impl Doc {
+    /// Number the footnotes and find the references and definitions that don't match up.
+    pub fn resolve_footnotes(&self) -> Footnotes<'_> {
+        let walk = Walk::doc(self);
+        let mut footnotes = Footnotes::default();
+        let mut defined = HashMap::new();
+        for def in walk.definitions {
+            if defined.contains_key(&def.label) {
+                if !footnotes.duplicates.contains(&def.label) {
+                    footnotes.duplicates.push(def.label.clone());
+                }
+            } else {
+                defined.insert(def.label.clone(), def);
+                footnotes.unused.push(def.label.clone());
+            }
+        }
+        for (label, _) in walk.references {
+            if let Some(def) = defined.get(&label) {
+                if !footnotes.numbers.contains_key(&label) {
+                    footnotes.definitions.push(def);
+                    footnotes.numbers.insert(label.clone(), footnotes.definitions.len());
+                    footnotes.unused.retain(|unused| *unused != label);
+                }
+            } else if !footnotes.dangling.contains(&label) {
+                footnotes.dangling.push(label);
+            }
+        }
+        for label in &footnotes.unused {
+            footnotes.definitions.push(defined[label]);
+            footnotes.numbers.insert(label.clone(), footnotes.definitions.len());
+        }
+        footnotes
+    }
+
+    /// Move the footnote definitions to where they should be rendered.
+    /// Moved definitions are ordered by their numbers, see [`Doc::resolve_footnotes`].
+    pub fn place_footnotes(&mut self, placement: FootnotePlacement) {
+        if placement == FootnotePlacement::Defined {
+            return;
+        }
+        let walk = Walk::doc(self);
+        // where the top level items will be once the definitions between them are taken out
+        let mut first_refs = HashMap::new();
+        for (label, top) in walk.references {
+            let removed = walk.top_removed.iter().filter(|i| **i < top).count();
+            first_refs.entry(label).or_insert(top - removed);
+        }
+        let numbers = self.resolve_footnotes().numbers;
+        let mut defs = Vec::new();
+        take_footnotes_doc(self, &mut defs);
+        // duplicates keep their place after the definition that counts
+        defs.sort_by_key(|def| numbers.get(&def.label).copied().unwrap_or(usize::MAX));
+        for def in defs {
+            let target = match placement {
+                FootnotePlacement::Section => first_refs.get(&def.label)
+                    .and_then(|top| self.items.get_mut(*top)),
+                _ => None,
+            };
+            match target {
+                Some(DocItem::Section(section)) => section.items.push(SectionItem::Footnote(def)),
+                _ => self.items.push(DocItem::Footnote(def)),
+            }
+        }
+    }
+}
+
+/// Definitions and references in the order they are written, with the top level item they are in.
+#[derive(Default)]
+struct Walk<'a> {
+    top: usize,
+    definitions: Vec<&'a FootnoteDef>,
+    references: Vec<(String, usize)>,
+    top_removed: Vec<usize>,
+}
+
+impl<'a> Walk<'a> {
+    fn doc(doc: &'a Doc) -> Self {
+        let mut walk = Walk::default();
+        for (i, item) in doc.items.iter().enumerate() {
+            walk.top = i;
+            walk.doc_item(item);
+        }
+        walk
+    }
+
+    fn doc_item(&mut self, item: &'a DocItem) {
+        match item {
+            DocItem::Paragraph(par) => self.paragraph(par),
+            DocItem::Section(section) => self.section(section),
+            DocItem::Quote(quote) => self.quote(quote),
+            DocItem::Footnote(footnote) => {
+                self.top_removed.push(self.top);
+                self.footnote(footnote);
+            },
+        }
+    }
+
+    fn section(&mut self, section: &'a Section) {
+        for item in &section.items {
+            match item {
+                SectionItem::Paragraph(par) => self.paragraph(par),
+                SectionItem::Section(section) => self.section(section),
+                SectionItem::Quote(quote) => self.quote(quote),
+                SectionItem::Footnote(footnote) => self.footnote(footnote),
+            }
+        }
+    }
+
+    fn quote(&mut self, quote: &'a Quote) {
+        for item in &quote.items {
+            match item {
+                QuoteItem::Paragraph(par) => self.paragraph(par),
+                QuoteItem::Quote(quote) => self.quote(quote),
+            }
+        }
+    }
+
+    fn footnote(&mut self, footnote: &'a FootnoteDef) {
+        self.definitions.push(footnote);
+        for par in &footnote.items {
+            self.paragraph(par);
+        }
+    }
+
+    fn paragraph(&mut self, par: &'a Paragraph) {
+        for item in &par.items {
+            match item {
+                ParagraphItem::FootnoteRef(footnote_ref) => {
+                    self.references.push((footnote_ref.label.clone(), self.top));
+                },
+                ParagraphItem::List(list) => {
+                    for par in &list.items {
+                        self.paragraph(par);
+                    }
+                },
+                ParagraphItem::Table(table) => {
+                    for par in table.rows.iter().flat_map(|row| &row.items) {
+                        self.paragraph(par);
+                    }
+                },
+                _ => { },
+            }
+        }
+    }
+}
+
+/// Take an item out from between the comments: those after it move up one place.
+fn unshift_comments(comments: &mut [Comment], position: usize) {
+    for comment in comments.iter_mut().filter(|comment| comment.position > position) {
+        comment.position -= 1;
+    }
+}
+
+/// Take all footnote definitions out of the document.
+fn take_footnotes_doc(doc: &mut Doc, res: &mut Vec<FootnoteDef>) {
+    let mut kept = Vec::new();
+    for item in std::mem::take(&mut doc.items) {
+        match item {
+            DocItem::Footnote(def) => {
+                unshift_comments(&mut doc.comments, doc.navs.len() + kept.len());
+                res.push(def);
+            },
+            DocItem::Section(mut section) => {
+                take_footnotes_section(&mut section, res);
+                kept.push(DocItem::Section(section));
+            },
+            item => kept.push(item),
+        }
+    }
+    doc.items = kept;
+}
+
+fn take_footnotes_section(section: &mut Section, res: &mut Vec<FootnoteDef>) {
+    let mut kept = Vec::new();
+    for item in std::mem::take(&mut section.items) {
+        match item {
+            SectionItem::Footnote(def) => {
+                // the heading comes first
+                unshift_comments(&mut section.comments, kept.len() + 1);
+                res.push(def);
+            },
+            SectionItem::Section(mut sub) => {
+                take_footnotes_section(&mut sub, res);
+                kept.push(SectionItem::Section(sub));
+            },
+            item => kept.push(item),
+        }
+    }
+    section.items = kept;
+}
@@ -0,0 +1,329 @@
+use crate::*;
+use crate::parsing::{ NodePath, NodeStep };
+use crate::actions::deemphasise::DeEmphasise;
+
+impl Doc {
+    /// Turn sections that are quotes by convention into quotes.
+    /// Those are the sections with the `blockquote` tag, and the sections with the
+    /// `blockquote-typed` tag, of which the `blockquote-type` property or else the heading is the
+    /// kind. The heading of such a section only names it and is left out, so it has to be
+    /// `Quote` or the kind, without metadata; a section with any other heading stays as it is.
+    /// So does a section that holds sub-sections or footnotes, as quotes can not hold those, or
+    /// that holds nothing.
+    pub fn migrate_quotes(&mut self) {
+        migrate_quotes_doc(self);
+    }
+
+    /// Turn footnotes by convention into footnotes.
+    /// Sections with the `footnote-def` tag become footnote definitions, labeled by their `id`
+    /// property without its `footnote-` prefix, or else by their heading. Their heading only names
+    /// them and is left out, so it has to be the label, without metadata. A section with any
+    /// other heading or that holds anything but paragraphs, or nothing, stays as it is, and so do
+    /// the links to it.
+    /// Links with the `footnote-ref` tag to `#footnote-{label}` become references to that label;
+    /// their text is left out.
+    pub fn migrate_footnotes(&mut self) {
+        migrate_footnotes_doc(self);
+    }
+}
+
+/// Turn sections that are quotes by convention into quotes, like [`Doc::migrate_quotes`].
+/// Returns the paths of the nodes that were left out.
+pub fn migrate_quotes_doc(doc: &mut Doc) -> Vec<NodePath> {
+    let mut left_out = Vec::new();
+    let mut path = NodePath::new();
+    for (i, item) in doc.items.iter_mut().enumerate() {
+        if let DocItem::Section(section) = item {
+            path.push(NodeStep::Item(i));
+            migrate_quotes_section(section, &mut path, &mut left_out);
+            if let Some(quote) = section_to_quote(section) {
+                *item = DocItem::Quote(quote);
+                left_out.push(heading_path(&path));
+            }
+            path.pop();
+        }
+    }
+    left_out
+}
+
+fn heading_path(path: &NodePath) -> NodePath {
+    let mut heading = path.clone();
+    heading.push(NodeStep::Heading);
+    heading
+}
+
+fn migrate_quotes_section(
+    section: &mut Section, path: &mut NodePath, left_out: &mut Vec<NodePath>,
+) {
+    for (i, item) in section.items.iter_mut().enumerate() {
+        if let SectionItem::Section(sub) = item {
+            path.push(NodeStep::Item(i));
+            migrate_quotes_section(sub, path, left_out);
+            if let Some(quote) = section_to_quote(sub) {
+                *item = SectionItem::Quote(quote);
+                left_out.push(heading_path(path));
+            }
+            path.pop();
+        }
+    }
+}
+
+/// Without the heading in front every item of a section moves up one place.
+fn shift_comments(comments: Vec<Comment>) -> Vec<Comment> {
+    comments.into_iter()
+        .map(|comment| Comment { position: comment.position.saturating_sub(1), ..comment })
+        .collect()
+}
+
+/// Whether the heading holds just the text, or nothing, so leaving it out loses nothing.
+fn heading_is(heading: &Heading, text: &str) -> bool {
+    heading.tags.is_empty()
+        && heading.props.is_empty()
+        && match heading.items.as_slice() {
+            [] => true,
+            [EmOrText::Text(item)] => item == text,
+            _ => false,
+        }
+}
+
+fn section_to_quote(section: &mut Section) -> Option<Quote> {
+    let typed = section.tags.contains("blockquote-typed");
+    if !typed && !section.tags.contains("blockquote")
+        // a quote needs an item
+        || section.items.is_empty()
+        || section.items.iter()
+            .any(|item| matches!(item, SectionItem::Section(_) | SectionItem::Footnote(_)))
+    {
+        return None;
+    }
+    let heading = &section.heading;
+    let named = heading_is(heading, "Quote") || match section.props.get("blockquote-type") {
+        Some(PropVal::String(kind) | PropVal::Text(kind)) if typed => heading_is(heading, kind),
+        // the heading becomes the kind
+        _ => typed && heading.tags.is_empty() && heading.props.is_empty(),
+    };
+    if !named {
+        return None;
+    }
+    let Section { heading, items, mut tags, mut props, comments } = std::mem::take(section);
+    tags.remove("blockquote");
+    tags.remove("blockquote-typed");
+    let kind = if typed {
+        match props.remove("blockquote-type") {
+            Some(PropVal::String(kind) | PropVal::Text(kind)) => Some(kind),
+            _ => Some(heading.items.deemphasise()).filter(|kind| !kind.is_empty()),
+        }
+    } else {
+        None
+    };
+    let items = items.into_iter()
+        .filter_map(|item| match item {
+            SectionItem::Paragraph(par) => Some(QuoteItem::Paragraph(par)),
+            SectionItem::Quote(quote) => Some(QuoteItem::Quote(quote)),
+            SectionItem::Section(_) | SectionItem::Footnote(_) => None,
+        })
+        .collect();
+    Some(Quote {
+        kind,
+        items,
+        attribution: Vec::new(),
+        source: None,
+        tags,
+        props,
+        comments: shift_comments(comments),
+    })
+}
+
+/// Turn footnotes by convention into footnotes, like [`Doc::migrate_footnotes`].
+/// Returns the paths of the nodes that were left out.
+pub fn migrate_footnotes_doc(doc: &mut Doc) -> Vec<NodePath> {
+    let mut kept = HashSet::new();
+    for item in &doc.items {
+        if let DocItem::Section(section) = item {
+            kept_footnotes(section, &mut kept);
+        }
+    }
+    let mut migration = FootnoteMigration { kept, path: NodePath::new(), left_out: Vec::new() };
+    for (i, item) in doc.items.iter_mut().enumerate() {
+        migration.path.push(NodeStep::Item(i));
+        match item {
+            DocItem::Paragraph(par) => migration.paragraph(par),
+            DocItem::Section(section) => {
+                migration.section(section);
+                if let Some(footnote) = section_to_footnote(section) {
+                    *item = DocItem::Footnote(footnote);
+                    migration.left_out.push(heading_path(&migration.path));
+                }
+            },
+            DocItem::Quote(quote) => migration.quote(quote),
+            DocItem::Footnote(footnote) => migration.footnote(footnote),
+        }
+        migration.path.pop();
+    }
+    migration.left_out
+}
+
+/// Labels of the sections with the `footnote-def` tag that stay sections.
+fn kept_footnotes(section: &Section, kept: &mut HashSet<String>) {
+    if section.tags.contains("footnote-def")
+        && migrated_label(section).is_none()
+        && let Some(label) = footnote_label(section)
+    {
+        kept.insert(label);
+    }
+    for item in &section.items {
+        if let SectionItem::Section(sub) = item {
+            kept_footnotes(sub, kept);
+        }
+    }
+}
+
+struct FootnoteMigration {
+    /// Labels that links keep pointing to.
+    kept: HashSet<String>,
+    path: NodePath,
+    left_out: Vec<NodePath>,
+}
+
+impl FootnoteMigration {
+    fn section(&mut self, section: &mut Section) {
+        for (i, item) in section.items.iter_mut().enumerate() {
+            self.path.push(NodeStep::Item(i));
+            match item {
+                SectionItem::Paragraph(par) => self.paragraph(par),
+                SectionItem::Section(sub) => {
+                    self.section(sub);
+                    if let Some(footnote) = section_to_footnote(sub) {
+                        *item = SectionItem::Footnote(footnote);
+                        self.left_out.push(heading_path(&self.path));
+                    }
+                },
+                SectionItem::Quote(quote) => self.quote(quote),
+                SectionItem::Footnote(footnote) => self.footnote(footnote),
+            }
+            self.path.pop();
+        }
+    }
+
+    fn quote(&mut self, quote: &mut Quote) {
+        for (i, item) in quote.items.iter_mut().enumerate() {
+            self.path.push(NodeStep::Item(i));
+            match item {
+                QuoteItem::Paragraph(par) => self.paragraph(par),
+                QuoteItem::Quote(quote) => self.quote(quote),
+            }
+            self.path.pop();
+        }
+    }
+
+    fn footnote(&mut self, footnote: &mut FootnoteDef) {
+        for (i, par) in footnote.items.iter_mut().enumerate() {
+            self.path.push(NodeStep::Item(i));
+            self.paragraph(par);
+            self.path.pop();
+        }
+    }
+
+    fn paragraph(&mut self, par: &mut Paragraph) {
+        for (i, item) in par.items.iter_mut().enumerate() {
+            self.path.push(NodeStep::Item(i));
+            match item {
+                ParagraphItem::Link(link) => if let Some(footnote_ref) = self.link(link) {
+                    for j in 0..link.items.len() {
+                        let mut text = self.path.clone();
+                        text.push(NodeStep::Item(j));
+                        self.left_out.push(text);
+                    }
+                    *item = ParagraphItem::FootnoteRef(footnote_ref);
+                },
+                ParagraphItem::List(list) => {
+                    for (j, par) in list.items.iter_mut().enumerate() {
+                        self.path.push(NodeStep::Item(j));
+                        self.paragraph(par);
+                        self.path.pop();
+                    }
+                },
+                ParagraphItem::Table(table) => {
+                    for (r, row) in table.rows.iter_mut().enumerate() {
+                        self.path.push(NodeStep::Row(r));
+                        for (c, par) in row.items.iter_mut().enumerate() {
+                            self.path.push(NodeStep::Item(c));
+                            self.paragraph(par);
+                            self.path.pop();
+                        }
+                        self.path.pop();
+                    }
+                },
+                _ => { },
+            }
+            self.path.pop();
+        }
+    }
+
+    fn link(&self, link: &mut Link) -> Option<FootnoteRef> {
+        if !link.tags.contains("footnote-ref") {
+            return None;
+        }
+        let label = link.url.strip_prefix("#footnote-")
+            .filter(|label| !label.is_empty() && !self.kept.contains(*label))?;
+        let mut tags = std::mem::take(&mut link.tags);
+        tags.remove("footnote-ref");
+        Some(FootnoteRef {
+            label: label.to_string(),
+            tags,
+            props: std::mem::take(&mut link.props),
+        })
+    }
+}
+
+/// Whether the section holds at least one paragraph and nothing else.
+fn is_paragraphs(section: &Section) -> bool {
+    !section.items.is_empty()
+        && section.items.iter().all(|item| matches!(item, SectionItem::Paragraph(_)))
+}
+
+/// The `id` property without its `footnote-` prefix, or else the heading.
+fn footnote_label(section: &Section) -> Option<String> {
+    let label = match section.props.get("id") {
+        Some(PropVal::String(id) | PropVal::Text(id)) => {
+            id.strip_prefix("footnote-").unwrap_or(id).to_string()
+        },
+        _ => section.heading.items.deemphasise(),
+    };
+    Some(label).filter(|label| !label.is_empty())
+}
+
+/// The label of the section when it becomes a footnote definition.
+fn migrated_label(section: &Section) -> Option<String> {
+    if !section.tags.contains("footnote-def") || !is_paragraphs(section) {
+        return None;
+    }
+    let label = footnote_label(section)?;
+    let heading = &section.heading;
+    let named = match section.props.get("id") {
+        Some(PropVal::String(_) | PropVal::Text(_)) => heading_is(heading, &label),
+        // the heading is the label
+        _ => heading.tags.is_empty() && heading.props.is_empty(),
+    };
+    named.then_some(label)
+}
+
+fn section_to_footnote(section: &mut Section) -> Option<FootnoteDef> {
+    let label = migrated_label(section)?;
+    let Section { items, mut tags, mut props, comments, .. } = std::mem::take(section);
+    tags.remove("footnote-def");
+    props.remove("id");
+    let items = items.into_iter()
+        .filter_map(|item| match item {
+            SectionItem::Paragraph(par) => Some(par),
+            _ => None,
+        })
+        .collect();
+    Some(FootnoteDef {
+        label,
+        items,
+        tags,
+        props,
+        comments: shift_comments(comments),
+    })
+}
@@ -5,3 +5,6 @@ pub mod toc;
 pub mod deemphasise;
 pub mod query;
 pub mod collect;
+pub mod migrate;
+pub mod footnotes;
+pub mod tabulate;
@@ -56,6 +56,8 @@ impl PruneIncodoc for DocItem {
         match self {
             DocItem::Paragraph(par) => par.prune_errors(),
             DocItem::Section(section) => section.prune_errors(),
+            DocItem::Quote(quote) => quote.prune_errors(),
+            DocItem::Footnote(footnote) => footnote.prune_errors(),
         }
     }
 
@@ -63,6 +65,8 @@ impl PruneIncodoc for DocItem {
         match self {
             DocItem::Paragraph(par) => par.prune_contentless(),
             DocItem::Section(section) => section.prune_contentless(),
+            DocItem::Quote(quote) => quote.prune_contentless(),
+            DocItem::Footnote(footnote) => footnote.prune_contentless(),
         }
     }
 
@@ -70,6 +74,8 @@ impl PruneIncodoc for DocItem {
         match self {
             DocItem::Paragraph(par) => par.is_contentless(),
             DocItem::Section(section) => section.is_contentless(),
+            DocItem::Quote(quote) => quote.is_contentless(),
+            DocItem::Footnote(footnote) => footnote.is_contentless(),
         }
     }
 }
@@ -166,6 +172,8 @@ impl PruneIncodoc for SectionItem {
         match self {
             Self::Paragraph(par) => par.prune_errors(),
             Self::Section(section) => section.prune_errors(),
+            Self::Quote(quote) => quote.prune_errors(),
+            Self::Footnote(footnote) => footnote.prune_errors(),
         }
     }
 
@@ -173,6 +181,8 @@ impl PruneIncodoc for SectionItem {
         match self {
             Self::Paragraph(par) => par.prune_contentless(),
             Self::Section(section) => section.prune_contentless(),
+            Self::Quote(quote) => quote.prune_contentless(),
+            Self::Footnote(footnote) => footnote.prune_contentless(),
         }
     }
 
@@ -180,10 +190,151 @@ impl PruneIncodoc for SectionItem {
         match self {
             Self::Paragraph(par) => par.is_contentless(),
             Self::Section(section) => section.is_contentless(),
+            Self::Quote(quote) => quote.is_contentless(),
+            Self::Footnote(footnote) => footnote.is_contentless(),
         }
     }
 }
 
+impl PruneIncodoc for Option<String> {
+    fn prune_errors(&mut self) { }
+
+    fn prune_contentless(&mut self) {
+        if self.as_ref().is_some_and(|string| string.trim().is_empty()) {
+            *self = None;
+        }
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl PruneIncodoc for Option<Link> {
+    fn prune_errors(&mut self) {
+        if let Some(link) = self {
+            link.prune_errors();
+        }
+    }
+
+    fn prune_contentless(&mut self) {
+        if let Some(link) = self {
+            link.prune_contentless();
+        }
+        if self.as_ref().is_some_and(Link::is_contentless) {
+            *self = None;
+        }
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl PruneIncodoc for Quote {
+    fn prune_errors(&mut self) {
+        self.props.prune_errors();
+        self.items.prune_errors();
+        self.attribution.prune_errors();
+        self.source.prune_errors();
+    }
+
+    fn prune_contentless(&mut self) {
+        self.kind.prune_contentless();
+        self.items.prune_contentless();
+        self.items.retain(|item| !item.is_contentless());
+        self.attribution.prune_contentless();
+        self.attribution.retain(|item| !item.is_contentless());
+        self.source.prune_contentless();
+        self.tags.prune_contentless();
+        self.props.prune_contentless();
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.items.is_contentless()
+    }
+}
+
+impl PruneIncodoc for QuoteItem {
+    fn prune_errors(&mut self) {
+        match self {
+            Self::Paragraph(par) => par.prune_errors(),
+            Self::Quote(quote) => quote.prune_errors(),
+        }
+    }
+
+    fn prune_contentless(&mut self) {
+        match self {
+            Self::Paragraph(par) => par.prune_contentless(),
+            Self::Quote(quote) => quote.prune_contentless(),
+        }
+    }
+
+    fn is_contentless(&self) -> bool {
+        match self {
+            Self::Paragraph(par) => par.is_contentless(),
+            Self::Quote(quote) => quote.is_contentless(),
+        }
+    }
+}
+
+impl PruneIncodoc for InlineQuote {
+    fn prune_errors(&mut self) {
+        self.props.prune_errors();
+        self.items.prune_errors();
+        self.attribution.prune_errors();
+        self.source.prune_errors();
+    }
+
+    fn prune_contentless(&mut self) {
+        self.kind.prune_contentless();
+        self.items.prune_contentless();
+        self.items.retain(|item| !item.is_contentless());
+        self.attribution.prune_contentless();
+        self.attribution.retain(|item| !item.is_contentless());
+        self.source.prune_contentless();
+        self.tags.prune_contentless();
+        self.props.prune_contentless();
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.items.is_contentless()
+    }
+}
+
+impl PruneIncodoc for FootnoteDef {
+    fn prune_errors(&mut self) {
+        self.props.prune_errors();
+        self.items.prune_errors();
+    }
+
+    fn prune_contentless(&mut self) {
+        self.items.prune_contentless();
+        self.items.retain(|item| !item.is_contentless());
+        self.tags.prune_contentless();
+        self.props.prune_contentless();
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.label.trim().is_empty() || self.items.is_contentless()
+    }
+}
+
+impl PruneIncodoc for FootnoteRef {
+    fn prune_errors(&mut self) {
+        self.props.prune_errors();
+    }
+
+    fn prune_contentless(&mut self) {
+        self.tags.prune_contentless();
+        self.props.prune_contentless();
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.label.trim().is_empty()
+    }
+}
+
 impl PruneIncodoc for Heading {
     fn prune_errors(&mut self) {
         self.props.prune_errors();
@@ -250,6 +401,9 @@ impl PruneIncodoc for ParagraphItem {
             Self::Code(Ok(code)) => code.prune_errors(),
             Self::Link(link) => link.prune_errors(),
             Self::List(list) => list.prune_errors(),
+            Self::Quote(quote) => quote.prune_errors(),
+            Self::FootnoteRef(footnote_ref) => footnote_ref.prune_errors(),
+            Self::Data(data) => data.prune_errors(),
             _ => (),
         }
     }
@@ -264,6 +418,9 @@ impl PruneIncodoc for ParagraphItem {
             Self::Link(link) => link.prune_contentless(),
             Self::List(list) => list.prune_contentless(),
             Self::Table(table) => table.prune_contentless(),
+            Self::Quote(quote) => quote.prune_contentless(),
+            Self::FootnoteRef(footnote_ref) => footnote_ref.prune_contentless(),
+            Self::Data(data) => data.prune_contentless(),
         }
     }
 
@@ -277,6 +434,9 @@ impl PruneIncodoc for ParagraphItem {
             Self::Link(link) => link.is_contentless(),
             Self::List(list) => list.is_contentless(),
             Self::Table(table) => table.is_contentless(),
+            Self::Quote(quote) => quote.is_contentless(),
+            Self::FootnoteRef(footnote_ref) => footnote_ref.is_contentless(),
+            Self::Data(data) => data.is_contentless(),
         }
     }
 }
@@ -333,6 +493,22 @@ impl PruneIncodoc for Table {
     }
 }
 
+impl PruneIncodoc for Data {
+    fn prune_errors(&mut self) {
+        self.props.prune_errors();
+    }
+
+    fn prune_contentless(&mut self) {
+        self.rows.retain(|row| !row.is_empty());
+        self.tags.prune_contentless();
+        self.props.prune_contentless();
+    }
+
+    fn is_contentless(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
 impl PruneIncodoc for TableRow {
     fn prune_errors(&mut self) {
         self.props.prune_errors();
@@ -130,30 +130,26 @@ macro_rules! impl_squash_text_mtext_em {
     }
 }
 
-macro_rules! impl_squash_text_em {
-    ($enumname:ident) => {
-        pub fn squash(&mut self) {
-            let mut keep = Vec::new();
-            let mut ltext: Option<&mut String> = None;
-            let mut lem: Option<&mut Emphasis> = None;
-
-            // squash items into earlier item if possible
-            for item in &mut self.items {
-                match item {
-                    $enumname::Text(text) => {
-                        squash_alg_text_case(&mut ltext, &mut None, &mut lem, text, &mut keep);
-                    },
-                    $enumname::Em(em) => {
-                        squash_alg_em_case(&mut ltext, &mut None, &mut lem, em, &mut keep);
-                    },
-                }
-            }
+fn squash_em_or_text(items: &mut Vec<EmOrText>) {
+    let mut keep = Vec::new();
+    let mut ltext: Option<&mut String> = None;
+    let mut lem: Option<&mut Emphasis> = None;
 
-            // remove parts that have been squashed
-            let mut kiter = keep.into_iter();
-            self.items.retain(|_| kiter.next().unwrap());
+    // squash items into earlier item if possible
+    for item in items.iter_mut() {
+        match item {
+            EmOrText::Text(text) => {
+                squash_alg_text_case(&mut ltext, &mut None, &mut lem, text, &mut keep);
+            },
+            EmOrText::Em(em) => {
+                squash_alg_em_case(&mut ltext, &mut None, &mut lem, em, &mut keep);
+            },
         }
     }
+
+    // remove parts that have been squashed
+    let mut kiter = keep.into_iter();
+    items.retain(|_| kiter.next().unwrap());
 }
 
 impl Doc {
@@ -169,6 +165,8 @@ impl DocItem {
         match self {
             Self::Paragraph(par) => par.squash(),
             Self::Section(section) => section.squash(),
+            Self::Quote(quote) => quote.squash(),
+            Self::Footnote(footnote) => footnote.squash(),
         }
     }
 }
@@ -186,12 +184,55 @@ impl SectionItem {
         match self {
             Self::Paragraph(par) => par.squash(),
             Self::Section(section) => section.squash(),
+            Self::Quote(quote) => quote.squash(),
+            Self::Footnote(footnote) => footnote.squash(),
+        }
+    }
+}
+
+impl Quote {
+    pub fn squash(&mut self) {
+        for item in &mut self.items {
+            item.squash();
+        }
+        squash_em_or_text(&mut self.attribution);
+        if let Some(source) = &mut self.source {
+            source.squash();
+        }
+    }
+}
+
+impl QuoteItem {
+    pub fn squash(&mut self) {
+        match self {
+            Self::Paragraph(par) => par.squash(),
+            Self::Quote(quote) => quote.squash(),
+        }
+    }
+}
+
+impl InlineQuote {
+    pub fn squash(&mut self) {
+        squash_em_or_text(&mut self.items);
+        squash_em_or_text(&mut self.attribution);
+        if let Some(source) = &mut self.source {
+            source.squash();
+        }
+    }
+}
+
+impl FootnoteDef {
+    pub fn squash(&mut self) {
+        for par in &mut self.items {
+            par.squash();
         }
     }
 }
 
 impl Heading {
-    impl_squash_text_em!(EmOrText);
+    pub fn squash(&mut self) {
+        squash_em_or_text(&mut self.items);
+    }
 }
 
 impl Paragraph {
@@ -204,6 +245,7 @@ impl ParagraphItem {
             Self::Link(link) => link.squash(),
             Self::List(list) => list.squash(),
             Self::Table(table) => table.squash(),
+            Self::Quote(quote) => quote.squash(),
             _ => { },
         }
     }
@@ -234,6 +276,8 @@ impl TableRow {
 }
 
 impl Link {
-    impl_squash_text_em!(EmOrText);
+    pub fn squash(&mut self) {
+        squash_em_or_text(&mut self.items);
+    }
 }
 
@@ -0,0 +1,50 @@
+use crate::*;
+
+use std::fmt;
+
+/// Text of the value, as it is shown in a table.
+impl fmt::Display for DataCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataCell::Text(text) => write!(f, "{text}"),
+            DataCell::Int(int) => write!(f, "{int}"),
+            DataCell::Float(float) => write!(f, "{float}"),
+            DataCell::Bool(bool) => write!(f, "{bool}"),
+        }
+    }
+}
+
+impl Data {
+    /// Table that shows the dataset, for consumers that can't show it the way its `type-hint`
+    /// property says. The columns become a header row and every value becomes a paragraph with its
+    /// text. The `type-hint` property is left out, the other metadata and comments are kept.
+    pub fn to_table(&self) -> Table {
+        let cell = |text: String| Paragraph {
+            items: vec![ParagraphItem::Text(text)],
+            ..Default::default()
+        };
+        let mut rows = Vec::new();
+        if !self.columns.is_empty() {
+            rows.push(TableRow {
+                items: self.columns.iter().cloned().map(cell).collect(),
+                is_header: true,
+                ..Default::default()
+            });
+        }
+        let header = rows.len();
+        rows.extend(self.rows.iter().map(|row| TableRow {
+            items: row.iter().map(|value| cell(value.to_string())).collect(),
+            ..Default::default()
+        }));
+        let mut props = self.props.clone();
+        props.remove("type-hint");
+        Table {
+            rows,
+            tags: self.tags.clone(),
+            props,
+            comments: self.comments.iter()
+                .map(|comment| Comment { position: comment.position + header, ..comment.clone() })
+                .collect(),
+        }
+    }
+}
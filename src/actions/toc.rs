@@ -1,4 +1,5 @@
 use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
 
 /// A recursive table of contents.
 #[derive(Clone, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -22,8 +23,10 @@ pub enum TableOfContentsItemType {
     Nav,
     Quote,
     FootnoteDefinition,
+    FootnoteReference,
     List,
     Table,
+    Data,
     CodeBlock,
     Link,
     Emphasis,
@@ -108,6 +111,14 @@ impl GetTableOfContents for Doc {
                     &mut children,
                     section.get_table_of_contents(filter)
                 ),
+                DocItem::Quote(quote) => push_toci(
+                    &mut children,
+                    quote.get_table_of_contents(filter)
+                ),
+                DocItem::Footnote(footnote) => push_toci(
+                    &mut children,
+                    footnote.get_table_of_contents(filter)
+                ),
             }
         }
         if children.is_empty()
@@ -159,6 +170,14 @@ impl GetTableOfContents for Section {
                     &mut children,
                     section.get_table_of_contents(filter)
                 ),
+                SectionItem::Quote(quote) => push_toci(
+                    &mut children,
+                    quote.get_table_of_contents(filter)
+                ),
+                SectionItem::Footnote(footnote) => push_toci(
+                    &mut children,
+                    footnote.get_table_of_contents(filter)
+                ),
             }
         }
         if children.is_empty()
@@ -185,6 +204,140 @@ impl GetTableOfContents for Section {
     }
 }
 
+impl GetTableOfContents for Quote {
+    fn get_table_of_contents(
+        &self,
+        filter: &Option<(HashSet<TableOfContentsItemType>, TableOfContentsFilterType)>,
+    ) -> Option<TableOfContentsItem> {
+        if let Some((filter, ftype)) = filter
+            && !filter.contains(&TableOfContentsItemType::Quote)
+            && *ftype == TableOfContentsFilterType::HardStop
+        {
+            return None;
+        }
+        let mut children = Vec::new();
+        for item in &self.items {
+            match item {
+                QuoteItem::Paragraph(par) => push_toci(
+                    &mut children,
+                    par.get_table_of_contents(filter)
+                ),
+                QuoteItem::Quote(quote) => push_toci(
+                    &mut children,
+                    quote.get_table_of_contents(filter)
+                ),
+            }
+        }
+        if children.is_empty()
+            && let Some((filter, ftype)) = filter
+            && !filter.contains(&TableOfContentsItemType::Quote)
+            && *ftype == TableOfContentsFilterType::IncludeWithChildren
+        {
+            return None;
+        }
+        // named by its kind, or else by who is quoted
+        let mut title = String::from("Quote");
+        let name = match &self.kind {
+            Some(kind) => kind.clone(),
+            None => self.attribution.deemphasise(),
+        };
+        if !name.is_empty() {
+            title += ": ";
+            title += &name;
+        }
+        let link = match self.props.get("id") {
+            Some(PropVal::String(id)) => id_to_link(id),
+            _ => String::new(),
+        };
+        Some(TableOfContentsItem {
+            title,
+            link,
+            item_type: TableOfContentsItemType::Quote,
+            children,
+        })
+    }
+}
+
+impl GetTableOfContents for InlineQuote {
+    fn get_table_of_contents(
+        &self,
+        filter: &Option<(HashSet<TableOfContentsItemType>, TableOfContentsFilterType)>,
+    ) -> Option<TableOfContentsItem> {
+        if let Some((filter, _)) = filter && !filter.contains(&TableOfContentsItemType::Quote) {
+            return None;
+        }
+        if let Some(PropVal::String(id)) = self.props.get("id") {
+            Some(TableOfContentsItem {
+                title: self.items.deemphasise(),
+                link: id_to_link(id),
+                item_type: TableOfContentsItemType::Quote,
+                children: vec![],
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl GetTableOfContents for FootnoteDef {
+    fn get_table_of_contents(
+        &self,
+        filter: &Option<(HashSet<TableOfContentsItemType>, TableOfContentsFilterType)>,
+    ) -> Option<TableOfContentsItem> {
+        if let Some((filter, ftype)) = filter
+            && !filter.contains(&TableOfContentsItemType::FootnoteDefinition)
+            && *ftype == TableOfContentsFilterType::HardStop
+        {
+            return None;
+        }
+        let mut children = Vec::new();
+        for par in &self.items {
+            push_toci(&mut children, par.get_table_of_contents(filter));
+        }
+        if children.is_empty()
+            && let Some((filter, ftype)) = filter
+            && !filter.contains(&TableOfContentsItemType::FootnoteDefinition)
+            && *ftype == TableOfContentsFilterType::IncludeWithChildren
+        {
+            return None;
+        }
+        // the same link as footnotes by convention, so references keep working
+        let link = match self.props.get("id") {
+            Some(PropVal::String(id)) => id_to_link(id),
+            _ => format!("#footnote-{}", self.label),
+        };
+        Some(TableOfContentsItem {
+            title: format!("Footnote definition: {}", self.label),
+            link,
+            item_type: TableOfContentsItemType::FootnoteDefinition,
+            children,
+        })
+    }
+}
+
+impl GetTableOfContents for FootnoteRef {
+    fn get_table_of_contents(
+        &self,
+        filter: &Option<(HashSet<TableOfContentsItemType>, TableOfContentsFilterType)>,
+    ) -> Option<TableOfContentsItem> {
+        if let Some((filter, _)) = filter
+            && !filter.contains(&TableOfContentsItemType::FootnoteReference)
+        {
+            return None;
+        }
+        if let Some(PropVal::String(id)) = self.props.get("id") {
+            Some(TableOfContentsItem {
+                title: self.label.to_string(),
+                link: id_to_link(id),
+                item_type: TableOfContentsItemType::FootnoteReference,
+                children: vec![],
+            })
+        } else {
+            None
+        }
+    }
+}
+
 impl GetTableOfContents for Paragraph {
     fn get_table_of_contents(
         &self,
@@ -221,6 +374,18 @@ impl GetTableOfContents for Paragraph {
                     &mut children,
                     table.get_table_of_contents(filter)
                 ),
+                ParagraphItem::Quote(quote) => push_toci(
+                    &mut children,
+                    quote.get_table_of_contents(filter)
+                ),
+                ParagraphItem::FootnoteRef(footnote_ref) => push_toci(
+                    &mut children,
+                    footnote_ref.get_table_of_contents(filter)
+                ),
+                ParagraphItem::Data(data) => push_toci(
+                    &mut children,
+                    data.get_table_of_contents(filter)
+                ),
             }
         }
         if children.is_empty()
@@ -391,6 +556,29 @@ impl GetTableOfContents for Result<CodeBlock, CodeIdentError> {
     }
 }
 
+impl GetTableOfContents for Data {
+    fn get_table_of_contents(
+        &self,
+        filter: &Option<(HashSet<TableOfContentsItemType>, TableOfContentsFilterType)>,
+    ) -> Option<TableOfContentsItem> {
+        if let Some((filter, _)) = filter
+            && !filter.contains(&TableOfContentsItemType::Data)
+        {
+            return None;
+        }
+        if let Some(PropVal::String(id)) = self.props.get("id") {
+            Some(TableOfContentsItem {
+                title: id.to_string(),
+                link: id_to_link(id),
+                item_type: TableOfContentsItemType::Data,
+                children: vec![],
+            })
+        } else {
+            None
+        }
+    }
+}
+
 impl GetTableOfContents for Table {
     fn get_table_of_contents(
         &self,
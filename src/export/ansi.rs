@@ -0,0 +1,696 @@
+//! Text for terminals, wrapped to a width and styled with ANSI escape codes.
+//!
+//! How things look is up to the reader: a [`StyleProfile`] decides the styles of headings,
+//! emphasis, links and code, the markers of lists and how wide the text gets.
+//! A profile can be read from the properties of an incodoc file:
+//!
+//! ``` incodoc
+//! props {
+//!     ("width", 72),
+//!     ("colours", "on"),
+//!     ("heading", "bold underline magenta"),
+//!     ("strong-emphasis", "bold red"),
+//!     ("bullet", "-"),
+//! },
+//! ```
+//!
+//! Styles are written as words: `bold`, `dim`, `italic`, `underline`, `strikethrough`, a colour
+//! like `red` or `bright-red`, or `plain` for no style at all.
+//! With colours off no escape codes are written at all and headings are underlined with `=` and
+//! `-` instead.
+//! Block quotes have the quote marker of the profile in front of every line, starting with their
+//! kind and ending with who is quoted and the source. Inline quotes go between quotation marks.
+//! Footnote references become `[label]`; footnote definitions are written where they are, with
+//! `[label]` in front like the marker of a list item.
+//! Control characters in the text, other than tabs and line breaks, are written as `�`, so the
+//! text can not send escape codes of its own to the terminal.
+
+use crate::*;
+use crate::parsing::{ parse, ParseError };
+use crate::export::plain::quote_name;
+
+use std::fmt;
+
+/// One of the colours terminals have a palette for.
+#[derive(Clone, Copy, Hash, Debug, Eq, PartialEq)]
+pub enum Colour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+const COLOURS: [(&str, Colour); 16] = [
+    ("black", Colour::Black),
+    ("red", Colour::Red),
+    ("green", Colour::Green),
+    ("yellow", Colour::Yellow),
+    ("blue", Colour::Blue),
+    ("magenta", Colour::Magenta),
+    ("cyan", Colour::Cyan),
+    ("white", Colour::White),
+    ("bright-black", Colour::BrightBlack),
+    ("bright-red", Colour::BrightRed),
+    ("bright-green", Colour::BrightGreen),
+    ("bright-yellow", Colour::BrightYellow),
+    ("bright-blue", Colour::BrightBlue),
+    ("bright-magenta", Colour::BrightMagenta),
+    ("bright-cyan", Colour::BrightCyan),
+    ("bright-white", Colour::BrightWhite),
+];
+
+/// How a piece of text looks.
+#[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq)]
+pub struct Style {
+    pub colour: Option<Colour>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+}
+
+impl Style {
+    /// Read a style from words like `bold red`.
+    pub fn from_words(words: &str) -> Option<Self> {
+        let mut style = Self::default();
+        for word in words.split_whitespace() {
+            match word {
+                "plain" => {},
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "strikethrough" => style.strikethrough = true,
+                word => {
+                    let (_, colour) = COLOURS.iter().find(|(name, _)| *name == word)?;
+                    style.colour = Some(*colour);
+                },
+            }
+        }
+        Some(style)
+    }
+
+    /// This style with the other one on top.
+    fn with(self, other: Self) -> Self {
+        Self {
+            colour: other.colour.or(self.colour),
+            bold: self.bold || other.bold,
+            dim: self.dim || other.dim,
+            italic: self.italic || other.italic,
+            underline: self.underline || other.underline,
+            strikethrough: self.strikethrough || other.strikethrough,
+        }
+    }
+
+    fn codes(&self) -> Vec<u8> {
+        let mut codes = Vec::new();
+        for (on, code) in [
+            (self.bold, 1), (self.dim, 2), (self.italic, 3), (self.underline, 4),
+            (self.strikethrough, 9),
+        ] {
+            if on { codes.push(code); }
+        }
+        if let Some(colour) = self.colour {
+            let index = COLOURS.iter().position(|(_, c)| *c == colour).unwrap_or(0) as u8;
+            codes.push(if index < 8 { 30 + index } else { 90 + index - 8 });
+        }
+        codes
+    }
+}
+
+/// The choices of the reader on how to render a document in a terminal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StyleProfile {
+    /// Width in characters that text is wrapped to.
+    pub width: usize,
+    /// Write escape codes for the styles, plain text otherwise.
+    pub colours: bool,
+    /// Spaces that code blocks and sub-navigations are indented with.
+    pub indent: usize,
+    /// Style of headings of top level sections.
+    pub heading: Style,
+    /// Style of headings of deeper sections.
+    pub sub_heading: Style,
+    /// Styles of light, medium and strong emphasis.
+    pub emphasis: [Style; 3],
+    /// Styles of light, medium and strong deemphasis.
+    pub deemphasis: [Style; 3],
+    pub link: Style,
+    pub code: Style,
+    pub table_header: Style,
+    /// Marker of items in lists where each item is denoted identically.
+    pub bullet: String,
+    /// Markers of items in checked lists.
+    pub checked: String,
+    pub unchecked: String,
+    /// Marker in front of every line of a block quote.
+    pub quote: String,
+}
+
+impl Default for StyleProfile {
+    fn default() -> Self {
+        let style = |words| Style::from_words(words).unwrap_or_default();
+        Self {
+            width: 80,
+            colours: true,
+            indent: 4,
+            heading: style("bold underline"),
+            sub_heading: style("bold"),
+            emphasis: [style("italic"), style("bold"), style("bold underline")],
+            deemphasis: [style("dim"), style("dim italic"), style("dim strikethrough")],
+            link: style("underline blue"),
+            code: style("cyan"),
+            table_header: style("bold"),
+            bullet: "•".to_string(),
+            checked: "[x]".to_string(),
+            unchecked: "[ ]".to_string(),
+            quote: "│".to_string(),
+        }
+    }
+}
+
+/// Error when a style profile could not be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProfileError {
+    /// The file could not be parsed.
+    Parse(ParseError),
+    /// There is no setting with this name.
+    UnknownKey(String),
+    /// The value of this setting is not of the right kind.
+    InvalidValue(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::UnknownKey(key) => write!(f, "unknown setting: {key}"),
+            Self::InvalidValue(key) => write!(f, "invalid value for setting: {key}"),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError { }
+
+impl StyleProfile {
+    /// Read a profile from properties, starting from the default profile.
+    pub fn from_props(props: &Props) -> Result<Self, ProfileError> {
+        let mut profile = Self::default();
+        let mut props = props.iter().collect::<Vec<_>>();
+        props.sort_by_key(|(key, _)| *key);
+        for (key, val) in props {
+            let invalid = || ProfileError::InvalidValue(key.clone());
+            let string = match val {
+                PropVal::String(string) | PropVal::Text(string) => Some(string.as_str()),
+                _ => None,
+            };
+            let int = match val {
+                PropVal::Int(int) => usize::try_from(*int).ok(),
+                _ => None,
+            };
+            let style = string.and_then(Style::from_words);
+            match key.as_str() {
+                "width" => profile.width = int.filter(|w| *w > 0).ok_or_else(invalid)?,
+                "indent" => profile.indent = int.ok_or_else(invalid)?,
+                "colours" => profile.colours = match string {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => return Err(invalid()),
+                },
+                "bullet" => profile.bullet = string.ok_or_else(invalid)?.to_string(),
+                "checked" => profile.checked = string.ok_or_else(invalid)?.to_string(),
+                "unchecked" => profile.unchecked = string.ok_or_else(invalid)?.to_string(),
+                "quote" => profile.quote = string.ok_or_else(invalid)?.to_string(),
+                key => {
+                    let slot = match key {
+                        "heading" => &mut profile.heading,
+                        "sub-heading" => &mut profile.sub_heading,
+                        "light-emphasis" => &mut profile.emphasis[0],
+                        "medium-emphasis" => &mut profile.emphasis[1],
+                        "strong-emphasis" => &mut profile.emphasis[2],
+                        "light-deemphasis" => &mut profile.deemphasis[0],
+                        "medium-deemphasis" => &mut profile.deemphasis[1],
+                        "strong-deemphasis" => &mut profile.deemphasis[2],
+                        "link" => &mut profile.link,
+                        "code" => &mut profile.code,
+                        "table-header" => &mut profile.table_header,
+                        key => return Err(ProfileError::UnknownKey(key.to_string())),
+                    };
+                    *slot = style.ok_or_else(invalid)?;
+                },
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Read a profile from the properties of an incodoc file.
+    pub fn from_incodoc(input: &str) -> Result<Self, ProfileError> {
+        let doc = parse(input).map_err(ProfileError::Parse)?;
+        Self::from_props(&doc.props)
+    }
+}
+
+/// Line of output together with how wide it looks.
+#[derive(Clone, Default)]
+struct Line {
+    text: String,
+    width: usize,
+    /// Style the escape codes written so far leave the terminal in.
+    style: Style,
+}
+
+type Block = Vec<Line>;
+
+enum Token {
+    /// Pieces of a word, together with the style of the whitespace before it.
+    Word(Vec<(Style, String)>, Style),
+    Break,
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+struct Renderer<'a> {
+    profile: &'a StyleProfile,
+}
+
+impl Renderer<'_> {
+    /// Add text to a line, only writing escape codes where the style changes.
+    fn styled(&self, style: Style, text: &str, line: &mut Line) {
+        if self.profile.colours && style != line.style {
+            if line.style != Style::default() {
+                line.text.push_str("\x1b[0m");
+            }
+            let codes = style.codes().iter().map(u8::to_string).collect::<Vec<_>>().join(";");
+            if !codes.is_empty() {
+                line.text.push_str(&format!("\x1b[{codes}m"));
+            }
+            line.style = style;
+        }
+        let text = text.chars()
+            .map(|c| if c.is_control() && c != '\t' { '\u{fffd}' } else { c })
+            .collect::<String>();
+        line.text.push_str(&text);
+        line.width += width(&text);
+    }
+
+    /// Reset the style at the end of a line.
+    fn closed(&self, mut line: Line) -> Line {
+        self.styled(Style::default(), "", &mut line);
+        line
+    }
+
+    fn indented(&self, block: Block, first: &str, rest: usize) -> Block {
+        let rest = " ".repeat(rest);
+        block.into_iter().enumerate().map(|(i, line)| {
+            let prefix = if i == 0 { first } else { &rest };
+            if line.width == 0 && i > 0 { return line; }
+            Line {
+                text: format!("{prefix}{}", line.text),
+                width: width(prefix) + line.width,
+                style: line.style,
+            }
+        }).collect()
+    }
+
+    /// Wrap styled text greedily, breaking only at whitespace.
+    fn wrap(&self, spans: &[(Style, String)], max: usize) -> Block {
+        let mut tokens = Vec::new();
+        let mut word: Vec<(Style, String)> = Vec::new();
+        let mut space = Style::default();
+        for (style, text) in spans {
+            for c in text.chars() {
+                if c.is_whitespace() {
+                    if !word.is_empty() {
+                        tokens.push(Token::Word(std::mem::take(&mut word), space));
+                    }
+                    space = *style;
+                    if c == '\n' {
+                        tokens.push(Token::Break);
+                    }
+                } else if let Some((last, piece)) = word.last_mut() && last == style {
+                    piece.push(c);
+                } else {
+                    word.push((*style, c.to_string()));
+                }
+            }
+        }
+        if !word.is_empty() {
+            tokens.push(Token::Word(word, space));
+        }
+        let mut lines = vec![Line::default()];
+        for token in tokens {
+            let Token::Word(word, space) = token else {
+                lines.push(Line::default());
+                continue;
+            };
+            let word_width = word.iter().map(|(_, piece)| width(piece)).sum::<usize>();
+            let line = lines.last_mut().expect("there is always a line");
+            if line.width > 0 && line.width + 1 + word_width > max {
+                lines.push(Line::default());
+            }
+            let line = lines.last_mut().expect("there is always a line");
+            if line.width > 0 {
+                self.styled(space, " ", line);
+            }
+            for (style, piece) in word {
+                self.styled(style, &piece, line);
+            }
+        }
+        lines.into_iter().map(|line| self.closed(line)).collect()
+    }
+
+    fn emphasis_style(&self, em: &Emphasis) -> Style {
+        let styles = match em.etype {
+            EmType::Emphasis => &self.profile.emphasis,
+            EmType::Deemphasis => &self.profile.deemphasis,
+        };
+        styles[em.strength as usize]
+    }
+
+    fn em_or_text_spans(&self, items: &[EmOrText], base: Style, spans: &mut Vec<(Style, String)>) {
+        for item in items {
+            match item {
+                EmOrText::Text(text) => spans.push((base, text.clone())),
+                EmOrText::Em(em) => {
+                    spans.push((base.with(self.emphasis_style(em)), em.text.clone()));
+                },
+            }
+        }
+    }
+
+    fn link_spans(&self, link: &Link, base: Style, spans: &mut Vec<(Style, String)>) {
+        let style = base.with(self.profile.link);
+        self.em_or_text_spans(&link.items, style, spans);
+        if link.items.is_empty() {
+            spans.push((style, link.url.clone()));
+        } else {
+            spans.push((base, format!(" <{}>", link.url)));
+        }
+    }
+
+    /// Who is quoted and the source, separated by a comma.
+    fn credit_spans(
+        &self, attribution: &[EmOrText], source: &Option<Link>, base: Style,
+        spans: &mut Vec<(Style, String)>,
+    ) {
+        self.em_or_text_spans(attribution, base, spans);
+        if let Some(source) = source {
+            if !attribution.is_empty() {
+                spans.push((base, ", ".to_string()));
+            }
+            self.link_spans(source, base, spans);
+        }
+    }
+
+    fn inline_quote_spans(
+        &self, quote: &InlineQuote, base: Style, spans: &mut Vec<(Style, String)>,
+    ) {
+        spans.push((base, "“".to_string()));
+        self.em_or_text_spans(&quote.items, base, spans);
+        spans.push((base, "”".to_string()));
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            spans.push((base, " (".to_string()));
+            self.credit_spans(&quote.attribution, &quote.source, base, spans);
+            spans.push((base, ")".to_string()));
+        }
+    }
+
+    fn quote(&self, quote: &Quote, max: usize) -> Block {
+        let marker = format!("{} ", self.profile.quote);
+        let marker_width = width(&marker);
+        let max = max.saturating_sub(marker_width).max(1);
+        let mut blocks = Vec::new();
+        if quote.kind.is_some() {
+            blocks.push(self.wrap(&[(self.profile.sub_heading, quote_name(&quote.kind))], max));
+        }
+        for item in &quote.items {
+            match item {
+                QuoteItem::Paragraph(par) => blocks.extend(self.paragraph(par, max)),
+                QuoteItem::Quote(quote) => blocks.push(self.quote(quote, max)),
+            }
+        }
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            let mut spans = vec![(Style::default(), "— ".to_string())];
+            self.credit_spans(&quote.attribution, &quote.source, Style::default(), &mut spans);
+            blocks.push(self.wrap(&spans, max));
+        }
+        joined(blocks).into_iter().map(|line| {
+            // empty lines get no trailing space
+            let prefix = if line.width == 0 { marker.trim_end() } else { &marker };
+            Line {
+                text: format!("{prefix}{}", line.text),
+                width: width(prefix) + line.width,
+                style: line.style,
+            }
+        }).collect()
+    }
+
+    fn footnote(&self, footnote: &FootnoteDef, max: usize) -> Block {
+        let marker = format!("[{}] ", footnote.label);
+        let marker_width = width(&marker);
+        let max = max.saturating_sub(marker_width).max(1);
+        let blocks = footnote.items.iter().flat_map(|par| self.paragraph(par, max)).collect();
+        let content = joined(blocks);
+        self.indented(content, &marker, marker_width)
+    }
+
+    fn code(&self, code: &CodeBlock) -> Block {
+        code.code.split('\n').map(|text| {
+            let mut line = Line::default();
+            self.styled(self.profile.code, text, &mut line);
+            self.closed(line)
+        }).collect()
+    }
+
+    fn list(&self, list: &List, max: usize) -> Block {
+        let mut block = Vec::new();
+        for (i, par) in list.items.iter().enumerate() {
+            let marker = match list.ltype {
+                ListType::Identical => self.profile.bullet.clone(),
+                ListType::Distinct => format!("{}.", i + 1),
+                ListType::Checked if par.tags.contains("checked") => self.profile.checked.clone(),
+                ListType::Checked => self.profile.unchecked.clone(),
+            };
+            let marker = format!("{marker} ");
+            let marker_width = width(&marker);
+            let content = joined(self.paragraph(par, max.saturating_sub(marker_width).max(1)));
+            block.extend(self.indented(content, &marker, marker_width));
+        }
+        block
+    }
+
+    fn table(&self, table: &Table, max: usize) -> Block {
+        let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0);
+        if columns == 0 { return Vec::new(); }
+        let mut widths = vec![0; columns];
+        for row in &table.rows {
+            for (j, par) in row.items.iter().enumerate() {
+                for line in joined(self.paragraph(par, usize::MAX)) {
+                    widths[j] = widths[j].max(line.width);
+                }
+            }
+        }
+        // every cell is surrounded by a space on both sides and columns are separated by a bar
+        let available = max.saturating_sub(3 * columns + 1).max(columns);
+        while widths.iter().sum::<usize>() > available {
+            let widest = (0..columns).max_by_key(|j| widths[*j]).unwrap_or(0);
+            if widths[widest] <= 1 { break; }
+            widths[widest] -= 1;
+        }
+        let separator = |line: &mut Line| {
+            let text = widths.iter().map(|w| "-".repeat(w + 2)).collect::<Vec<_>>().join("+");
+            self.styled(Style::default(), &format!("|{text}|"), line);
+        };
+        let mut block = Vec::new();
+        for row in &table.rows {
+            let base = if row.is_header { self.profile.table_header } else { Style::default() };
+            let cells = (0..columns).map(|j| match row.items.get(j) {
+                Some(par) => joined(self.paragraph_styled(par, widths[j].max(1), base)),
+                None => Vec::new(),
+            }).collect::<Vec<_>>();
+            let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            for k in 0..height {
+                let mut line = Line::default();
+                self.styled(Style::default(), "|", &mut line);
+                for (j, cell) in cells.iter().enumerate() {
+                    let part = cell.get(k).cloned().unwrap_or_default();
+                    line.text.push(' ');
+                    line.text.push_str(&part.text);
+                    line.text.push_str(&" ".repeat(widths[j].saturating_sub(part.width) + 1));
+                    line.text.push('|');
+                    line.width += widths[j].max(part.width) + 3;
+                }
+                block.push(line);
+            }
+            if row.is_header {
+                let mut line = Line::default();
+                separator(&mut line);
+                block.push(line);
+            }
+        }
+        block
+    }
+
+    fn paragraph(&self, par: &Paragraph, max: usize) -> Vec<Block> {
+        self.paragraph_styled(par, max, Style::default())
+    }
+
+    /// Runs of inline items and the code blocks, lists and tables in between.
+    /// The inline items get the base style below their own.
+    fn paragraph_styled(&self, par: &Paragraph, max: usize, base: Style) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut spans = Vec::new();
+        for item in &par.items {
+            let block = match item {
+                ParagraphItem::Text(text) => {
+                    spans.push((base, text.clone()));
+                    None
+                },
+                ParagraphItem::MText(mtext) => {
+                    let style = if mtext.tags.contains("code") {
+                        base.with(self.profile.code)
+                    } else {
+                        base
+                    };
+                    spans.push((style, mtext.text.clone()));
+                    None
+                },
+                ParagraphItem::Em(em) => {
+                    spans.push((base.with(self.emphasis_style(em)), em.text.clone()));
+                    None
+                },
+                ParagraphItem::Link(link) => {
+                    self.link_spans(link, base, &mut spans);
+                    None
+                },
+                ParagraphItem::Quote(quote) => {
+                    self.inline_quote_spans(quote, base, &mut spans);
+                    None
+                },
+                ParagraphItem::FootnoteRef(footnote_ref) => {
+                    spans.push((base, format!("[{}]", footnote_ref.label)));
+                    None
+                },
+                ParagraphItem::Code(Ok(code)) => {
+                    let indent = self.profile.indent;
+                    Some(self.indented(self.code(code), &" ".repeat(indent), indent))
+                },
+                ParagraphItem::Code(Err(_)) => None,
+                ParagraphItem::List(list) => Some(self.list(list, max)),
+                ParagraphItem::Table(table) => Some(self.table(table, max)),
+                ParagraphItem::Data(data) => Some(self.table(&data.to_table(), max)),
+            };
+            if let Some(block) = block {
+                if !spans.is_empty() {
+                    blocks.push(self.wrap(&std::mem::take(&mut spans), max));
+                }
+                blocks.push(block);
+            }
+        }
+        if !spans.is_empty() {
+            blocks.push(self.wrap(&spans, max));
+        }
+        blocks
+    }
+
+    fn section(&self, section: &Section, depth: usize, blocks: &mut Vec<Block>) {
+        let style = if depth == 0 { self.profile.heading } else { self.profile.sub_heading };
+        let mut spans = Vec::new();
+        self.em_or_text_spans(&section.heading.items, style, &mut spans);
+        let mut heading = self.wrap(&spans, self.profile.width);
+        if !self.profile.colours {
+            let underline = if depth == 0 { "=" } else { "-" };
+            let length = heading.iter().map(|line| line.width).max().unwrap_or(0);
+            let mut line = Line::default();
+            self.styled(Style::default(), &underline.repeat(length), &mut line);
+            heading.push(line);
+        }
+        blocks.push(heading);
+        self.section_items(&section.items, depth + 1, blocks);
+    }
+
+    fn section_items(&self, items: &[SectionItem], depth: usize, blocks: &mut Vec<Block>) {
+        for item in items {
+            match item {
+                SectionItem::Paragraph(par) => {
+                    blocks.extend(self.paragraph(par, self.profile.width));
+                },
+                SectionItem::Section(section) => self.section(section, depth, blocks),
+                SectionItem::Quote(quote) => blocks.push(self.quote(quote, self.profile.width)),
+                SectionItem::Footnote(footnote) => {
+                    blocks.push(self.footnote(footnote, self.profile.width));
+                },
+            }
+        }
+    }
+
+    fn nav(&self, nav: &Nav, max: usize) -> Block {
+        let mut block = Vec::new();
+        if !nav.description.is_empty() {
+            block.extend(self.wrap(&[(self.profile.sub_heading, nav.description.clone())], max));
+        }
+        let marker = format!("{} ", self.profile.bullet);
+        let marker_width = width(&marker);
+        for link in &nav.links {
+            let mut spans = Vec::new();
+            self.link_spans(link, Style::default(), &mut spans);
+            let lines = self.wrap(&spans, max.saturating_sub(marker_width).max(1));
+            block.extend(self.indented(lines, &marker, marker_width));
+        }
+        // navigations without a description only group their sub-navigations
+        let indent = if nav.description.is_empty() { 0 } else { self.profile.indent };
+        for sub in &nav.subs {
+            let lines = self.nav(sub, max.saturating_sub(indent).max(1));
+            block.extend(self.indented(lines, &" ".repeat(indent), indent));
+        }
+        block
+    }
+}
+
+/// Put blocks after each other with empty lines in between.
+fn joined(blocks: Vec<Block>) -> Block {
+    let mut joined = Vec::new();
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i > 0 {
+            joined.push(Line::default());
+        }
+        joined.extend(block);
+    }
+    joined
+}
+
+/// Write a document as text for a terminal, as the profile prescribes.
+pub fn doc_to_ansi(doc: &Doc, profile: &StyleProfile, output: &mut String) {
+    let renderer = Renderer { profile };
+    let mut blocks = Vec::new();
+    for nav in &doc.navs {
+        blocks.push(renderer.nav(nav, profile.width));
+    }
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => blocks.extend(renderer.paragraph(par, profile.width)),
+            DocItem::Section(section) => renderer.section(section, 0, &mut blocks),
+            DocItem::Quote(quote) => blocks.push(renderer.quote(quote, profile.width)),
+            DocItem::Footnote(footnote) => blocks.push(renderer.footnote(footnote, profile.width)),
+        }
+    }
+    for line in joined(blocks) {
+        output.push_str(&line.text);
+        output.push('\n');
+    }
+}
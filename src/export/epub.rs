@@ -0,0 +1,467 @@
+//! EPUB 3 e-books, enabled with the `epub` feature.
+//!
+//! Every document becomes a chapter in XHTML, written like [`crate::export::html`] does, with
+//! ids for all sections. The navigation document is the table of contents of the chapters,
+//! made with [`GetTableOfContents`].
+//!
+//! The metadata of the book comes from the properties of the documents: the first `title`,
+//! `language`, `author` and `date` that is found. The date is also used as modification date;
+//! without one the current time is used.
+//!
+//! Links with the `type-hint` property set to `image` that point to a local PNG, JPEG, GIF, SVG or
+//! WebP file are packaged with the book. Remote images are left as they are. Local images have to
+//! be inside the resource directory.
+//! No stylesheet is added unless asked for, and then only a minimal one.
+
+use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
+use crate::actions::toc::{
+    GetTableOfContents, InsertTableOfContentsSectionIDs, TableOfContentsFilterType,
+    TableOfContentsItem, TableOfContentsItemType,
+};
+use crate::export::html::{ doc_body_to_html, escaped_out, open_out };
+
+use zip::{ CompressionMethod, ZipWriter, result::ZipError, write::SimpleFileOptions };
+
+use std::{
+    fmt, fs, io,
+    hash::{ DefaultHasher, Hash, Hasher },
+    io::{ Seek, Write },
+    path::{ Component, Path, PathBuf },
+    time::{ SystemTime, UNIX_EPOCH },
+};
+
+/// Options for [`docs_to_epub`].
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct EpubOptions {
+    /// Title of the book, instead of the one from the properties.
+    pub title: Option<String>,
+    /// Unique identifier of the book, like a URN or an ISBN.
+    /// Without one an identifier is made from the content.
+    pub identifier: Option<String>,
+    /// Add a minimal stylesheet.
+    pub stylesheet: bool,
+    /// Directory that local images are relative to.
+    pub resource_dir: PathBuf,
+}
+
+/// Error when an e-book could not be written.
+#[derive(Debug)]
+pub enum EpubError {
+    /// Writing the container failed.
+    Zip(ZipError),
+    /// Writing to the output failed.
+    Io(io::Error),
+    /// A local image could not be read.
+    Image { url: String, error: io::Error },
+    /// A local image is not inside the resource directory.
+    ImageOutside { url: String },
+}
+
+impl fmt::Display for EpubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Zip(error) => write!(f, "{error}"),
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Image { url, error } => write!(f, "could not read image {url}: {error}"),
+            Self::ImageOutside { url } => {
+                write!(f, "image {url} is outside of the resource directory")
+            },
+        }
+    }
+}
+
+impl std::error::Error for EpubError { }
+
+impl From<ZipError> for EpubError {
+    fn from(error: ZipError) -> Self {
+        Self::Zip(error)
+    }
+}
+
+impl From<io::Error> for EpubError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+const CONTAINER: &str = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">
+<rootfiles>
+<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>
+</rootfiles>
+</container>
+";
+
+const STYLESHEET: &str = "\
+body { margin: 0 5%; line-height: 1.4; }
+pre { white-space: pre-wrap; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid; padding: 0.2em 0.4em; }
+img { max-width: 100%; }
+";
+
+const IMAGE_TYPES: [(&str, &str); 6] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("svg", "image/svg+xml"),
+    ("webp", "image/webp"),
+];
+
+/// Image packaged with the book.
+struct Image {
+    href: String,
+    media_type: &'static str,
+    data: Vec<u8>,
+}
+
+fn prop_text<'a>(docs: &'a [Doc], key: &str) -> Option<&'a str> {
+    docs.iter().find_map(|doc| match doc.props.get(key) {
+        Some(PropVal::String(string) | PropVal::Text(string)) if !string.is_empty() => {
+            Some(string.as_str())
+        },
+        _ => None,
+    })
+}
+
+fn prop_date(docs: &[Doc]) -> Option<Date> {
+    docs.iter().find_map(|doc| match doc.props.get("date") {
+        Some(PropVal::Date(date)) => Some(*date),
+        _ => None,
+    })
+}
+
+fn first_heading(doc: &Doc) -> Option<String> {
+    doc.items.iter().find_map(|item| match item {
+        DocItem::Section(section) => Some(section.heading.items.deemphasise()),
+        _ => None,
+    }).filter(|heading| !heading.is_empty())
+}
+
+fn chapter_title(doc: &Doc, number: usize) -> String {
+    match doc.props.get("title") {
+        Some(PropVal::String(title) | PropVal::Text(title)) if !title.is_empty() => title.clone(),
+        _ => first_heading(doc).unwrap_or_else(|| format!("Chapter {number}")),
+    }
+}
+
+fn is_local(url: &str) -> bool {
+    !url.contains("://") && !url.starts_with("data:") && !url.starts_with('#')
+}
+
+/// Date and time in UTC from seconds since the Unix epoch.
+fn utc_from_unix(seconds: u64) -> (i64, u64, u64, u64, u64, u64) {
+    // Days to a civil date, from Howard Hinnant's `civil_from_days`.
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let time = seconds % 86400;
+    (year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+fn modified(docs: &[Doc]) -> String {
+    match prop_date(docs) {
+        Some(date) => format!("{:04}-{:02}-{:02}T00:00:00Z", date.year, date.month, date.day),
+        None => {
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+            let (y, mo, d, h, mi, s) = utc_from_unix(seconds);
+            format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}Z")
+        },
+    }
+}
+
+/// Package the local images of a document and point its links to them.
+fn package_images(
+    doc: &mut Doc,
+    options: &EpubOptions,
+    images: &mut Vec<Image>,
+    packaged: &mut HashMap<String, String>,
+) -> Result<(), EpubError> {
+    for link in doc.links_mut(true) {
+        let is_image = matches!(
+            link.props.get("type-hint"),
+            Some(PropVal::String(hint) | PropVal::Text(hint)) if hint == "image"
+        );
+        if !is_image || !is_local(&link.url) {
+            continue;
+        }
+        if let Some(href) = packaged.get(&link.url) {
+            link.url = href.clone();
+            continue;
+        }
+        let path = link.url.strip_prefix("file:").unwrap_or(&link.url);
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+        let Some((extension, media_type)) = IMAGE_TYPES.iter()
+            .find(|(ext, _)| extension.as_deref() == Some(*ext))
+        else {
+            continue;
+        };
+        if !is_inside(path) {
+            return Err(EpubError::ImageOutside { url: link.url.clone() });
+        }
+        let data = fs::read(options.resource_dir.join(path))
+            .map_err(|error| EpubError::Image { url: link.url.clone(), error })?;
+        let href = format!("images/image-{}.{extension}", images.len() + 1);
+        images.push(Image { href: href.clone(), media_type, data });
+        packaged.insert(link.url.clone(), href.clone());
+        link.url = href;
+    }
+    Ok(())
+}
+
+/// Whether the relative path stays inside the directory it is relative to.
+fn is_inside(path: &str) -> bool {
+    let mut depth = 0usize;
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {},
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(up) => depth = up,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+fn xhtml_head(title: &str, language: &str, stylesheet: bool, output: &mut String) {
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n");
+    output.push_str("<html xmlns=\"http://www.w3.org/1999/xhtml\" ");
+    output.push_str("xmlns:epub=\"http://www.idpf.org/2007/ops\" lang=\"");
+    escaped_out(language, output);
+    output.push_str("\" xml:lang=\"");
+    escaped_out(language, output);
+    output.push_str("\">\n<head>\n<meta charset=\"utf-8\" />\n<title>");
+    escaped_out(title, output);
+    output.push_str("</title>\n");
+    if stylesheet {
+        output.push_str("<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />\n");
+    }
+    output.push_str("</head>\n");
+}
+
+fn chapter_xhtml(doc: &Doc, title: &str, language: &str, stylesheet: bool) -> String {
+    let mut output = String::new();
+    xhtml_head(title, language, stylesheet, &mut output);
+    open_out("body", &doc.tags, &doc.props, &mut output);
+    output.push('\n');
+    doc_body_to_html(doc, &mut output);
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+fn toc_out(items: &[TableOfContentsItem], file: &str, output: &mut String) {
+    if items.is_empty() {
+        return;
+    }
+    output.push_str("<ol>\n");
+    for item in items {
+        output.push_str("<li><a href=\"");
+        escaped_out(file, output);
+        escaped_out(&item.link, output);
+        output.push_str("\">");
+        escaped_out(&item.title, output);
+        output.push_str("</a>");
+        if !item.children.is_empty() {
+            output.push('\n');
+            toc_out(&item.children, file, output);
+        }
+        output.push_str("</li>\n");
+    }
+    output.push_str("</ol>\n");
+}
+
+fn nav_xhtml(
+    docs: &[Doc], titles: &[String], title: &str, language: &str, stylesheet: bool,
+) -> String {
+    let filter = Some((
+        HashSet::from([
+            TableOfContentsItemType::Document,
+            TableOfContentsItemType::Section,
+            TableOfContentsItemType::Quote,
+        ]),
+        TableOfContentsFilterType::HardStop,
+    ));
+    let mut output = String::new();
+    xhtml_head(title, language, stylesheet, &mut output);
+    output.push_str("<body>\n<nav epub:type=\"toc\" id=\"toc\">\n<h1>");
+    escaped_out(title, &mut output);
+    output.push_str("</h1>\n<ol>\n");
+    for (i, (doc, chapter)) in docs.iter().zip(titles).enumerate() {
+        let file = format!("chapter-{}.xhtml", i + 1);
+        output.push_str("<li><a href=\"");
+        output.push_str(&file);
+        output.push_str("\">");
+        escaped_out(chapter, &mut output);
+        output.push_str("</a>");
+        let children = doc.get_table_of_contents(&filter)
+            .map(|toc| toc.children)
+            .unwrap_or_default();
+        if !children.is_empty() {
+            output.push('\n');
+            toc_out(&children, &file, &mut output);
+        }
+        output.push_str("</li>\n");
+    }
+    output.push_str("</ol>\n</nav>\n</body>\n</html>\n");
+    output
+}
+
+fn element_out(name: &str, text: &str, output: &mut String) {
+    output.push('<');
+    output.push_str(name);
+    output.push('>');
+    escaped_out(text, output);
+    output.push_str("</");
+    output.push_str(name);
+    output.push_str(">\n");
+}
+
+fn item_out(id: &str, href: &str, media_type: &str, properties: Option<&str>, out: &mut String) {
+    out.push_str("<item id=\"");
+    out.push_str(id);
+    out.push_str("\" href=\"");
+    out.push_str(href);
+    out.push_str("\" media-type=\"");
+    out.push_str(media_type);
+    out.push('"');
+    if let Some(properties) = properties {
+        out.push_str(" properties=\"");
+        out.push_str(properties);
+        out.push('"');
+    }
+    out.push_str("/>\n");
+}
+
+fn package_opf(
+    docs: &[Doc], meta: &Metadata, chapters: usize, images: &[Image], stylesheet: bool,
+) -> String {
+    let mut output = String::new();
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" ");
+    output.push_str("unique-identifier=\"book-id\" xml:lang=\"");
+    escaped_out(&meta.language, &mut output);
+    output.push_str("\">\n<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+    output.push_str("<dc:identifier id=\"book-id\">");
+    escaped_out(&meta.identifier, &mut output);
+    output.push_str("</dc:identifier>\n");
+    element_out("dc:title", &meta.title, &mut output);
+    element_out("dc:language", &meta.language, &mut output);
+    if let Some(author) = prop_text(docs, "author") {
+        element_out("dc:creator", author, &mut output);
+    }
+    if let Some(date) = prop_date(docs) {
+        let date = format!("{:04}-{:02}-{:02}", date.year, date.month, date.day);
+        element_out("dc:date", &date, &mut output);
+    }
+    output.push_str("<meta property=\"dcterms:modified\">");
+    output.push_str(&meta.modified);
+    output.push_str("</meta>\n</metadata>\n<manifest>\n");
+    item_out("nav", "nav.xhtml", "application/xhtml+xml", Some("nav"), &mut output);
+    if stylesheet {
+        item_out("style", "style.css", "text/css", None, &mut output);
+    }
+    for i in 1..=chapters {
+        let id = format!("chapter-{i}");
+        item_out(&id, &format!("{id}.xhtml"), "application/xhtml+xml", None, &mut output);
+    }
+    for (i, image) in images.iter().enumerate() {
+        let id = format!("image-{}", i + 1);
+        item_out(&id, &image.href, image.media_type, None, &mut output);
+    }
+    output.push_str("</manifest>\n<spine>\n");
+    for i in 1..=chapters {
+        output.push_str(&format!("<itemref idref=\"chapter-{i}\"/>\n"));
+    }
+    output.push_str("</spine>\n</package>\n");
+    output
+}
+
+/// Metadata of the whole book.
+struct Metadata {
+    identifier: String,
+    title: String,
+    language: String,
+    modified: String,
+}
+
+/// Write documents as the chapters of an EPUB 3 e-book.
+pub fn docs_to_epub<W: Write + Seek>(
+    docs: &[Doc], options: &EpubOptions, writer: W,
+) -> Result<W, EpubError> {
+    let mut docs = docs.to_vec();
+    let mut images = Vec::new();
+    let mut packaged = HashMap::new();
+    for doc in &mut docs {
+        doc.insert_table_of_contents_section_ids();
+        package_images(doc, options, &mut images, &mut packaged)?;
+    }
+    let titles: Vec<String> = docs.iter().enumerate()
+        .map(|(i, doc)| chapter_title(doc, i + 1))
+        .collect();
+    let title = options.title.clone()
+        .or_else(|| prop_text(&docs, "title").map(str::to_string))
+        .or_else(|| titles.first().cloned())
+        .unwrap_or("Untitled".to_string());
+    let language = prop_text(&docs, "language").unwrap_or("und").to_string();
+    let chapters: Vec<String> = docs.iter().zip(&titles)
+        .map(|(doc, chapter)| chapter_xhtml(doc, chapter, &language, options.stylesheet))
+        .collect();
+    let identifier = options.identifier.clone()
+        .or_else(|| prop_text(&docs, "identifier").map(str::to_string))
+        .unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            title.hash(&mut hasher);
+            chapters.hash(&mut hasher);
+            format!("urn:incodoc:{:016x}", hasher.finish())
+        });
+    let meta = Metadata { identifier, title, language, modified: modified(&docs) };
+
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut zip = ZipWriter::new(writer);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+    zip.start_file("META-INF/container.xml", stored)?;
+    zip.write_all(CONTAINER.as_bytes())?;
+    zip.start_file("OEBPS/content.opf", stored)?;
+    let opf = package_opf(&docs, &meta, chapters.len(), &images, options.stylesheet);
+    zip.write_all(opf.as_bytes())?;
+    zip.start_file("OEBPS/nav.xhtml", stored)?;
+    let nav = nav_xhtml(&docs, &titles, &meta.title, &meta.language, options.stylesheet);
+    zip.write_all(nav.as_bytes())?;
+    if options.stylesheet {
+        zip.start_file("OEBPS/style.css", stored)?;
+        zip.write_all(STYLESHEET.as_bytes())?;
+    }
+    for (i, chapter) in chapters.iter().enumerate() {
+        zip.start_file(format!("OEBPS/chapter-{}.xhtml", i + 1), stored)?;
+        zip.write_all(chapter.as_bytes())?;
+    }
+    for image in &images {
+        zip.start_file(format!("OEBPS/{}", image.href), stored)?;
+        zip.write_all(&image.data)?;
+    }
+    Ok(zip.finish()?)
+}
+
+/// Write a document as an EPUB 3 e-book of one chapter.
+pub fn doc_to_epub<W: Write + Seek>(
+    doc: &Doc, options: &EpubOptions, writer: W,
+) -> Result<W, EpubError> {
+    docs_to_epub(std::slice::from_ref(doc), options, writer)
+}
@@ -0,0 +1,376 @@
+//! Gemtext, the document format of the Gemini protocol.
+//!
+//! Gemtext is line based and has no inline markup, so much is flattened on the way out:
+//! - Emphasis and metadata are dropped; text is written as is. Text lines that would be read
+//!   as another kind of line get a space in front.
+//! - Sections become `#`, `##` and `###` headings by how deep they are nested; deeper sections
+//!   get `###` as well.
+//! - Links cannot be inline: a paragraph keeps the text of its links and is followed by a link
+//!   line for each of them. A paragraph of only links becomes only link lines, without empty
+//!   lines between it and other paragraphs of only links.
+//!   Line breaks in text start a new line.
+//! - Lists become `*` lines. Items of numbered lists start with their number, items of checked
+//!   lists with `[x]` or `[ ]`. Items of nested lists are indented by two spaces per level.
+//!   Anything else in an item follows the line of the item, unindented.
+//! - Tables become preformatted blocks with `table` as alt text, with aligned columns split by
+//!   `|` and a line of `-` under a header row. Datasets become tables as well, see
+//!   [`Data::to_table`].
+//! - Quotes become `>` lines, starting with their kind and ending with who is quoted. Their
+//!   source is a link line after the quote. Inline quotes go between quotation marks.
+//!   Sections with the `blockquote` tag become `>` lines without their heading.
+//!   Sections with the `blockquote-typed` tag start with their heading as first quote line.
+//!   Links and code in quotes are written after the quote.
+//! - Footnote references become `[label]`. Footnote definitions are written where they are,
+//!   starting with `[label]`.
+//! - Code becomes a preformatted block with the language as alt text, none for `plain`.
+//! - Navigations become their description followed by their links, before the content.
+
+use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
+use crate::export::plain::quote_name;
+
+/// Block of lines; blocks are written with an empty line between them.
+type Lines = Vec<String>;
+
+fn is_quote(section: &Section) -> bool {
+    section.tags.contains("blockquote") || section.tags.contains("blockquote-typed")
+}
+
+/// Text line that is not read as another kind of line: those get a space in front.
+fn text_line(line: &str) -> String {
+    let marked = ["#", "=>", "* ", ">", "```"].iter().any(|marker| line.starts_with(marker));
+    if marked { format!(" {line}") } else { line.to_string() }
+}
+
+fn link_line(link: &Link) -> String {
+    let label = link.items.deemphasise();
+    let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    if label.is_empty() || label == link.url {
+        format!("=> {}", link.url)
+    } else {
+        format!("=> {} {label}", link.url)
+    }
+}
+
+fn preformatted(alt: &str, text: &str, lines: &mut Lines) {
+    lines.push(format!("```{alt}"));
+    lines.extend(text.lines().map(|line| line.to_string()));
+    lines.push("```".to_string());
+}
+
+fn code_lines(code: &Result<CodeBlock, CodeIdentError>) -> Option<Lines> {
+    let code = code.as_ref().ok()?;
+    let alt = if code.language == "plain" { "" } else { &code.language };
+    let mut lines = Lines::new();
+    preformatted(alt, &code.code, &mut lines);
+    Some(lines)
+}
+
+/// Text of a paragraph on one line, to put in bullets and table cells.
+fn single_line(par: &Paragraph) -> String {
+    let mut text = String::new();
+    for item in &par.items {
+        match item {
+            ParagraphItem::Text(string) => text.push_str(string),
+            ParagraphItem::MText(mtext) => text.push_str(&mtext.text),
+            ParagraphItem::Em(em) => text.push_str(&em.deemphasise()),
+            ParagraphItem::Link(link) => text.push_str(&link.items.deemphasise()),
+            ParagraphItem::FootnoteRef(footnote_ref) => {
+                text.push_str(&format!("[{}]", footnote_ref.label));
+            },
+            _ => text.push(' '),
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn table_lines(table: &Table) -> Lines {
+    let rows: Vec<Vec<String>> = table.rows.iter()
+        .map(|row| row.items.iter().map(single_line).collect())
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| rows.iter()
+            .filter_map(|row| row.get(c))
+            .map(|cell| cell.chars().count())
+            .max()
+            .unwrap_or(0))
+        .collect();
+    let mut text = String::new();
+    for (row, cells) in table.rows.iter().zip(&rows) {
+        let line = cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        text.push_str(line.trim_end());
+        text.push('\n');
+        if row.is_header {
+            let line = widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>();
+            text.push_str(&line.join("-+-"));
+            text.push('\n');
+        }
+    }
+    let mut lines = Lines::new();
+    preformatted("table", &text, &mut lines);
+    lines
+}
+
+fn list_lines(list: &List, depth: usize, lines: &mut Lines) {
+    let indent = "  ".repeat(depth);
+    for (i, par) in list.items.iter().enumerate() {
+        let marker = match list.ltype {
+            ListType::Identical => String::new(),
+            ListType::Distinct => format!("{}. ", i + 1),
+            ListType::Checked if par.tags.contains("checked") => "[x] ".to_string(),
+            ListType::Checked => "[ ] ".to_string(),
+        };
+        let text = single_line(&Paragraph {
+            items: par.items.iter()
+                .take_while(|item| !matches!(
+                    item,
+                    ParagraphItem::List(_) | ParagraphItem::Table(_) | ParagraphItem::Code(_)
+                ))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        });
+        lines.push(format!("* {indent}{marker}{text}").trim_end().to_string());
+        let mut blocks = Vec::new();
+        for item in &par.items {
+            match item {
+                ParagraphItem::Link(link) => lines.push(link_line(link)),
+                ParagraphItem::List(list) => list_lines(list, depth + 1, lines),
+                ParagraphItem::Table(table) => blocks.push(table_lines(table)),
+                ParagraphItem::Code(code) => blocks.extend(code_lines(code)),
+                _ => { },
+            }
+        }
+        for block in blocks {
+            lines.extend(block);
+        }
+    }
+}
+
+/// Text of a paragraph up to the next block, with the links in it.
+#[derive(Default)]
+struct TextRun<'a> {
+    text: String,
+    links: Vec<&'a Link>,
+    /// Whether there is text besides the links.
+    plain: bool,
+}
+
+impl TextRun<'_> {
+    fn push_text(&mut self, text: &str) {
+        self.plain |= !text.trim().is_empty();
+        self.text.push_str(text);
+    }
+
+    /// A run of only links is written as link lines only.
+    fn flush(&mut self, blocks: &mut Vec<Lines>) {
+        let mut lines = Lines::new();
+        if self.plain {
+            lines.extend(
+                self.text.lines().map(str::trim).filter(|line| !line.is_empty()).map(text_line)
+            );
+        }
+        lines.extend(self.links.drain(..).map(link_line));
+        if !lines.is_empty() {
+            blocks.push(lines);
+        }
+        self.text.clear();
+        self.plain = false;
+    }
+}
+
+fn paragraph_blocks(par: &Paragraph, blocks: &mut Vec<Lines>) {
+    let mut run = TextRun::default();
+    for item in &par.items {
+        match item {
+            ParagraphItem::Text(string) => run.push_text(string),
+            ParagraphItem::MText(mtext) => run.push_text(&mtext.text),
+            ParagraphItem::Em(em) => run.push_text(&em.deemphasise()),
+            ParagraphItem::Link(link) => {
+                run.text.push_str(&link.items.deemphasise());
+                run.links.push(link);
+            },
+            ParagraphItem::Quote(quote) => {
+                run.push_text(&format!("“{}”", quote.items.deemphasise()));
+                if !quote.attribution.is_empty() {
+                    run.push_text(&format!(" ({})", quote.attribution.deemphasise()));
+                }
+                if let Some(source) = &quote.source {
+                    run.links.push(source);
+                }
+            },
+            ParagraphItem::FootnoteRef(footnote_ref) => {
+                run.push_text(&format!("[{}]", footnote_ref.label));
+            },
+            ParagraphItem::Code(code) => {
+                run.flush(blocks);
+                blocks.extend(code_lines(code));
+            },
+            ParagraphItem::List(list) => {
+                run.flush(blocks);
+                let mut lines = Lines::new();
+                list_lines(list, 0, &mut lines);
+                blocks.push(lines);
+            },
+            ParagraphItem::Table(table) => {
+                run.flush(blocks);
+                blocks.push(table_lines(table));
+            },
+            ParagraphItem::Data(data) => {
+                run.flush(blocks);
+                blocks.push(table_lines(&data.to_table()));
+            },
+        }
+    }
+    run.flush(blocks);
+}
+
+/// Only text lines can be quoted; other lines go after the quote.
+fn quoted_blocks(first: Option<String>, inner: Vec<Lines>, last: Lines, blocks: &mut Vec<Lines>) {
+    let mut quote = Lines::new();
+    if let Some(first) = first {
+        quote.push(format!("> {first}"));
+    }
+    let mut after = Lines::new();
+    for block in inner {
+        if !quote.is_empty() && block.first().is_some_and(|line| is_text_line(line)) {
+            quote.push(">".to_string());
+        }
+        let mut preformatted = false;
+        for line in block {
+            if line.starts_with("```") {
+                preformatted = !preformatted;
+                after.push(line);
+            } else if preformatted || line.starts_with("=>") {
+                after.push(line);
+            } else {
+                quote.push(format!("> {}", line.trim_start()));
+            }
+        }
+    }
+    for line in last {
+        if line.starts_with("=>") {
+            after.push(line);
+        } else {
+            quote.push(format!("> {line}"));
+        }
+    }
+    if !quote.is_empty() {
+        blocks.push(quote);
+    }
+    if !after.is_empty() {
+        blocks.push(after);
+    }
+}
+
+fn quote_section_blocks(section: &Section, blocks: &mut Vec<Lines>) {
+    let mut inner = Vec::new();
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            SectionItem::Section(section) => section_blocks(section, 2, &mut inner),
+            SectionItem::Quote(quote) => quote_blocks(quote, &mut inner),
+            SectionItem::Footnote(footnote) => footnote_blocks(footnote, &mut inner),
+        }
+    }
+    let first = section.tags.contains("blockquote-typed")
+        .then(|| section.heading.items.deemphasise());
+    quoted_blocks(first, inner, Lines::new(), blocks);
+}
+
+fn quote_blocks(quote: &Quote, blocks: &mut Vec<Lines>) {
+    let mut inner = Vec::new();
+    for item in &quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            QuoteItem::Quote(quote) => quote_blocks(quote, &mut inner),
+        }
+    }
+    let first = quote.kind.is_some().then(|| quote_name(&quote.kind));
+    let mut last = Lines::new();
+    if !quote.attribution.is_empty() {
+        last.push(format!("— {}", quote.attribution.deemphasise()));
+    }
+    last.extend(quote.source.iter().map(link_line));
+    quoted_blocks(first, inner, last, blocks);
+}
+
+fn footnote_blocks(footnote: &FootnoteDef, blocks: &mut Vec<Lines>) {
+    let mut inner = Vec::new();
+    for par in &footnote.items {
+        paragraph_blocks(par, &mut inner);
+    }
+    let label = format!("[{}]", footnote.label);
+    match inner.first_mut().and_then(|block| block.first_mut()) {
+        Some(line) if is_text_line(line) && !line.starts_with("* ") => {
+            *line = format!("{label} {}", line.trim_start());
+        },
+        _ => inner.insert(0, vec![label]),
+    }
+    blocks.extend(inner);
+}
+
+fn is_text_line(line: &str) -> bool {
+    !line.starts_with("```") && !line.starts_with("=>")
+}
+
+fn section_blocks(section: &Section, depth: usize, blocks: &mut Vec<Lines>) {
+    if is_quote(section) {
+        quote_section_blocks(section, blocks);
+        return;
+    }
+    let heading = section.heading.items.deemphasise();
+    blocks.push(vec![format!("{} {}", "#".repeat((depth + 1).min(3)), heading.trim())]);
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => paragraph_blocks(par, blocks),
+            SectionItem::Section(section) => section_blocks(section, depth + 1, blocks),
+            SectionItem::Quote(quote) => quote_blocks(quote, blocks),
+            SectionItem::Footnote(footnote) => footnote_blocks(footnote, blocks),
+        }
+    }
+}
+
+fn nav_blocks(nav: &Nav, blocks: &mut Vec<Lines>) {
+    let mut lines = Lines::new();
+    if !nav.description.is_empty() {
+        lines.push(text_line(&nav.description));
+    }
+    lines.extend(nav.links.iter().map(link_line));
+    if !lines.is_empty() {
+        blocks.push(lines);
+    }
+    for sub in &nav.subs {
+        nav_blocks(sub, blocks);
+    }
+}
+
+/// Write a document as gemtext.
+pub fn doc_to_gemtext(doc: &Doc, output: &mut String) {
+    let mut blocks = Vec::new();
+    for nav in &doc.navs {
+        nav_blocks(nav, &mut blocks);
+    }
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => paragraph_blocks(par, &mut blocks),
+            DocItem::Section(section) => section_blocks(section, 0, &mut blocks),
+            DocItem::Quote(quote) => quote_blocks(quote, &mut blocks),
+            DocItem::Footnote(footnote) => footnote_blocks(footnote, &mut blocks),
+        }
+    }
+    let is_links = |block: &Lines| block.iter().all(|line| line.starts_with("=>"));
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 && !(is_links(block) && is_links(&blocks[i - 1])) {
+            output.push('\n');
+        }
+        for line in block {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+}
@@ -0,0 +1,446 @@
+//! Semantic HTML5 without any styling.
+//!
+//! Sections become `<section>` elements with a heading, lists become `<ul>` or `<ol>`, tables use
+//! `<th>` for header rows and navigations become `<nav>` elements.
+//! The `id` property becomes the `id` attribute.
+//! Other properties become `data-*` attributes, where the name is lowercased and characters that
+//! can not appear in an attribute name are replaced by `-`. When that makes names the same, the
+//! later ones in the order of the keys get `-2`, `-3` and so on appended.
+//! Tags become a space separated `data-tags` attribute.
+//! Links with the `type-hint` property set to `image` become `<img>` elements with the text of
+//! the link as alternative text.
+//! Quotes become `<blockquote>` and `<q>` elements, with the kind as `data-kind` attribute and
+//! the source as `cite` attribute. A block quote with an attribution or source goes in a
+//! `<figure>` with them as caption; an inline quote is followed by them between parentheses.
+//! Footnote references become superscript links to `#footnote-{label}`, and footnote definitions
+//! `<aside>` elements with that id, unless they have an `id` property of their own.
+//! Datasets become tables, see [`Data::to_table`].
+//!
+//! The markup is also well-formed XML, so it can be used as XHTML as well.
+
+use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
+use crate::output::{ sorted_tags, sorted_props };
+
+/// Escape text so it can be put between elements or inside a quoted attribute.
+pub(crate) fn escaped_out(string: &str, output: &mut String) {
+    for c in string.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+}
+
+fn attribute_out(name: &str, value: &str, output: &mut String) {
+    output.push(' ');
+    output.push_str(name);
+    output.push_str("=\"");
+    escaped_out(value, output);
+    output.push('"');
+}
+
+fn data_name(key: &str) -> String {
+    let mut name = "data-".to_string();
+    for c in key.chars() {
+        match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '-' | '_' | '.') => name.push(c),
+            _ => name.push('-'),
+        }
+    }
+    name
+}
+
+fn prop_val_string(val: &PropVal) -> Option<String> {
+    match val {
+        PropVal::String(string) | PropVal::Text(string) => Some(string.clone()),
+        PropVal::Int(int) => Some(int.to_string()),
+        PropVal::Date(date) => Some(format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)),
+        PropVal::Error(_) => None,
+    }
+}
+
+/// Write the metadata as attributes, next to the `data-*` attributes in `taken` that are already
+/// written.
+fn meta_out(tags: &Tags, props: &Props, taken: &[&str], output: &mut String) {
+    if let Some(PropVal::String(id) | PropVal::Text(id)) = props.get("id") {
+        attribute_out("id", id, output);
+    }
+    // an attribute can only be there once, also when keys come out as the same name
+    let mut names: HashSet<String> = taken.iter().map(|name| name.to_string()).collect();
+    if !tags.is_empty() {
+        let tags = sorted_tags(tags).into_iter().map(String::as_str).collect::<Vec<_>>();
+        attribute_out("data-tags", &tags.join(" "), output);
+        names.insert("data-tags".to_string());
+    }
+    for (key, val) in sorted_props(props) {
+        if key == "id" { continue; }
+        if let Some(val) = prop_val_string(val) {
+            let base = data_name(key);
+            let mut name = base.clone();
+            for n in 2.. {
+                if names.insert(name.clone()) { break; }
+                name = format!("{base}-{n}");
+            }
+            attribute_out(&name, &val, output);
+        }
+    }
+}
+
+pub(crate) fn open_out(element: &str, tags: &Tags, props: &Props, output: &mut String) {
+    output.push('<');
+    output.push_str(element);
+    meta_out(tags, props, &[], output);
+    output.push('>');
+}
+
+fn close_out(element: &str, output: &mut String) {
+    output.push_str("</");
+    output.push_str(element);
+    output.push('>');
+}
+
+fn emphasis_to_html(em: &Emphasis, output: &mut String) {
+    let element = match (em.etype, em.strength) {
+        (EmType::Emphasis, EmStrength::Light) => "em",
+        (EmType::Emphasis, EmStrength::Medium) => "strong",
+        (EmType::Emphasis, EmStrength::Strong) => "mark",
+        (EmType::Deemphasis, EmStrength::Light) => "small",
+        (EmType::Deemphasis, EmStrength::Medium) => "s",
+        (EmType::Deemphasis, EmStrength::Strong) => "del",
+    };
+    open_out(element, &em.tags, &em.props, output);
+    escaped_out(&em.text, output);
+    close_out(element, output);
+}
+
+fn em_or_text_to_html(items: &[EmOrText], output: &mut String) {
+    for item in items {
+        match item {
+            EmOrText::Text(text) => escaped_out(text, output),
+            EmOrText::Em(em) => emphasis_to_html(em, output),
+        }
+    }
+}
+
+fn link_to_html(link: &Link, output: &mut String) {
+    if let Some(PropVal::String(hint) | PropVal::Text(hint)) = link.props.get("type-hint")
+        && hint == "image"
+    {
+        output.push_str("<img");
+        attribute_out("src", &link.url, output);
+        attribute_out("alt", &link.items.deemphasise(), output);
+        meta_out(&link.tags, &link.props, &[], output);
+        output.push_str(" />");
+        return;
+    }
+    output.push_str("<a");
+    attribute_out("href", &link.url, output);
+    meta_out(&link.tags, &link.props, &[], output);
+    output.push('>');
+    em_or_text_to_html(&link.items, output);
+    output.push_str("</a>");
+}
+
+/// Who is quoted and the source, separated by a comma.
+fn credit_to_html(attribution: &[EmOrText], source: &Option<Link>, output: &mut String) {
+    em_or_text_to_html(attribution, output);
+    if let Some(source) = source {
+        if !attribution.is_empty() {
+            output.push_str(", ");
+        }
+        output.push_str("<cite>");
+        link_to_html(source, output);
+        output.push_str("</cite>");
+    }
+}
+
+fn quote_open_out(
+    element: &str, kind: &Option<String>, source: &Option<Link>, tags: &Tags, props: &Props,
+    output: &mut String,
+) {
+    output.push('<');
+    output.push_str(element);
+    if let Some(source) = source {
+        attribute_out("cite", &source.url, output);
+    }
+    if let Some(kind) = kind {
+        attribute_out("data-kind", kind, output);
+    }
+    meta_out(tags, props, if kind.is_some() { &["data-kind"] } else { &[] }, output);
+    output.push('>');
+}
+
+fn footnote_ref_to_html(footnote_ref: &FootnoteRef, output: &mut String) {
+    output.push_str("<sup><a");
+    attribute_out("href", &format!("#footnote-{}", footnote_ref.label), output);
+    attribute_out("role", "doc-noteref", output);
+    meta_out(&footnote_ref.tags, &footnote_ref.props, &[], output);
+    output.push('>');
+    escaped_out(&footnote_ref.label, output);
+    output.push_str("</a></sup>");
+}
+
+fn inline_quote_to_html(quote: &InlineQuote, output: &mut String) {
+    quote_open_out("q", &quote.kind, &quote.source, &quote.tags, &quote.props, output);
+    em_or_text_to_html(&quote.items, output);
+    output.push_str("</q>");
+    if !quote.attribution.is_empty() || quote.source.is_some() {
+        output.push_str(" (");
+        credit_to_html(&quote.attribution, &quote.source, output);
+        output.push(')');
+    }
+}
+
+fn code_to_html(code: &Result<CodeBlock, CodeIdentError>, output: &mut String) {
+    match code {
+        Ok(code) => {
+            output.push_str("<pre><code");
+            if !code.language.is_empty() {
+                attribute_out("class", &format!("language-{}", code.language), output);
+            }
+            let mode = match code.mode {
+                CodeModeHint::Show => None,
+                CodeModeHint::Runnable => Some("runnable"),
+                CodeModeHint::Run => Some("run"),
+                CodeModeHint::Replace => Some("replace"),
+            };
+            if let Some(mode) = mode {
+                attribute_out("data-mode", mode, output);
+            }
+            let taken: &[&str] = if mode.is_some() { &["data-mode"] } else { &[] };
+            meta_out(&code.tags, &code.props, taken, output);
+            output.push('>');
+            escaped_out(&code.code, output);
+            output.push_str("</code></pre>\n");
+        },
+        Err(_) => output.push_str("<!-- code with broken indentation -->\n"),
+    }
+}
+
+fn list_to_html(list: &List, output: &mut String) {
+    let element = if list.ltype == ListType::Distinct { "ol" } else { "ul" };
+    open_out(element, &list.tags, &list.props, output);
+    output.push('\n');
+    for par in &list.items {
+        open_out("li", &par.tags, &par.props, output);
+        if list.ltype == ListType::Checked {
+            output.push_str("<input type=\"checkbox\" disabled=\"disabled\"");
+            if par.tags.contains("checked") {
+                output.push_str(" checked=\"checked\"");
+            }
+            output.push_str(" /> ");
+        }
+        paragraph_content_to_html(par, output);
+        output.push_str("</li>\n");
+    }
+    close_out(element, output);
+    output.push('\n');
+}
+
+fn table_to_html(table: &Table, output: &mut String) {
+    open_out("table", &table.tags, &table.props, output);
+    output.push('\n');
+    for row in &table.rows {
+        open_out("tr", &row.tags, &row.props, output);
+        let element = if row.is_header { "th" } else { "td" };
+        for par in &row.items {
+            open_out(element, &par.tags, &par.props, output);
+            paragraph_content_to_html(par, output);
+            close_out(element, output);
+        }
+        output.push_str("</tr>\n");
+    }
+    output.push_str("</table>\n");
+}
+
+fn is_inline(item: &ParagraphItem) -> bool {
+    matches!(
+        item,
+        ParagraphItem::Text(_) | ParagraphItem::MText(_) | ParagraphItem::Em(_)
+            | ParagraphItem::Link(_) | ParagraphItem::Quote(_) | ParagraphItem::FootnoteRef(_)
+    )
+}
+
+fn inline_item_to_html(item: &ParagraphItem, output: &mut String) {
+    match item {
+        ParagraphItem::Text(text) => escaped_out(text, output),
+        ParagraphItem::MText(mtext) => {
+            open_out("span", &mtext.tags, &mtext.props, output);
+            escaped_out(&mtext.text, output);
+            output.push_str("</span>");
+        },
+        ParagraphItem::Em(em) => emphasis_to_html(em, output),
+        ParagraphItem::Link(link) => link_to_html(link, output),
+        ParagraphItem::Code(code) => code_to_html(code, output),
+        ParagraphItem::List(list) => list_to_html(list, output),
+        ParagraphItem::Table(table) => table_to_html(table, output),
+        ParagraphItem::Quote(quote) => inline_quote_to_html(quote, output),
+        ParagraphItem::FootnoteRef(footnote_ref) => footnote_ref_to_html(footnote_ref, output),
+        ParagraphItem::Data(data) => table_to_html(&data.to_table(), output),
+    }
+}
+
+/// Write the items of a paragraph that is already in an element, like a list item.
+/// Text is written directly when there are only inline items, otherwise runs of inline items go
+/// in their own `<p>`.
+fn paragraph_content_to_html(par: &Paragraph, output: &mut String) {
+    if par.items.iter().all(is_inline) {
+        for item in &par.items {
+            inline_item_to_html(item, output);
+        }
+        return;
+    }
+    output.push('\n');
+    let mut in_p = false;
+    for item in &par.items {
+        if is_inline(item) {
+            if !in_p {
+                output.push_str("<p>");
+                in_p = true;
+            }
+        } else if in_p {
+            output.push_str("</p>\n");
+            in_p = false;
+        }
+        inline_item_to_html(item, output);
+    }
+    if in_p {
+        output.push_str("</p>\n");
+    }
+}
+
+fn paragraph_to_html(par: &Paragraph, output: &mut String) {
+    let element = if par.items.iter().all(is_inline) { "p" } else { "div" };
+    open_out(element, &par.tags, &par.props, output);
+    paragraph_content_to_html(par, output);
+    close_out(element, output);
+    output.push('\n');
+}
+
+fn heading_to_html(heading: &Heading, output: &mut String) {
+    let element = format!("h{}", heading.level.saturating_add(1).min(6));
+    open_out(&element, &heading.tags, &heading.props, output);
+    em_or_text_to_html(&heading.items, output);
+    close_out(&element, output);
+    output.push('\n');
+}
+
+fn section_to_html(section: &Section, output: &mut String) {
+    open_out("section", &section.tags, &section.props, output);
+    output.push('\n');
+    heading_to_html(&section.heading, output);
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => paragraph_to_html(par, output),
+            SectionItem::Section(section) => section_to_html(section, output),
+            SectionItem::Quote(quote) => quote_to_html(quote, output),
+            SectionItem::Footnote(footnote) => footnote_to_html(footnote, output),
+        }
+    }
+    output.push_str("</section>\n");
+}
+
+fn quote_to_html(quote: &Quote, output: &mut String) {
+    let credited = !quote.attribution.is_empty() || quote.source.is_some();
+    if credited {
+        output.push_str("<figure>\n");
+    }
+    quote_open_out("blockquote", &quote.kind, &quote.source, &quote.tags, &quote.props, output);
+    output.push('\n');
+    for item in &quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => paragraph_to_html(par, output),
+            QuoteItem::Quote(quote) => quote_to_html(quote, output),
+        }
+    }
+    output.push_str("</blockquote>\n");
+    if credited {
+        output.push_str("<figcaption>");
+        if !quote.attribution.is_empty() {
+            output.push_str("— ");
+        }
+        credit_to_html(&quote.attribution, &quote.source, output);
+        output.push_str("</figcaption>\n</figure>\n");
+    }
+}
+
+fn footnote_to_html(footnote: &FootnoteDef, output: &mut String) {
+    output.push_str("<aside");
+    if !footnote.props.contains_key("id") {
+        attribute_out("id", &format!("footnote-{}", footnote.label), output);
+    }
+    attribute_out("role", "doc-footnote", output);
+    meta_out(&footnote.tags, &footnote.props, &[], output);
+    output.push_str(">\n<p><sup>");
+    escaped_out(&footnote.label, output);
+    output.push_str("</sup></p>\n");
+    for par in &footnote.items {
+        paragraph_to_html(par, output);
+    }
+    output.push_str("</aside>\n");
+}
+
+fn nav_to_html(nav: &Nav, output: &mut String) {
+    open_out("nav", &nav.tags, &nav.props, output);
+    output.push('\n');
+    if !nav.description.is_empty() {
+        output.push_str("<p>");
+        escaped_out(&nav.description, output);
+        output.push_str("</p>\n");
+    }
+    if !nav.links.is_empty() {
+        output.push_str("<ul>\n");
+        for link in &nav.links {
+            output.push_str("<li>");
+            link_to_html(link, output);
+            output.push_str("</li>\n");
+        }
+        output.push_str("</ul>\n");
+    }
+    for sub in &nav.subs {
+        nav_to_html(sub, output);
+    }
+    output.push_str("</nav>\n");
+}
+
+/// Write the navigations and items of a document, without the surrounding `<body>`.
+/// The metadata of the document itself is not written.
+pub fn doc_body_to_html(doc: &Doc, output: &mut String) {
+    for nav in &doc.navs {
+        nav_to_html(nav, output);
+    }
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => paragraph_to_html(par, output),
+            DocItem::Section(section) => section_to_html(section, output),
+            DocItem::Quote(quote) => quote_to_html(quote, output),
+            DocItem::Footnote(footnote) => footnote_to_html(footnote, output),
+        }
+    }
+}
+
+/// Write a complete HTML5 document.
+/// The `language` and `title` properties of the document fill in the `lang` attribute and the
+/// `<title>` element. The metadata of the document goes on the `<body>` element.
+pub fn doc_to_html(doc: &Doc, output: &mut String) {
+    output.push_str("<!DOCTYPE html>\n<html");
+    if let Some(PropVal::String(lang) | PropVal::Text(lang)) = doc.props.get("language") {
+        attribute_out("lang", lang, output);
+    }
+    output.push_str(">\n<head>\n<meta charset=\"utf-8\" />\n");
+    if let Some(PropVal::String(title) | PropVal::Text(title)) = doc.props.get("title") {
+        output.push_str("<title>");
+        escaped_out(title, output);
+        output.push_str("</title>\n");
+    }
+    output.push_str("</head>\n");
+    open_out("body", &doc.tags, &doc.props, output);
+    output.push('\n');
+    doc_body_to_html(doc, output);
+    output.push_str("</body>\n</html>\n");
+}
@@ -0,0 +1,392 @@
+//! LaTeX, to typeset documents for print.
+//!
+//! - [`doc_to_latex`] writes a complete `article`, with the `title`, `author` and `date`
+//!   properties of the document as its title. [`doc_body_to_latex`] writes only the content,
+//!   to put in a document of your own; it needs the `amssymb` and `hyperref` packages.
+//! - Sections become `\section`, `\subsection`, `\subsubsection`, `\paragraph` and
+//!   `\subparagraph` by how deep they are nested. The `id` property becomes a `\label`.
+//! - Light emphasis becomes `\emph`, medium emphasis `\textbf` and strong emphasis both.
+//!   Deemphasis makes text smaller: `\small`, `\footnotesize` and `\scriptsize`.
+//!   Text with the `code` tag becomes `\texttt`. Line breaks in text become `\\`.
+//! - Links become `\href`.
+//! - Lists become `itemize` and `enumerate`; items of checked lists are marked with a box.
+//! - Tables become `tabular`, with a rule under a header row. Only the inline content of cells
+//!   is written. Datasets become tables as well, see [`Data::to_table`].
+//! - Code becomes `verbatim`, apart from lines with `\end{verbatim}` in them, which become
+//!   `\verb`. Code in `replace` mode with `latex` as language, or a language starting with
+//!   `latex-`, is written as is; with `latex-formula` as language it becomes displayed math.
+//! - Quotes become `quote` environments, starting with their kind in bold and ending with
+//!   their attribution and source after a dash. Quotes in paragraphs are put in quotation
+//!   marks. Sections with the `blockquote` tag become `quote` environments without their
+//!   heading; sections with the `blockquote-typed` tag start with their heading in bold.
+//! - Footnote references become their label in superscript. Footnote definitions are written
+//!   where they are, starting with their label in superscript; see [`Doc::place_footnotes`] to
+//!   move them.
+//! - Navigations and other metadata are left out.
+
+use crate::*;
+use crate::export::plain::quote_name;
+
+fn is_quote(section: &Section) -> bool {
+    section.tags.contains("blockquote") || section.tags.contains("blockquote-typed")
+}
+
+fn prop_text<'a>(props: &'a Props, key: &str) -> Option<&'a str> {
+    match props.get(key) {
+        Some(PropVal::String(val) | PropVal::Text(val)) => Some(val),
+        _ => None,
+    }
+}
+
+/// Code that is LaTeX, to write as is.
+fn is_raw(code: &CodeBlock) -> bool {
+    code.mode == CodeModeHint::Replace
+        && (code.language == "latex" || code.language.starts_with("latex-"))
+}
+
+fn escaped_latex(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '\\' => output.push_str("\\textbackslash{}"),
+            '~' => output.push_str("\\textasciitilde{}"),
+            '^' => output.push_str("\\textasciicircum{}"),
+            // without the T1 font encoding these come out as other characters
+            '<' => output.push_str("\\textless{}"),
+            '>' => output.push_str("\\textgreater{}"),
+            '|' => output.push_str("\\textbar{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                output.push('\\');
+                output.push(c);
+            },
+            '\n' => output.push_str("\\\\\n"),
+            _ => output.push(c),
+        }
+    }
+}
+
+fn emphasis(em: &Emphasis, output: &mut String) {
+    let (open, close) = match (em.etype, em.strength) {
+        (EmType::Emphasis, EmStrength::Light) => ("\\emph{", "}"),
+        (EmType::Emphasis, EmStrength::Medium) => ("\\textbf{", "}"),
+        (EmType::Emphasis, EmStrength::Strong) => ("\\textbf{\\emph{", "}}"),
+        (EmType::Deemphasis, EmStrength::Light) => ("{\\small ", "}"),
+        (EmType::Deemphasis, EmStrength::Medium) => ("{\\footnotesize ", "}"),
+        (EmType::Deemphasis, EmStrength::Strong) => ("{\\scriptsize ", "}"),
+    };
+    output.push_str(open);
+    escaped_latex(&em.text, output);
+    output.push_str(close);
+}
+
+fn em_or_text(items: &[EmOrText], output: &mut String) {
+    for item in items {
+        match item {
+            EmOrText::Text(text) => escaped_latex(text, output),
+            EmOrText::Em(em) => emphasis(em, output),
+        }
+    }
+}
+
+fn link(link: &Link, output: &mut String) {
+    output.push_str("\\href{");
+    for c in link.url.chars() {
+        if matches!(c, '\\' | '{' | '}' | '#' | '%') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output.push_str("}{");
+    em_or_text(&link.items, output);
+    output.push('}');
+}
+
+fn inline(item: &ParagraphItem, output: &mut String) {
+    match item {
+        ParagraphItem::Text(text) => escaped_latex(text, output),
+        ParagraphItem::MText(mtext) if mtext.tags.contains("code") => {
+            output.push_str("\\texttt{");
+            escaped_latex(&mtext.text, output);
+            output.push('}');
+        },
+        ParagraphItem::MText(mtext) => escaped_latex(&mtext.text, output),
+        ParagraphItem::Em(em) => emphasis(em, output),
+        ParagraphItem::Link(l) => link(l, output),
+        ParagraphItem::FootnoteRef(footnote_ref) => {
+            output.push_str("\\textsuperscript{");
+            escaped_latex(&footnote_ref.label, output);
+            output.push('}');
+        },
+        ParagraphItem::Quote(quote) => {
+            output.push_str("``");
+            em_or_text(&quote.items, output);
+            output.push_str("''");
+            if !quote.attribution.is_empty() || quote.source.is_some() {
+                output.push_str(" (");
+                credit(&quote.attribution, &quote.source, output);
+                output.push(')');
+            }
+        },
+        _ => { },
+    }
+}
+
+fn credit(attribution: &[EmOrText], source: &Option<Link>, output: &mut String) {
+    em_or_text(attribution, output);
+    if let Some(source) = source {
+        if !attribution.is_empty() {
+            output.push_str(", ");
+        }
+        link(source, output);
+    }
+}
+
+fn code(code: &CodeBlock) -> String {
+    if is_raw(code) {
+        if code.language == "latex-formula" {
+            format!("\\[\n{}\n\\]", code.code.trim())
+        } else {
+            code.code.trim_end().to_string()
+        }
+    } else {
+        verbatim(code.code.trim_end())
+    }
+}
+
+/// A `verbatim` environment ends at the first `\end{verbatim}` in it, so the lines that hold one
+/// are written with `\verb` between environments.
+fn verbatim(code: &str) -> String {
+    let mut output = String::new();
+    let mut open = false;
+    for line in code.split('\n') {
+        if !line.contains("\\end{verbatim}") {
+            if !open {
+                output.push_str("\\begin{verbatim}\n");
+                open = true;
+            }
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+        if open {
+            output.push_str("\\end{verbatim}\n");
+            open = false;
+        }
+        output.push_str("\\noindent");
+        match ['|', '+', '!', '=', '@', ';', ':', '/', '.', '-'].into_iter()
+            .find(|delimiter| !line.contains(*delimiter))
+        {
+            Some(delimiter) => {
+                output.push_str("\\verb");
+                output.push(delimiter);
+                output.push_str(line);
+                output.push(delimiter);
+            },
+            None => {
+                output.push_str("\\texttt{");
+                escaped_latex(line, &mut output);
+                output.push('}');
+            },
+        }
+        output.push_str("\\par\n");
+    }
+    if open {
+        output.push_str("\\end{verbatim}");
+    }
+    output.trim_end().to_string()
+}
+
+fn list(list: &List) -> String {
+    let env = if list.ltype == ListType::Distinct { "enumerate" } else { "itemize" };
+    let mut output = format!("\\begin{{{env}}}\n");
+    for par in &list.items {
+        output.push_str(match list.ltype {
+            ListType::Checked if par.tags.contains("checked") => "\\item[$\\boxtimes$] ",
+            ListType::Checked => "\\item[$\\square$] ",
+            _ => "\\item ",
+        });
+        let mut blocks = Vec::new();
+        paragraph_blocks(par, &mut blocks);
+        output.push_str(&blocks.join("\n\n"));
+        output.push('\n');
+    }
+    output.push_str(&format!("\\end{{{env}}}"));
+    output
+}
+
+fn table(table: &Table) -> String {
+    let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0).max(1);
+    let mut output = format!("\\begin{{tabular}}{{{}}}\n", "l".repeat(columns));
+    for row in &table.rows {
+        let cells: Vec<String> = row.items.iter()
+            .map(|par| {
+                let mut cell = String::new();
+                for item in &par.items {
+                    inline(item, &mut cell);
+                }
+                cell.replace("\\\\\n", " ")
+            })
+            .collect();
+        output.push_str(&cells.join(" & "));
+        output.push_str(" \\\\\n");
+        if row.is_header {
+            output.push_str("\\hline\n");
+        }
+    }
+    output.push_str("\\end{tabular}");
+    output
+}
+
+/// Line breaks at the end of a paragraph have no line to end.
+fn push_run(run: &mut String, blocks: &mut Vec<String>) {
+    let text = run.trim_end().trim_end_matches("\\\\").trim_end();
+    if !text.is_empty() {
+        blocks.push(text.to_string());
+    }
+    run.clear();
+}
+
+/// Runs of inline items and the code, lists and tables in between, as blocks.
+fn paragraph_blocks(par: &Paragraph, blocks: &mut Vec<String>) {
+    let mut run = String::new();
+    for item in &par.items {
+        let block = match item {
+            ParagraphItem::Code(Ok(c)) => code(c),
+            ParagraphItem::Code(Err(_)) => continue,
+            ParagraphItem::List(l) => list(l),
+            ParagraphItem::Table(t) => table(t),
+            ParagraphItem::Data(d) => table(&d.to_table()),
+            _ => {
+                inline(item, &mut run);
+                continue;
+            },
+        };
+        push_run(&mut run, blocks);
+        blocks.push(block);
+    }
+    push_run(&mut run, blocks);
+}
+
+fn quote_blocks(quote: &Quote, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    if quote.kind.is_some() {
+        let mut kind = "\\textbf{".to_string();
+        escaped_latex(&quote_name(&quote.kind), &mut kind);
+        kind.push('}');
+        inner.push(kind);
+    }
+    for item in &quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            QuoteItem::Quote(quote) => quote_blocks(quote, &mut inner),
+        }
+    }
+    if !quote.attribution.is_empty() || quote.source.is_some() {
+        let mut line = "--- ".to_string();
+        credit(&quote.attribution, &quote.source, &mut line);
+        inner.push(line);
+    }
+    blocks.push(format!("\\begin{{quote}}\n{}\n\\end{{quote}}", inner.join("\n\n")));
+}
+
+fn footnote_blocks(footnote: &FootnoteDef, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    for par in &footnote.items {
+        paragraph_blocks(par, &mut inner);
+    }
+    let mut label = "\\textsuperscript{".to_string();
+    escaped_latex(&footnote.label, &mut label);
+    label.push_str("} ");
+    match inner.first_mut() {
+        Some(first) => first.insert_str(0, &label),
+        None => inner.push(label.trim_end().to_string()),
+    }
+    blocks.extend(inner);
+}
+
+fn section_blocks(section: &Section, depth: usize, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            SectionItem::Section(section) => section_blocks(section, depth + 1, &mut inner),
+            SectionItem::Quote(quote) => quote_blocks(quote, &mut inner),
+            SectionItem::Footnote(footnote) => footnote_blocks(footnote, &mut inner),
+        }
+    }
+    if is_quote(section) {
+        let mut quote = "\\begin{quote}\n".to_string();
+        if section.tags.contains("blockquote-typed") {
+            quote.push_str("\\textbf{");
+            em_or_text(&section.heading.items, &mut quote);
+            quote.push_str("}\n\n");
+        }
+        quote.push_str(&inner.join("\n\n"));
+        quote.push_str("\n\\end{quote}");
+        blocks.push(quote);
+        return;
+    }
+    let command = match depth {
+        0 => "section",
+        1 => "subsection",
+        2 => "subsubsection",
+        3 => "paragraph",
+        _ => "subparagraph",
+    };
+    let mut heading = format!("\\{command}{{");
+    em_or_text(&section.heading.items, &mut heading);
+    heading.push('}');
+    if let Some(id) = prop_text(&section.props, "id") {
+        heading.push_str("\\label{");
+        heading.push_str(&id.replace(['\\', '{', '}', '#', '%'], "-"));
+        heading.push('}');
+    }
+    blocks.push(heading);
+    blocks.extend(inner);
+}
+
+/// Write the content of a document as LaTeX, without preamble.
+pub fn doc_body_to_latex(doc: &Doc, output: &mut String) {
+    let mut blocks = Vec::new();
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => paragraph_blocks(par, &mut blocks),
+            DocItem::Section(section) => section_blocks(section, 0, &mut blocks),
+            DocItem::Quote(quote) => quote_blocks(quote, &mut blocks),
+            DocItem::Footnote(footnote) => footnote_blocks(footnote, &mut blocks),
+        }
+    }
+    output.push_str(&blocks.join("\n\n"));
+    if !blocks.is_empty() {
+        output.push('\n');
+    }
+}
+
+/// Write a document as a complete LaTeX article.
+pub fn doc_to_latex(doc: &Doc, output: &mut String) {
+    output.push_str("\\documentclass{article}\n\\usepackage{amssymb}\n\\usepackage{hyperref}\n");
+    let title = prop_text(&doc.props, "title");
+    if let Some(title) = title {
+        output.push_str("\\title{");
+        escaped_latex(title, output);
+        output.push_str("}\n");
+        output.push_str("\\author{");
+        escaped_latex(prop_text(&doc.props, "author").unwrap_or_default(), output);
+        output.push_str("}\n");
+        output.push_str("\\date{");
+        match doc.props.get("date") {
+            Some(PropVal::Date(date)) => {
+                output.push_str(&format!("{:04}-{:02}-{:02}", date.year, date.month, date.day));
+            },
+            Some(PropVal::String(date) | PropVal::Text(date)) => escaped_latex(date, output),
+            _ => { },
+        }
+        output.push_str("}\n");
+    }
+    output.push_str("\\begin{document}\n");
+    if title.is_some() {
+        output.push_str("\\maketitle\n");
+    }
+    output.push('\n');
+    doc_body_to_latex(doc, output);
+    output.push_str("\n\\end{document}\n");
+}
@@ -0,0 +1,529 @@
+//! GitHub flavoured markdown.
+//!
+//! Sections become `#` headings by how deep they are nested, emphasis becomes `*`, `**` or `***`
+//! and deemphasis is written as chosen in [`MarkdownOptions`]. Navigations become lists of
+//! links before the content. Quotes become `>` block quotes, where a kind makes them a GitHub
+//! alert and the attribution and source go on the last line. Inline quotes go between quotation
+//! marks. Footnote references become `[^label]` and footnote definitions `[^label]: `.
+//! Datasets become tables, see [`Data::to_table`]; their `type-hint` property is not reported.
+//!
+//! Markdown has no place for most metadata. The metadata it does have a place for is used:
+//! - the `checked` tag of items in checked lists,
+//! - the `code` and `html` tags of text, which becomes inline code or raw HTML,
+//! - the `title` property of links and the `type-hint` property set to `image`,
+//! - the `footnote-ref` tag of links to `#footnote-{label}`,
+//! - the `blockquote`, `blockquote-typed` and `footnote-def` tags of sections, together with
+//!   the `blockquote-type` and `id` properties that go with them.
+//!
+//! All other tags and properties are reported as [`Unrepresented`], as is code that could not be
+//! parsed.
+
+use crate::*;
+use crate::parsing::{ NodeStep, NodePath };
+use crate::output::{ sorted_tags, sorted_props };
+
+/// How deemphasised text is written.
+#[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq)]
+pub enum DeemphasisStyle {
+    /// `~~text~~`, which reads back as medium deemphasis.
+    #[default]
+    Strikethrough,
+    /// `<small>text</small>`.
+    Small,
+    /// Plain text.
+    Plain,
+}
+
+/// Options for [`doc_to_markdown`].
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct MarkdownOptions {
+    pub deemphasis: DeemphasisStyle,
+}
+
+/// Metadata that markdown has no place for and that was left out.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Unrepresented {
+    Tag { path: NodePath, tag: String },
+    Prop { path: NodePath, key: String },
+    /// Code that could not be parsed, so there is nothing to write.
+    Code { path: NodePath },
+}
+
+struct Writer<'a> {
+    options: &'a MarkdownOptions,
+    path: NodePath,
+    unrepresented: Vec<Unrepresented>,
+}
+
+fn escaped_md(text: &str, output: &mut String) {
+    let mut line_start = true;
+    let mut digits = false;
+    for c in text.chars() {
+        let escape = match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~' | '|' => true,
+            '#' | '-' | '+' | '=' => line_start,
+            '.' | ')' => digits,
+            _ => false,
+        };
+        // a hard line break is a backslash at the end of a line
+        if escape || c == '\n' {
+            output.push('\\');
+        }
+        output.push(c);
+        digits = c.is_ascii_digit() && (line_start || digits);
+        line_start = c == '\n' || (line_start && c == ' ');
+    }
+}
+
+fn code_span_md(code: &str, output: &mut String) {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    let pad = code.starts_with('`') || code.ends_with('`');
+    output.push_str(&fence);
+    if pad { output.push(' '); }
+    output.push_str(code);
+    if pad { output.push(' '); }
+    output.push_str(&fence);
+}
+
+/// Put a prefix before each line, leaving out trailing spaces on empty lines.
+fn prefixed(text: &str, first: &str, rest: &str) -> String {
+    let mut output = String::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        let prefix = if i == 0 { first } else { rest };
+        if line.is_empty() {
+            output.push_str(prefix.trim_end());
+        } else {
+            output.push_str(prefix);
+            output.push_str(line);
+        }
+    }
+    output
+}
+
+fn is_image(link: &Link) -> bool {
+    matches!(
+        link.props.get("type-hint"),
+        Some(PropVal::String(hint) | PropVal::Text(hint)) if hint == "image"
+    )
+}
+
+impl Writer<'_> {
+    fn child<T>(&mut self, step: NodeStep, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(step);
+        let res = f(self);
+        self.path.pop();
+        res
+    }
+
+    /// Report the tags and properties that are not among the ones that were written.
+    fn meta(&mut self, tags: &Tags, props: &Props, used_tags: &[&str], used_props: &[&str]) {
+        for tag in sorted_tags(tags) {
+            if !used_tags.contains(&tag.as_str()) {
+                let path = self.path.clone();
+                self.unrepresented.push(Unrepresented::Tag { path, tag: tag.clone() });
+            }
+        }
+        for (key, _) in sorted_props(props) {
+            if !used_props.contains(&key.as_str()) {
+                let path = self.path.clone();
+                self.unrepresented.push(Unrepresented::Prop { path, key: key.clone() });
+            }
+        }
+    }
+
+    fn emphasis(&mut self, em: &Emphasis, output: &mut String) {
+        self.meta(&em.tags, &em.props, &[], &[]);
+        let (open, close) = match (em.etype, em.strength, self.options.deemphasis) {
+            (EmType::Emphasis, EmStrength::Light, _) => ("*", "*"),
+            (EmType::Emphasis, EmStrength::Medium, _) => ("**", "**"),
+            (EmType::Emphasis, EmStrength::Strong, _) => ("***", "***"),
+            (EmType::Deemphasis, _, DeemphasisStyle::Strikethrough) => ("~~", "~~"),
+            (EmType::Deemphasis, _, DeemphasisStyle::Small) => ("<small>", "</small>"),
+            (EmType::Deemphasis, _, DeemphasisStyle::Plain) => ("", ""),
+        };
+        if em.text.is_empty() { return; }
+        output.push_str(open);
+        escaped_md(&em.text, output);
+        output.push_str(close);
+    }
+
+    fn em_or_text(&mut self, items: &[EmOrText], output: &mut String) {
+        for (i, item) in items.iter().enumerate() {
+            match item {
+                EmOrText::Text(text) => escaped_md(text, output),
+                EmOrText::Em(em) => self.child(NodeStep::Item(i), |w| w.emphasis(em, output)),
+            }
+        }
+    }
+
+    fn footnote_ref(&mut self, footnote_ref: &FootnoteRef, output: &mut String) {
+        self.meta(&footnote_ref.tags, &footnote_ref.props, &[], &[]);
+        output.push_str("[^");
+        output.push_str(&footnote_ref.label);
+        output.push(']');
+    }
+
+    fn link(&mut self, link: &Link, output: &mut String) {
+        if link.tags.contains("footnote-ref")
+            && let Some(label) = link.url.strip_prefix("#footnote-")
+        {
+            self.meta(&link.tags, &link.props, &["footnote-ref"], &[]);
+            output.push_str("[^");
+            output.push_str(label);
+            output.push(']');
+            return;
+        }
+        let image = is_image(link);
+        let used: &[&str] = if image { &["title", "type-hint"] } else { &["title"] };
+        self.meta(&link.tags, &link.props, &[], used);
+        if image {
+            output.push('!');
+        }
+        output.push('[');
+        self.em_or_text(&link.items, output);
+        output.push_str("](");
+        if link.url.is_empty() || link.url.contains([' ', '(', ')', '<', '>']) {
+            output.push('<');
+            output.push_str(&link.url.replace('<', "%3C").replace('>', "%3E"));
+            output.push('>');
+        } else {
+            output.push_str(&link.url);
+        }
+        if let Some(PropVal::String(title) | PropVal::Text(title)) = link.props.get("title") {
+            output.push_str(" \"");
+            output.push_str(&title.replace('\\', "\\\\").replace('"', "\\\""));
+            output.push('"');
+        }
+        output.push(')');
+    }
+
+    /// Who is quoted and the source, separated by a comma.
+    fn credit(&mut self, attribution: &[EmOrText], source: &Option<Link>, output: &mut String) {
+        self.child(NodeStep::Attribution, |w| w.em_or_text(attribution, output));
+        if let Some(source) = source {
+            if !attribution.is_empty() {
+                output.push_str(", ");
+            }
+            self.child(NodeStep::Source, |w| w.link(source, output));
+        }
+    }
+
+    fn inline_quote(&mut self, quote: &InlineQuote, output: &mut String) {
+        self.meta(&quote.tags, &quote.props, &[], &[]);
+        output.push('“');
+        self.em_or_text(&quote.items, output);
+        output.push('”');
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            output.push_str(" (");
+            self.credit(&quote.attribution, &quote.source, output);
+            output.push(')');
+        }
+    }
+
+    fn mtext(&mut self, mtext: &TextWithMeta, output: &mut String) {
+        if mtext.tags.contains("code") {
+            self.meta(&mtext.tags, &mtext.props, &["code"], &[]);
+            code_span_md(&mtext.text, output);
+        } else if mtext.tags.contains("html") {
+            self.meta(&mtext.tags, &mtext.props, &["html"], &[]);
+            output.push_str(&mtext.text);
+        } else {
+            self.meta(&mtext.tags, &mtext.props, &[], &[]);
+            escaped_md(&mtext.text, output);
+        }
+    }
+
+    fn code(&mut self, code: &CodeBlock) -> String {
+        self.meta(&code.tags, &code.props, &[], &[]);
+        let mut longest = 2;
+        for line in code.code.lines() {
+            let line = line.trim_start();
+            longest = longest.max(line.len() - line.trim_start_matches('`').len());
+        }
+        let fence = "`".repeat(longest + 1);
+        let mut output = fence.clone();
+        if code.language != "plain" {
+            output.push_str(&code.language);
+        }
+        output.push('\n');
+        output.push_str(&code.code);
+        if !code.code.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&fence);
+        output
+    }
+
+    fn list(&mut self, list: &List) -> String {
+        self.meta(&list.tags, &list.props, &[], &[]);
+        let mut items = Vec::new();
+        for (i, par) in list.items.iter().enumerate() {
+            let marker = match list.ltype {
+                ListType::Identical => "- ".to_string(),
+                ListType::Distinct => format!("{}. ", i + 1),
+                ListType::Checked if par.tags.contains("checked") => "- [x] ".to_string(),
+                ListType::Checked => "- [ ] ".to_string(),
+            };
+            let checked: &[&str] = if list.ltype == ListType::Checked { &["checked"] } else { &[] };
+            let content = self.child(NodeStep::Item(i), |w| {
+                w.meta(&par.tags, &par.props, checked, &[]);
+                w.paragraph_parts(par).join("\n\n")
+            });
+            // continuation lines line up with the content, after the number or bullet
+            let indent = if list.ltype == ListType::Distinct { marker.len() } else { 2 };
+            items.push(prefixed(&content, &marker, &" ".repeat(indent)));
+        }
+        items.join("\n")
+    }
+
+    fn table(&mut self, table: &Table) -> String {
+        self.meta(&table.tags, &table.props, &[], &[]);
+        let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0).max(1);
+        let mut rows = Vec::new();
+        for (i, row) in table.rows.iter().enumerate() {
+            let cells = self.child(NodeStep::Row(i), |w| {
+                w.meta(&row.tags, &row.props, &[], &[]);
+                let mut cells = Vec::new();
+                for (j, par) in row.items.iter().enumerate() {
+                    cells.push(w.child(NodeStep::Item(j), |w| {
+                        w.meta(&par.tags, &par.props, &[], &[]);
+                        w.paragraph_parts(par).join(" ").replace("\\\n", " ").replace('\n', " ")
+                    }));
+                }
+                cells
+            });
+            rows.push((row.is_header, cells));
+        }
+        // markdown tables start with exactly one header row
+        if !rows.first().is_some_and(|(header, _)| *header) {
+            rows.insert(0, (true, Vec::new()));
+        }
+        let mut output = String::new();
+        for (i, (_, mut cells)) in rows.into_iter().enumerate() {
+            cells.resize(columns, String::new());
+            output.push('|');
+            for cell in cells {
+                output.push(' ');
+                output.push_str(&cell);
+                output.push_str(" |");
+            }
+            output.push('\n');
+            if i == 0 {
+                output.push('|');
+                output.push_str(&" --- |".repeat(columns));
+                output.push('\n');
+            }
+        }
+        output.pop();
+        output
+    }
+
+    /// Runs of inline items and the code blocks, lists and tables in between, as markdown blocks.
+    fn paragraph_parts(&mut self, par: &Paragraph) -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut run = String::new();
+        for (i, item) in par.items.iter().enumerate() {
+            self.child(NodeStep::Item(i), |w| {
+                let block = match item {
+                    ParagraphItem::Text(text) => {
+                        escaped_md(text, &mut run);
+                        None
+                    },
+                    ParagraphItem::MText(mtext) => {
+                        w.mtext(mtext, &mut run);
+                        None
+                    },
+                    ParagraphItem::Em(em) => {
+                        w.emphasis(em, &mut run);
+                        None
+                    },
+                    ParagraphItem::Link(link) => {
+                        w.link(link, &mut run);
+                        None
+                    },
+                    ParagraphItem::Quote(quote) => {
+                        w.inline_quote(quote, &mut run);
+                        None
+                    },
+                    ParagraphItem::FootnoteRef(footnote_ref) => {
+                        w.footnote_ref(footnote_ref, &mut run);
+                        None
+                    },
+                    ParagraphItem::Code(Ok(code)) => Some(w.code(code)),
+                    ParagraphItem::Code(Err(_)) => {
+                        w.unrepresented.push(Unrepresented::Code { path: w.path.clone() });
+                        None
+                    },
+                    ParagraphItem::List(list) => Some(w.list(list)),
+                    ParagraphItem::Table(table) => Some(w.table(table)),
+                    ParagraphItem::Data(data) => Some(w.table(&data.to_table())),
+                };
+                if let Some(block) = block {
+                    if !run.is_empty() {
+                        parts.push(std::mem::take(&mut run));
+                    }
+                    parts.push(block);
+                }
+            });
+        }
+        if !run.is_empty() {
+            parts.push(run);
+        }
+        parts
+    }
+
+    fn paragraph(&mut self, par: &Paragraph, blocks: &mut Vec<String>) {
+        self.meta(&par.tags, &par.props, &[], &[]);
+        blocks.extend(self.paragraph_parts(par));
+    }
+
+    fn section_items(&mut self, items: &[SectionItem], depth: usize, blocks: &mut Vec<String>) {
+        for (i, item) in items.iter().enumerate() {
+            self.child(NodeStep::Item(i), |w| match item {
+                SectionItem::Paragraph(par) => w.paragraph(par, blocks),
+                SectionItem::Section(section) => w.section(section, depth, blocks),
+                SectionItem::Quote(quote) => w.quote(quote, blocks),
+                SectionItem::Footnote(footnote) => w.footnote(footnote, blocks),
+            });
+        }
+    }
+
+    fn quote(&mut self, quote: &Quote, blocks: &mut Vec<String>) {
+        self.meta(&quote.tags, &quote.props, &[], &[]);
+        let mut inner = Vec::new();
+        for (i, item) in quote.items.iter().enumerate() {
+            self.child(NodeStep::Item(i), |w| match item {
+                QuoteItem::Paragraph(par) => w.paragraph(par, &mut inner),
+                QuoteItem::Quote(quote) => w.quote(quote, &mut inner),
+            });
+        }
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            let mut credit = String::from("— ");
+            self.credit(&quote.attribution, &quote.source, &mut credit);
+            inner.push(credit);
+        }
+        let content = inner.join("\n\n");
+        let block = match &quote.kind {
+            Some(kind) => prefixed(&format!("[!{}]\n{content}", kind.to_uppercase()), "> ", "> "),
+            None => prefixed(&content, "> ", "> "),
+        };
+        blocks.push(block);
+    }
+
+    fn footnote(&mut self, footnote: &FootnoteDef, blocks: &mut Vec<String>) {
+        self.meta(&footnote.tags, &footnote.props, &[], &[]);
+        let mut inner = Vec::new();
+        for (i, par) in footnote.items.iter().enumerate() {
+            self.child(NodeStep::Item(i), |w| w.paragraph(par, &mut inner));
+        }
+        blocks.push(prefixed(&inner.join("\n\n"), &format!("[^{}]: ", footnote.label), "    "));
+    }
+
+    fn section(&mut self, section: &Section, depth: usize, blocks: &mut Vec<String>) {
+        let tags = &section.tags;
+        let (used_tags, used_props): (&[&str], &[&str]) = if tags.contains("footnote-def") {
+            (&["footnote-def"], &["id"])
+        } else if tags.contains("blockquote-typed") {
+            (&["blockquote-typed"], &["blockquote-type"])
+        } else if tags.contains("blockquote") {
+            (&["blockquote"], &[])
+        } else {
+            (&[], &[])
+        };
+        self.meta(&section.tags, &section.props, used_tags, used_props);
+        if used_tags.is_empty() {
+            let mut heading = "#".repeat((depth + 1).min(6));
+            heading.push(' ');
+            self.child(NodeStep::Heading, |w| {
+                w.meta(&section.heading.tags, &section.heading.props, &[], &[]);
+                w.em_or_text(&section.heading.items, &mut heading);
+            });
+            blocks.push(heading);
+            self.section_items(&section.items, depth + 1, blocks);
+            return;
+        }
+        // the heading of quotes and footnote definitions only names them
+        let mut inner = Vec::new();
+        self.child(NodeStep::Heading, |w| {
+            w.meta(&section.heading.tags, &section.heading.props, &[], &[]);
+        });
+        self.section_items(&section.items, depth + 1, &mut inner);
+        let content = inner.join("\n\n");
+        let prop = |key| match section.props.get(key) {
+            Some(PropVal::String(val) | PropVal::Text(val)) => Some(val.as_str()),
+            _ => None,
+        };
+        let block = if tags.contains("footnote-def") {
+            let id = prop("id").unwrap_or_default();
+            let label = id.strip_prefix("footnote-").unwrap_or(id);
+            prefixed(&content, &format!("[^{label}]: "), "    ")
+        } else if tags.contains("blockquote-typed") {
+            let kind = prop("blockquote-type").unwrap_or("note").to_uppercase();
+            prefixed(&format!("[!{kind}]\n{content}"), "> ", "> ")
+        } else {
+            prefixed(&content, "> ", "> ")
+        };
+        blocks.push(block);
+    }
+
+    fn nav(&mut self, nav: &Nav, lines: &mut Vec<String>, indent: usize) {
+        self.meta(&nav.tags, &nav.props, &[], &[]);
+        let mut indent = indent;
+        if !nav.description.is_empty() {
+            let mut line = " ".repeat(indent);
+            line.push_str("- ");
+            escaped_md(&nav.description, &mut line);
+            lines.push(line);
+            indent += 2;
+        }
+        for (i, link) in nav.links.iter().enumerate() {
+            let mut line = " ".repeat(indent);
+            line.push_str("- ");
+            self.child(NodeStep::Link(i), |w| w.link(link, &mut line));
+            lines.push(line);
+        }
+        for (i, sub) in nav.subs.iter().enumerate() {
+            self.child(NodeStep::Sub(i), |w| w.nav(sub, lines, indent));
+        }
+    }
+}
+
+/// Write a document as GitHub flavoured markdown.
+/// Returns the tags, properties and code that could not be written, in the order of the document.
+pub fn doc_to_markdown(
+    doc: &Doc, options: &MarkdownOptions, output: &mut String,
+) -> Vec<Unrepresented> {
+    let mut writer = Writer { options, path: NodePath::new(), unrepresented: Vec::new() };
+    writer.meta(&doc.tags, &doc.props, &[], &[]);
+    let mut blocks = Vec::new();
+    for (i, nav) in doc.navs.iter().enumerate() {
+        let mut lines = Vec::new();
+        writer.child(NodeStep::Nav(i), |w| w.nav(nav, &mut lines, 0));
+        if !lines.is_empty() {
+            blocks.push(lines.join("\n"));
+        }
+    }
+    for (i, item) in doc.items.iter().enumerate() {
+        writer.child(NodeStep::Item(i), |w| match item {
+            DocItem::Paragraph(par) => w.paragraph(par, &mut blocks),
+            DocItem::Section(section) => w.section(section, 0, &mut blocks),
+            DocItem::Quote(quote) => w.quote(quote, &mut blocks),
+            DocItem::Footnote(footnote) => w.footnote(footnote, &mut blocks),
+        });
+    }
+    for block in blocks {
+        output.push_str(&block);
+        output.push_str("\n\n");
+    }
+    output.pop();
+    writer.unrepresented
+}
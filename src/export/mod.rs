@@ -0,0 +1,11 @@
+pub mod html;
+pub mod markdown;
+pub mod ansi;
+pub mod gemtext;
+pub mod plain;
+pub mod roff;
+pub mod latex;
+pub mod typst;
+pub mod ssml;
+#[cfg(feature = "epub")]
+pub mod epub;
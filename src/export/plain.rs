@@ -0,0 +1,423 @@
+//! Linear plain text, for screen readers, speech and other accessibility pipelines.
+//!
+//! [`to_plain_text`] reads a document in order and keeps its structure as [`PlainBlock`]s:
+//! sections keep their heading, lists and tables are announced before their items and every
+//! table cell says where it is, like `Row 2, column B, Price`. Datasets are read as tables, see
+//! [`Data::to_table`].
+//! Text is deemphasised; links are expanded with their `bg-text` property, images are announced
+//! by their `type-hint` property and footnote references say the number of their footnote.
+//! Footnotes are numbered like [`Doc::resolve_footnotes`] does and read where they are defined;
+//! sections with the `footnote-def` tag are numbered after those, in the order they appear.
+//! Quotes are announced by their kind, who is quoted and their source; inline quotes go between
+//! quotation marks.
+//!
+//! [`plain_text_out`] writes the blocks as indented lines when one string is needed after all.
+
+use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
+
+/// Options for [`to_plain_text`].
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct PlainTextOptions {
+    /// Also read code that hints to be replaced by its results.
+    pub include_replace_code: bool,
+}
+
+/// Piece of linear text, with the structure it had in the document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlainBlock {
+    /// Section with the level and the text of its heading.
+    Section { level: u8, heading: String, blocks: Vec<PlainBlock> },
+    /// Footnote definition, or section with the `footnote-def` tag.
+    Footnote { number: usize, blocks: Vec<PlainBlock> },
+    /// Quote, announced by its kind, attribution and source.
+    Quote { announcement: String, blocks: Vec<PlainBlock> },
+    /// Navigation with its description.
+    Navigation { description: String, blocks: Vec<PlainBlock> },
+    /// Running text.
+    Text(String),
+    /// List, announced by how many items it has and how many are checked.
+    List { announcement: String, items: Vec<PlainItem> },
+    /// Table, announced by its size, with a cell for every item of every row.
+    Table { announcement: String, cells: Vec<PlainItem> },
+    /// Code, announced by its language.
+    Code { announcement: String, code: String },
+}
+
+/// Item of a list or cell of a table: a label that says where it is and what it holds.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct PlainItem {
+    pub label: String,
+    pub blocks: Vec<PlainBlock>,
+}
+
+struct Linearizer<'a> {
+    options: &'a PlainTextOptions,
+    footnotes: HashMap<String, usize>,
+}
+
+pub(crate) fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("1 {word}")
+    } else {
+        format!("{count} {word}s")
+    }
+}
+
+/// Name a column like a spreadsheet does: A to Z, then AA, AB and so on.
+pub(crate) fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.iter().rev().map(|b| *b as char).collect()
+}
+
+/// Name of a quote: its kind starting with a capital, or `Quote` when it has no kind.
+pub(crate) fn quote_name(kind: &Option<String>) -> String {
+    let Some(kind) = kind else { return "Quote".to_string() };
+    let mut chars = kind.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+fn is_footnote_def(section: &Section) -> bool {
+    section.tags.contains("footnote-def")
+}
+
+fn prop_text(props: &Props, key: &str) -> Option<String> {
+    match props.get(key) {
+        Some(PropVal::String(string)) | Some(PropVal::Text(string)) => Some(string.clone()),
+        _ => None,
+    }
+}
+
+fn number_footnotes_in_section(section: &Section, footnotes: &mut HashMap<String, usize>) {
+    if is_footnote_def(section) && let Some(id) = prop_text(&section.props, "id") {
+        let number = footnotes.len() + 1;
+        footnotes.entry(id).or_insert(number);
+    }
+    for item in &section.items {
+        if let SectionItem::Section(section) = item {
+            number_footnotes_in_section(section, footnotes);
+        }
+    }
+}
+
+/// Numbers of footnotes by the id they are linked to with: `footnote-{label}` for footnote
+/// definitions, the `id` property for sections with the `footnote-def` tag.
+pub(crate) fn number_footnotes(doc: &Doc) -> HashMap<String, usize> {
+    let resolved = doc.resolve_footnotes();
+    let mut footnotes: HashMap<String, usize> = resolved.definitions.iter()
+        .enumerate()
+        .map(|(i, def)| (format!("footnote-{}", def.label), i + 1))
+        .collect();
+    for item in &doc.items {
+        if let DocItem::Section(section) = item {
+            number_footnotes_in_section(section, &mut footnotes);
+        }
+    }
+    footnotes
+}
+
+fn push_text(text: &mut String, blocks: &mut Vec<PlainBlock>) {
+    let trimmed = text.trim();
+    if !trimmed.is_empty() {
+        blocks.push(PlainBlock::Text(trimmed.to_string()));
+    }
+    text.clear();
+}
+
+impl Linearizer<'_> {
+    fn footnote_ref(&self, footnote_ref: &FootnoteRef) -> String {
+        match self.footnotes.get(&format!("footnote-{}", footnote_ref.label)) {
+            Some(number) => format!("footnote {number}"),
+            None => format!("footnote {}", footnote_ref.label),
+        }
+    }
+
+    fn link(&self, link: &Link) -> String {
+        if link.tags.contains("footnote-ref")
+            && let Some(number) = link.url.strip_prefix('#').and_then(|id| self.footnotes.get(id))
+        {
+            return format!("footnote {number}");
+        }
+        let mut text = link.items.deemphasise();
+        if text.trim().is_empty() {
+            text = link.url.clone();
+        }
+        if let Some(hint) = prop_text(&link.props, "type-hint") {
+            text = format!("{hint}: {text}");
+        }
+        if let Some(bg) = prop_text(&link.props, "bg-text") {
+            text = format!("{text} ({bg})");
+        }
+        text
+    }
+
+    /// Who is quoted and the source, separated by a comma.
+    fn credit(&self, attribution: &[EmOrText], source: &Option<Link>) -> String {
+        let mut credit = attribution.deemphasise();
+        if let Some(source) = source {
+            if !credit.is_empty() {
+                credit.push_str(", ");
+            }
+            credit.push_str(&self.link(source));
+        }
+        credit
+    }
+
+    fn inline_quote(&self, quote: &InlineQuote) -> String {
+        let mut text = format!("“{}”", quote.items.deemphasise());
+        let credit = self.credit(&quote.attribution, &quote.source);
+        if !credit.is_empty() {
+            text.push_str(&format!(" ({credit})"));
+        }
+        text
+    }
+
+    fn quote(&self, quote: &Quote) -> PlainBlock {
+        let mut announcement = quote_name(&quote.kind);
+        if !quote.attribution.is_empty() {
+            announcement.push_str(" by ");
+            announcement.push_str(&quote.attribution.deemphasise());
+        }
+        if let Some(source) = &quote.source {
+            announcement.push_str(", from ");
+            announcement.push_str(&self.link(source));
+        }
+        let mut blocks = Vec::new();
+        for item in &quote.items {
+            match item {
+                QuoteItem::Paragraph(par) => blocks.extend(self.paragraph(par)),
+                QuoteItem::Quote(quote) => blocks.push(self.quote(quote)),
+            }
+        }
+        PlainBlock::Quote { announcement, blocks }
+    }
+
+    fn code(&self, code: &Result<CodeBlock, CodeIdentError>) -> Option<PlainBlock> {
+        let code = code.as_ref().ok()?;
+        if code.mode == CodeModeHint::Replace && !self.options.include_replace_code {
+            return None;
+        }
+        let announcement = if code.language.is_empty() || code.language == "plain" {
+            "Code".to_string()
+        } else {
+            format!("Code in {}", code.language)
+        };
+        Some(PlainBlock::Code { announcement, code: code.code.clone() })
+    }
+
+    fn list(&self, list: &List) -> PlainBlock {
+        let count = plural(list.items.len(), "item");
+        let checked = list.items.iter().filter(|par| par.tags.contains("checked")).count();
+        let announcement = match list.ltype {
+            ListType::Identical => format!("List of {count}."),
+            ListType::Distinct => format!("Numbered list of {count}."),
+            ListType::Checked => format!("Checklist of {count}, {checked} checked."),
+        };
+        let items = list.items.iter().enumerate().map(|(i, par)| {
+            let label = match list.ltype {
+                ListType::Checked if par.tags.contains("checked") => {
+                    format!("Item {}, checked", i + 1)
+                },
+                ListType::Checked => format!("Item {}, not checked", i + 1),
+                _ => format!("Item {}", i + 1),
+            };
+            PlainItem { label, blocks: self.paragraph(par) }
+        }).collect();
+        PlainBlock::List { announcement, items }
+    }
+
+    fn table(&self, table: &Table) -> PlainBlock {
+        let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0);
+        let mut announcement = format!(
+            "Table of {} and {}.", plural(table.rows.len(), "row"), plural(columns, "column")
+        );
+        let header = table.rows.iter().find(|row| row.is_header);
+        if table.rows.first().is_some_and(|row| row.is_header) {
+            announcement.push_str(" The first row is a header.");
+        }
+        let mut cells = Vec::new();
+        for (r, row) in table.rows.iter().enumerate() {
+            for (c, par) in row.items.iter().enumerate() {
+                let mut label = format!("Row {}, column {}", r + 1, column_name(c));
+                if !row.is_header
+                    && let Some(name) = header.and_then(|header| header.items.get(c))
+                {
+                    let name = self.inline_text(name);
+                    if !name.is_empty() {
+                        label.push_str(", ");
+                        label.push_str(&name);
+                    }
+                }
+                cells.push(PlainItem { label, blocks: self.paragraph(par) });
+            }
+        }
+        PlainBlock::Table { announcement, cells }
+    }
+
+    /// Only the text of a paragraph, to name table columns with.
+    fn inline_text(&self, par: &Paragraph) -> String {
+        self.paragraph(par).iter().filter_map(|block| match block {
+            PlainBlock::Text(text) => Some(text.as_str()),
+            _ => None,
+        }).collect::<Vec<_>>().join(" ")
+    }
+
+    fn paragraph(&self, par: &Paragraph) -> Vec<PlainBlock> {
+        let mut blocks = Vec::new();
+        let mut text = String::new();
+        for item in &par.items {
+            match item {
+                ParagraphItem::Text(string) => text.push_str(string),
+                ParagraphItem::MText(mtext) => text.push_str(&mtext.text),
+                ParagraphItem::Em(em) => text.push_str(&em.deemphasise()),
+                ParagraphItem::Link(link) => text.push_str(&self.link(link)),
+                ParagraphItem::Quote(quote) => text.push_str(&self.inline_quote(quote)),
+                ParagraphItem::FootnoteRef(footnote_ref) => {
+                    text.push_str(&self.footnote_ref(footnote_ref));
+                },
+                ParagraphItem::Code(code) => {
+                    push_text(&mut text, &mut blocks);
+                    blocks.extend(self.code(code));
+                },
+                ParagraphItem::List(list) => {
+                    push_text(&mut text, &mut blocks);
+                    blocks.push(self.list(list));
+                },
+                ParagraphItem::Table(table) => {
+                    push_text(&mut text, &mut blocks);
+                    blocks.push(self.table(table));
+                },
+                ParagraphItem::Data(data) => {
+                    push_text(&mut text, &mut blocks);
+                    blocks.push(self.table(&data.to_table()));
+                },
+            }
+        }
+        push_text(&mut text, &mut blocks);
+        blocks
+    }
+
+    fn section(&self, section: &Section) -> PlainBlock {
+        let mut blocks = Vec::new();
+        for item in &section.items {
+            match item {
+                SectionItem::Paragraph(par) => blocks.extend(self.paragraph(par)),
+                SectionItem::Section(section) => blocks.push(self.section(section)),
+                SectionItem::Quote(quote) => blocks.push(self.quote(quote)),
+                SectionItem::Footnote(footnote) => blocks.push(self.footnote(footnote)),
+            }
+        }
+        let number = prop_text(&section.props, "id").and_then(|id| self.footnotes.get(&id));
+        match number {
+            Some(number) if is_footnote_def(section) => {
+                PlainBlock::Footnote { number: *number, blocks }
+            },
+            _ => PlainBlock::Section {
+                level: section.heading.level,
+                heading: section.heading.items.deemphasise(),
+                blocks,
+            },
+        }
+    }
+
+    fn footnote(&self, footnote: &FootnoteDef) -> PlainBlock {
+        let blocks = footnote.items.iter().flat_map(|par| self.paragraph(par)).collect();
+        let number = self.footnotes.get(&format!("footnote-{}", footnote.label));
+        PlainBlock::Footnote { number: number.copied().unwrap_or_default(), blocks }
+    }
+
+    fn nav(&self, nav: &Nav) -> PlainBlock {
+        let mut blocks: Vec<PlainBlock> = nav.links.iter()
+            .map(|link| PlainBlock::Text(self.link(link)))
+            .collect();
+        blocks.extend(nav.subs.iter().map(|sub| self.nav(sub)));
+        PlainBlock::Navigation { description: nav.description.clone(), blocks }
+    }
+}
+
+/// Read a document as linear text, keeping its structure.
+pub fn to_plain_text(doc: &Doc, options: &PlainTextOptions) -> Vec<PlainBlock> {
+    let linearizer = Linearizer { options, footnotes: number_footnotes(doc) };
+    let mut blocks: Vec<PlainBlock> = doc.navs.iter().map(|nav| linearizer.nav(nav)).collect();
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => blocks.extend(linearizer.paragraph(par)),
+            DocItem::Section(section) => blocks.push(linearizer.section(section)),
+            DocItem::Quote(quote) => blocks.push(linearizer.quote(quote)),
+            DocItem::Footnote(footnote) => blocks.push(linearizer.footnote(footnote)),
+        }
+    }
+    blocks
+}
+
+fn line_out(depth: usize, text: &str, output: &mut String) {
+    for line in text.lines() {
+        for _ in 0..depth {
+            output.push_str("  ");
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+}
+
+fn labeled_out(label: &str, blocks: &[PlainBlock], depth: usize, output: &mut String) {
+    match blocks.split_first() {
+        Some((PlainBlock::Text(text), rest)) => {
+            line_out(depth, &format!("{label}: {text}"), output);
+            blocks_out(rest, depth + 1, output);
+        },
+        _ => {
+            line_out(depth, &format!("{label}:"), output);
+            blocks_out(blocks, depth + 1, output);
+        },
+    }
+}
+
+fn blocks_out(blocks: &[PlainBlock], depth: usize, output: &mut String) {
+    for block in blocks {
+        match block {
+            PlainBlock::Section { level, heading, blocks } => {
+                line_out(depth, &format!("Heading level {}: {heading}", level + 1), output);
+                blocks_out(blocks, depth + 1, output);
+            },
+            PlainBlock::Footnote { number, blocks } => {
+                labeled_out(&format!("Footnote {number}"), blocks, depth, output);
+            },
+            PlainBlock::Quote { announcement, blocks } => {
+                labeled_out(announcement, blocks, depth, output);
+            },
+            PlainBlock::Navigation { description, blocks } => {
+                if description.is_empty() {
+                    line_out(depth, "Navigation:", output);
+                } else {
+                    line_out(depth, &format!("Navigation, {description}:"), output);
+                }
+                blocks_out(blocks, depth + 1, output);
+            },
+            PlainBlock::Text(text) => line_out(depth, text, output),
+            PlainBlock::List { announcement, items }
+            | PlainBlock::Table { announcement, cells: items } => {
+                line_out(depth, announcement, output);
+                for item in items {
+                    labeled_out(&item.label, &item.blocks, depth + 1, output);
+                }
+            },
+            PlainBlock::Code { announcement, code } => {
+                line_out(depth, &format!("{announcement}:"), output);
+                line_out(depth + 1, code, output);
+            },
+        }
+    }
+}
+
+/// Write blocks as lines of text, nested blocks indented by two spaces.
+pub fn plain_text_out(blocks: &[PlainBlock], output: &mut String) {
+    blocks_out(blocks, 0, output);
+}
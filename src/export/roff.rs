@@ -0,0 +1,464 @@
+//! Roff with the `man` macros, to read with `man`.
+//!
+//! - The `.TH` header is filled from the properties of the document: `name` (or else `title`)
+//!   in capitals, `section` (`1` if there is none), `date` when it is a date, and `source` and
+//!   `manual` when they are there.
+//! - Top level sections become `.SH`, nested sections `.SS`.
+//! - Light emphasis becomes italic, medium emphasis bold and strong emphasis bold italic.
+//!   Deemphasis is written as plain text. Text with the `code` tag is bold, as literals are in
+//!   manual pages. Line breaks in text become `.br`.
+//! - Links become `.UR`/`.UE`. Footnote references, and links with the `footnote-ref` tag,
+//!   become `[label]`.
+//! - Lists become `.IP` paragraphs with a bullet, number or checkbox. Items of bullet lists
+//!   with a line break in their text become `.TP` paragraphs, with the first line as tag.
+//!   Lists, tables and code in items are indented with `.RS`/`.RE`.
+//! - Tables become `tbl` markup, with a line under a header row. Only the inline content of
+//!   cells is written. Datasets become tables as well, see [`Data::to_table`]. Documents with
+//!   tables start with the comment that tells `man` to run `tbl`.
+//! - Code becomes an indented `.EX`/`.EE` example.
+//! - Quotes become indented paragraphs, starting with their kind in bold and ending with their
+//!   attribution and source after a dash. Quotes in paragraphs are put in quotation marks.
+//! - Sections with the `blockquote` tag become indented paragraphs without their heading;
+//!   sections with the `blockquote-typed` tag start with their heading in bold. Sections with
+//!   the `footnote-def` tag become `.IP [label]` paragraphs, just like footnote definitions.
+//! - Navigations and metadata are left out.
+
+use crate::*;
+use crate::export::plain::quote_name;
+
+fn is_quote(section: &Section) -> bool {
+    section.tags.contains("blockquote") || section.tags.contains("blockquote-typed")
+}
+
+fn prop_text<'a>(props: &'a Props, key: &str) -> Option<&'a str> {
+    match props.get(key) {
+        Some(PropVal::String(val) | PropVal::Text(val)) => Some(val),
+        _ => None,
+    }
+}
+
+fn at_line_start(output: &str) -> bool {
+    output.is_empty() || output.ends_with('\n')
+}
+
+/// Escape text for text lines. Spaces at the start of a line are left out, as roff would
+/// start a new paragraph on them.
+fn escaped_roff(text: &str, output: &mut String) {
+    for c in text.chars() {
+        let line_start = at_line_start(output);
+        match c {
+            '\\' => output.push_str("\\e"),
+            '-' => output.push_str("\\-"),
+            '"' => output.push_str("\\(dq"),
+            ' ' | '\t' if line_start => { },
+            '.' | '\'' if line_start => {
+                output.push_str("\\&");
+                output.push(c);
+            },
+            '\n' => {
+                if !line_start {
+                    output.push('\n');
+                }
+                output.push_str(".br\n");
+            },
+            _ => output.push(c),
+        }
+    }
+}
+
+/// Escape a line of code, keeping its indentation.
+fn escaped_code(line: &str, output: &mut String) {
+    if line.starts_with(['.', '\'']) {
+        output.push_str("\\&");
+    }
+    for c in line.chars() {
+        match c {
+            '\\' => output.push_str("\\e"),
+            '-' => output.push_str("\\-"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// Argument of a request, in quotes.
+fn quoted_arg(text: &str) -> String {
+    let mut arg = String::new();
+    escaped_roff(&text.replace('\n', " "), &mut arg);
+    format!("\"{arg}\"")
+}
+
+struct Writer<'a> {
+    output: &'a mut String,
+    /// Whether the last line is a `.UE` that punctuation right after the link still goes on.
+    link_end: bool,
+    tables: bool,
+}
+
+impl<'a> Writer<'a> {
+    fn new(output: &'a mut String) -> Self {
+        Self { output, link_end: false, tables: false }
+    }
+
+    fn finish_line(&mut self) {
+        if !at_line_start(self.output) {
+            let end = self.output.trim_end_matches(' ').len();
+            self.output.truncate(end);
+            self.output.push('\n');
+        }
+        self.link_end = false;
+    }
+
+    fn request(&mut self, request: &str) {
+        self.finish_line();
+        self.output.push_str(request);
+        self.output.push('\n');
+    }
+
+    /// Escapes that change the font or name a special character.
+    fn font(&mut self, escape: &str) {
+        self.finish_line_after_link();
+        self.output.push_str(escape);
+    }
+
+    fn finish_line_after_link(&mut self) {
+        if self.link_end {
+            self.finish_line();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let mut text = text;
+        if self.link_end {
+            let end = text.find(char::is_whitespace).unwrap_or(text.len());
+            if end > 0 {
+                self.output.push(' ');
+                escaped_roff(&text[..end], self.output);
+                text = &text[end..];
+            }
+            self.finish_line();
+        }
+        escaped_roff(text, self.output);
+    }
+
+    fn emphasis(&mut self, em: &Emphasis) {
+        if em.text.is_empty() {
+            return;
+        }
+        let open = match (em.etype, em.strength) {
+            (EmType::Deemphasis, _) => {
+                self.text(&em.text);
+                return;
+            },
+            (EmType::Emphasis, EmStrength::Light) => "\\fI",
+            (EmType::Emphasis, EmStrength::Medium) => "\\fB",
+            (EmType::Emphasis, EmStrength::Strong) => "\\f(BI",
+        };
+        self.font(open);
+        self.text(&em.text);
+        self.font("\\fR");
+    }
+
+    fn em_or_text(&mut self, items: &[EmOrText]) {
+        for item in items {
+            match item {
+                EmOrText::Text(text) => self.text(text),
+                EmOrText::Em(em) => self.emphasis(em),
+            }
+        }
+    }
+
+    fn link(&mut self, link: &Link) {
+        if link.tags.contains("footnote-ref")
+            && let Some(label) = link.url.strip_prefix("#footnote-")
+        {
+            self.text(&format!("[{label}]"));
+            return;
+        }
+        let url = link.url.replace('\\', "\\e").replace(' ', "%20");
+        self.request(&format!(".UR {url}"));
+        self.em_or_text(&link.items);
+        self.finish_line();
+        self.output.push_str(".UE");
+        self.link_end = true;
+    }
+
+    fn inline(&mut self, item: &ParagraphItem) {
+        match item {
+            ParagraphItem::Text(text) => self.text(text),
+            ParagraphItem::MText(mtext) if mtext.tags.contains("code") => {
+                self.font("\\fB");
+                self.text(&mtext.text);
+                self.font("\\fR");
+            },
+            ParagraphItem::MText(mtext) => self.text(&mtext.text),
+            ParagraphItem::Em(em) => self.emphasis(em),
+            ParagraphItem::Link(link) => self.link(link),
+            ParagraphItem::FootnoteRef(footnote_ref) => {
+                self.text(&format!("[{}]", footnote_ref.label));
+            },
+            ParagraphItem::Quote(quote) => {
+                self.font("\\(lq");
+                self.em_or_text(&quote.items);
+                self.font("\\(rq");
+                if !quote.attribution.is_empty() || quote.source.is_some() {
+                    self.text(" (");
+                    self.credit(&quote.attribution, &quote.source);
+                    self.text(")");
+                }
+            },
+            _ => { },
+        }
+    }
+
+    fn credit(&mut self, attribution: &[EmOrText], source: &Option<Link>) {
+        self.em_or_text(attribution);
+        if let Some(source) = source {
+            if !attribution.is_empty() {
+                self.text(", ");
+            }
+            self.link(source);
+        }
+    }
+
+    fn code(&mut self, code: &CodeBlock) {
+        self.request(".PP");
+        self.request(".RS 4");
+        self.request(".EX");
+        for line in code.code.lines() {
+            escaped_code(line, self.output);
+            self.output.push('\n');
+        }
+        self.request(".EE");
+        self.request(".RE");
+    }
+
+    /// Splits the item of a bullet list at its first line break, into the tag and the body of
+    /// a `.TP` paragraph.
+    fn tagged_item(par: &Paragraph) -> Option<(Vec<ParagraphItem>, Paragraph)> {
+        for (i, item) in par.items.iter().enumerate() {
+            match item {
+                ParagraphItem::Text(text) => if let Some((first, rest)) = text.split_once('\n') {
+                    let mut tag = par.items[..i].to_vec();
+                    tag.push(ParagraphItem::Text(first.to_string()));
+                    let mut body = vec![ParagraphItem::Text(rest.to_string())];
+                    body.extend_from_slice(&par.items[i + 1..]);
+                    return Some((tag, Paragraph { items: body, ..Default::default() }));
+                },
+                ParagraphItem::List(_) | ParagraphItem::Table(_) | ParagraphItem::Data(_)
+                    | ParagraphItem::Code(_) => return None,
+                _ => { },
+            }
+        }
+        None
+    }
+
+    fn list(&mut self, list: &List) {
+        for (i, par) in list.items.iter().enumerate() {
+            if list.ltype == ListType::Identical
+                && let Some((tag, body)) = Self::tagged_item(par)
+            {
+                self.request(".TP");
+                for item in &tag {
+                    self.inline(item);
+                }
+                self.finish_line();
+                self.paragraph(&body, true, true);
+                continue;
+            }
+            let request = match list.ltype {
+                ListType::Identical => ".IP \\(bu 2".to_string(),
+                ListType::Distinct => format!(".IP {}. 4", i + 1),
+                ListType::Checked if par.tags.contains("checked") => ".IP [x] 4".to_string(),
+                ListType::Checked => ".IP [\\ ] 4".to_string(),
+            };
+            self.request(&request);
+            self.paragraph(par, true, true);
+        }
+    }
+
+    fn cell(par: &Paragraph) -> String {
+        let mut text = String::new();
+        let mut writer = Writer::new(&mut text);
+        for item in &par.items {
+            writer.inline(item);
+        }
+        writer.finish_line();
+        let text = text.trim_end().replace('\t', " ");
+        if text.contains('\n') {
+            format!("T{{\n{text}\nT}}")
+        } else if text == "_" || text == "=" {
+            format!("\\&{text}")
+        } else {
+            text
+        }
+    }
+
+    fn table(&mut self, table: &Table) {
+        self.tables = true;
+        let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0).max(1);
+        let format = |font: &str| vec![format!("l{font}"); columns].join(" ");
+        self.request(".PP");
+        self.request(".TS");
+        if table.rows.first().is_some_and(|row| row.is_header) {
+            self.request(&format("b"));
+        }
+        self.request(&format!("{}.", format("")));
+        for row in &table.rows {
+            let cells: Vec<String> = row.items.iter().map(Self::cell).collect();
+            self.request(&cells.join("\t"));
+            if row.is_header {
+                self.request("_");
+            }
+        }
+        self.request(".TE");
+    }
+
+    /// Write a paragraph. Nested paragraphs are in list items: they continue with `.IP` and
+    /// indent their blocks.
+    fn paragraph(&mut self, par: &Paragraph, nested: bool, started: bool) {
+        let mut started = started;
+        for item in &par.items {
+            let block = matches!(
+                item,
+                ParagraphItem::Code(_) | ParagraphItem::List(_) | ParagraphItem::Table(_)
+                    | ParagraphItem::Data(_)
+            );
+            if !block {
+                if !started {
+                    self.request(if nested { ".IP" } else { ".PP" });
+                    started = true;
+                }
+                self.inline(item);
+                continue;
+            }
+            started = false;
+            if nested {
+                self.request(".RS");
+            }
+            match item {
+                ParagraphItem::Code(Ok(code)) => self.code(code),
+                ParagraphItem::List(list) => self.list(list),
+                ParagraphItem::Table(table) => self.table(table),
+                ParagraphItem::Data(data) => self.table(&data.to_table()),
+                _ => { },
+            }
+            if nested {
+                self.request(".RE");
+            }
+        }
+    }
+
+    fn quote(&mut self, quote: &Quote) {
+        self.request(".RS 4");
+        if quote.kind.is_some() {
+            self.request(".PP");
+            self.font("\\fB");
+            self.text(&quote_name(&quote.kind));
+            self.font("\\fR");
+        }
+        for item in &quote.items {
+            match item {
+                QuoteItem::Paragraph(par) => self.paragraph(par, false, false),
+                QuoteItem::Quote(quote) => self.quote(quote),
+            }
+        }
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            self.request(".PP");
+            self.font("\\(em");
+            self.text(" ");
+            self.credit(&quote.attribution, &quote.source);
+        }
+        self.request(".RE");
+    }
+
+    fn section_items(&mut self, section: &Section, depth: usize, nested: bool) {
+        for (i, item) in section.items.iter().enumerate() {
+            match item {
+                SectionItem::Paragraph(par) => self.paragraph(par, nested, nested && i == 0),
+                SectionItem::Section(section) => self.section(section, depth + 1),
+                SectionItem::Quote(quote) => self.quote(quote),
+                SectionItem::Footnote(footnote) => self.footnote(footnote),
+            }
+        }
+    }
+
+    fn footnote(&mut self, footnote: &FootnoteDef) {
+        self.request(&format!(".IP [{}] 4", footnote.label));
+        for (i, par) in footnote.items.iter().enumerate() {
+            self.paragraph(par, true, i == 0);
+        }
+    }
+
+    fn section(&mut self, section: &Section, depth: usize) {
+        if is_quote(section) {
+            self.request(".RS 4");
+            if section.tags.contains("blockquote-typed") {
+                self.request(".PP");
+                self.font("\\fB");
+                self.em_or_text(&section.heading.items);
+                self.font("\\fR");
+            }
+            self.section_items(section, depth, false);
+            self.request(".RE");
+        } else if section.tags.contains("footnote-def") {
+            let id = prop_text(&section.props, "id").unwrap_or_default();
+            let label = id.strip_prefix("footnote-").unwrap_or(id);
+            self.request(&format!(".IP [{label}] 4"));
+            self.section_items(section, depth, true);
+        } else {
+            let mut heading = String::new();
+            let mut writer = Writer::new(&mut heading);
+            writer.em_or_text(&section.heading.items);
+            let heading = heading.replace("\n.br\n", " ").replace('\n', " ");
+            let request = if depth == 0 { ".SH" } else { ".SS" };
+            self.request(&format!("{request} {}", heading.trim()));
+            self.section_items(section, depth, false);
+        }
+    }
+}
+
+fn title_header(doc: &Doc) -> String {
+    let name = prop_text(&doc.props, "name")
+        .or_else(|| prop_text(&doc.props, "title"))
+        .unwrap_or_default()
+        .to_uppercase();
+    let section = match doc.props.get("section") {
+        Some(PropVal::Int(section)) => section.to_string(),
+        Some(PropVal::String(section) | PropVal::Text(section)) => section.clone(),
+        _ => "1".to_string(),
+    };
+    let date = match doc.props.get("date") {
+        Some(PropVal::Date(date)) => {
+            format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+        },
+        _ => String::new(),
+    };
+    let source = prop_text(&doc.props, "source").unwrap_or_default();
+    let manual = prop_text(&doc.props, "manual").unwrap_or_default();
+    let mut args = vec![name.as_str(), &section, &date, source, manual];
+    while args.len() > 2 && args.last().is_some_and(|arg| arg.is_empty()) {
+        args.pop();
+    }
+    let args: Vec<String> = args.into_iter().map(quoted_arg).collect();
+    format!(".TH {}\n", args.join(" "))
+}
+
+/// Write a document as a manual page.
+pub fn doc_to_roff(doc: &Doc, output: &mut String) {
+    let mut body = String::new();
+    let mut writer = Writer::new(&mut body);
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => writer.paragraph(par, false, false),
+            DocItem::Section(section) => writer.section(section, 0),
+            DocItem::Quote(quote) => writer.quote(quote),
+            DocItem::Footnote(footnote) => writer.footnote(footnote),
+        }
+    }
+    writer.finish_line();
+    if writer.tables {
+        output.push_str("'\\\" t\n");
+    }
+    output.push_str(&title_header(doc));
+    output.push_str(&body);
+}
@@ -0,0 +1,531 @@
+//! Speech Synthesis Markup Language, for text to speech.
+//!
+//! How things sound is up to the listener: a [`SpeechProfile`] decides the voice of every
+//! strength and type of emphasis, of headings, links and code, and how long the pauses around
+//! headings and between items are. Like the style profile of [`crate::export::ansi`], a profile
+//! can be read from the properties of an incodoc file:
+//!
+//! ``` incodoc
+//! props {
+//!     ("strong-emphasis", "volume:x-loud rate:slow"),
+//!     ("light-deemphasis", "voice:en-GB-Ryan"),
+//!     ("heading-pause", 800),
+//!     ("announce-levels", "off"),
+//! },
+//! ```
+//!
+//! Voices are written as words: `voice:` with the name of a voice of the speech engine, and
+//! `rate:`, `pitch:` and `volume:` with a value as SSML takes it, like `rate:slow`,
+//! `pitch:+10%` or `volume:-6dB`. `plain` is the voice of the text around it.
+//!
+//! The document is read like [`crate::export::plain`] reads it: headings are announced with
+//! their level, lists and tables are announced before their items and every item and table cell
+//! says where it is; datasets are read as tables. Links are followed by their `bg-text` property.
+//! Quotes are announced by their kind, their end is too, and who is quoted and the source are
+//! read before the end; inline quotes are read between "quote" and "unquote". Footnote references
+//! say the number of their footnote, which is announced where it is defined. Code that hints to be
+//! replaced by its results is left out, just like navigations.
+
+use crate::*;
+use crate::actions::deemphasise::DeEmphasise;
+use crate::export::ansi::ProfileError;
+use crate::export::html::escaped_out;
+use crate::export::plain::{ plural, column_name, number_footnotes, quote_name };
+use crate::parsing::parse;
+
+/// How a piece of text is spoken.
+#[derive(Clone, Default, Hash, Debug, Eq, PartialEq)]
+pub struct Voice {
+    /// Name of a voice of the speech engine.
+    pub name: Option<String>,
+    pub rate: Option<String>,
+    pub pitch: Option<String>,
+    pub volume: Option<String>,
+}
+
+impl Voice {
+    /// Read a voice from words like `rate:slow pitch:high`.
+    pub fn from_words(words: &str) -> Option<Self> {
+        let mut voice = Self::default();
+        for word in words.split_whitespace() {
+            if word == "plain" {
+                continue;
+            }
+            let (key, value) = word.split_once(':')?;
+            if value.is_empty() {
+                return None;
+            }
+            let slot = match key {
+                "voice" => &mut voice.name,
+                "rate" => &mut voice.rate,
+                "pitch" => &mut voice.pitch,
+                "volume" => &mut voice.volume,
+                _ => return None,
+            };
+            *slot = Some(value.to_string());
+        }
+        Some(voice)
+    }
+
+    fn prosody(&self) -> Vec<(&str, &str)> {
+        [("rate", &self.rate), ("pitch", &self.pitch), ("volume", &self.volume)]
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value.as_deref()?)))
+            .collect()
+    }
+
+    fn open_out(&self, output: &mut String) {
+        if let Some(name) = &self.name {
+            output.push_str("<voice name=\"");
+            escaped_out(name, output);
+            output.push_str("\">");
+        }
+        let prosody = self.prosody();
+        if !prosody.is_empty() {
+            output.push_str("<prosody");
+            for (name, value) in prosody {
+                output.push(' ');
+                output.push_str(name);
+                output.push_str("=\"");
+                escaped_out(value, output);
+                output.push('"');
+            }
+            output.push('>');
+        }
+    }
+
+    fn close_out(&self, output: &mut String) {
+        if !self.prosody().is_empty() {
+            output.push_str("</prosody>");
+        }
+        if self.name.is_some() {
+            output.push_str("</voice>");
+        }
+    }
+
+    fn spoken_out(&self, text: &str, output: &mut String) {
+        self.open_out(output);
+        escaped_out(text, output);
+        self.close_out(output);
+    }
+}
+
+/// The choices of the listener on how a document is spoken.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpeechProfile {
+    /// Voices of light, medium and strong emphasis.
+    pub emphasis: [Voice; 3],
+    /// Voices of light, medium and strong deemphasis.
+    pub deemphasis: [Voice; 3],
+    pub heading: Voice,
+    pub link: Voice,
+    pub code: Voice,
+    /// Milliseconds of silence before and after headings.
+    pub heading_pause: u32,
+    /// Milliseconds of silence between items of lists and cells of tables.
+    pub item_pause: u32,
+    /// Say the level of a heading before it, like `Heading level 2.`
+    pub announce_levels: bool,
+}
+
+impl Default for SpeechProfile {
+    fn default() -> Self {
+        let voice = |words| Voice::from_words(words).unwrap_or_default();
+        Self {
+            emphasis: [
+                voice("pitch:high"),
+                voice("pitch:high volume:loud"),
+                voice("pitch:high volume:x-loud rate:slow"),
+            ],
+            deemphasis: [
+                voice("volume:soft"),
+                voice("volume:soft rate:fast"),
+                voice("volume:x-soft rate:fast"),
+            ],
+            heading: voice("rate:slow"),
+            link: voice("pitch:low"),
+            code: voice("rate:slow"),
+            heading_pause: 500,
+            item_pause: 250,
+            announce_levels: true,
+        }
+    }
+}
+
+impl SpeechProfile {
+    /// Read a profile from properties, starting from the default profile.
+    pub fn from_props(props: &Props) -> Result<Self, ProfileError> {
+        let mut profile = Self::default();
+        let mut props = props.iter().collect::<Vec<_>>();
+        props.sort_by_key(|(key, _)| *key);
+        for (key, val) in props {
+            let invalid = || ProfileError::InvalidValue(key.clone());
+            let string = match val {
+                PropVal::String(string) | PropVal::Text(string) => Some(string.as_str()),
+                _ => None,
+            };
+            let int = match val {
+                PropVal::Int(int) => u32::try_from(*int).ok(),
+                _ => None,
+            };
+            let voice = string.and_then(Voice::from_words);
+            match key.as_str() {
+                "heading-pause" => profile.heading_pause = int.ok_or_else(invalid)?,
+                "item-pause" => profile.item_pause = int.ok_or_else(invalid)?,
+                "announce-levels" => profile.announce_levels = match string {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => return Err(invalid()),
+                },
+                key => {
+                    let slot = match key {
+                        "light-emphasis" => &mut profile.emphasis[0],
+                        "medium-emphasis" => &mut profile.emphasis[1],
+                        "strong-emphasis" => &mut profile.emphasis[2],
+                        "light-deemphasis" => &mut profile.deemphasis[0],
+                        "medium-deemphasis" => &mut profile.deemphasis[1],
+                        "strong-deemphasis" => &mut profile.deemphasis[2],
+                        "heading" => &mut profile.heading,
+                        "link" => &mut profile.link,
+                        "code" => &mut profile.code,
+                        key => return Err(ProfileError::UnknownKey(key.to_string())),
+                    };
+                    *slot = voice.ok_or_else(invalid)?;
+                },
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Read a profile from the properties of an incodoc file.
+    pub fn from_incodoc(input: &str) -> Result<Self, ProfileError> {
+        let doc = parse(input).map_err(ProfileError::Parse)?;
+        Self::from_props(&doc.props)
+    }
+
+    fn voice(&self, em: &Emphasis) -> &Voice {
+        let index = match em.strength {
+            EmStrength::Light => 0,
+            EmStrength::Medium => 1,
+            EmStrength::Strong => 2,
+        };
+        match em.etype {
+            EmType::Emphasis => &self.emphasis[index],
+            EmType::Deemphasis => &self.deemphasis[index],
+        }
+    }
+}
+
+fn prop_text<'a>(props: &'a Props, key: &str) -> Option<&'a str> {
+    match props.get(key) {
+        Some(PropVal::String(val) | PropVal::Text(val)) => Some(val),
+        _ => None,
+    }
+}
+
+fn pause_out(ms: u32, output: &mut String) {
+    if ms > 0 {
+        output.push_str(&format!("<break time=\"{ms}ms\"/>\n"));
+    }
+}
+
+fn sentence_out(text: &str, output: &mut String) {
+    output.push_str("<p>");
+    escaped_out(text, output);
+    output.push_str("</p>\n");
+}
+
+struct Speaker<'a> {
+    profile: &'a SpeechProfile,
+    footnotes: HashMap<String, usize>,
+}
+
+impl Speaker<'_> {
+    fn em_or_text(&self, items: &[EmOrText], output: &mut String) {
+        for item in items {
+            match item {
+                EmOrText::Text(text) => escaped_out(text, output),
+                EmOrText::Em(em) => self.profile.voice(em).spoken_out(&em.text, output),
+            }
+        }
+    }
+
+    fn link(&self, link: &Link, output: &mut String) {
+        if link.tags.contains("footnote-ref")
+            && let Some(number) = link.url.strip_prefix('#').and_then(|id| self.footnotes.get(id))
+        {
+            escaped_out(&format!("footnote {number}"), output);
+            return;
+        }
+        self.profile.link.open_out(output);
+        if let Some(hint) = prop_text(&link.props, "type-hint") {
+            escaped_out(&format!("{hint}: "), output);
+        }
+        if link.items.deemphasise().trim().is_empty() {
+            escaped_out(&link.url, output);
+        } else {
+            self.em_or_text(&link.items, output);
+        }
+        self.profile.link.close_out(output);
+        if let Some(bg) = prop_text(&link.props, "bg-text") {
+            output.push_str("<break strength=\"weak\"/>");
+            escaped_out(bg, output);
+        }
+    }
+
+    fn inline(&self, item: &ParagraphItem, output: &mut String) {
+        match item {
+            ParagraphItem::Text(text) => escaped_out(text, output),
+            ParagraphItem::MText(mtext) if mtext.tags.contains("code") => {
+                self.profile.code.spoken_out(&mtext.text, output);
+            },
+            ParagraphItem::MText(mtext) => escaped_out(&mtext.text, output),
+            ParagraphItem::Em(em) => self.profile.voice(em).spoken_out(&em.text, output),
+            ParagraphItem::Link(link) => self.link(link, output),
+            ParagraphItem::FootnoteRef(footnote_ref) => {
+                match self.footnotes.get(&format!("footnote-{}", footnote_ref.label)) {
+                    Some(number) => escaped_out(&format!("footnote {number}"), output),
+                    None => escaped_out(&format!("footnote {}", footnote_ref.label), output),
+                }
+            },
+            ParagraphItem::Quote(quote) => {
+                escaped_out("quote ", output);
+                self.em_or_text(&quote.items, output);
+                escaped_out(" unquote", output);
+                if !quote.attribution.is_empty() {
+                    escaped_out(" by ", output);
+                    self.em_or_text(&quote.attribution, output);
+                }
+                if let Some(source) = &quote.source {
+                    escaped_out(", from ", output);
+                    self.link(source, output);
+                }
+            },
+            _ => { },
+        }
+    }
+
+    fn code(&self, code: &CodeBlock, output: &mut String) {
+        if code.mode == CodeModeHint::Replace {
+            return;
+        }
+        if code.language.is_empty() || code.language == "plain" {
+            sentence_out("Code.", output);
+        } else {
+            sentence_out(&format!("Code in {}.", code.language), output);
+        }
+        output.push_str("<p>");
+        self.profile.code.spoken_out(&code.code, output);
+        output.push_str("</p>\n");
+    }
+
+    fn list(&self, list: &List, output: &mut String) {
+        let count = plural(list.items.len(), "item");
+        let checked = list.items.iter().filter(|par| par.tags.contains("checked")).count();
+        sentence_out(&match list.ltype {
+            ListType::Identical => format!("List of {count}."),
+            ListType::Distinct => format!("Numbered list of {count}."),
+            ListType::Checked => format!("Checklist of {count}, {checked} checked."),
+        }, output);
+        for (i, par) in list.items.iter().enumerate() {
+            let label = match list.ltype {
+                ListType::Checked if par.tags.contains("checked") => {
+                    format!("Item {}, checked.", i + 1)
+                },
+                ListType::Checked => format!("Item {}, not checked.", i + 1),
+                _ => format!("Item {}.", i + 1),
+            };
+            pause_out(self.profile.item_pause, output);
+            self.paragraph(par, Some(&label), output);
+        }
+    }
+
+    fn table(&self, table: &Table, output: &mut String) {
+        let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0);
+        let mut announcement = format!(
+            "Table of {} and {}.", plural(table.rows.len(), "row"), plural(columns, "column")
+        );
+        let header = table.rows.first().filter(|row| row.is_header);
+        if header.is_some() {
+            announcement.push_str(" The first row is a header.");
+        }
+        sentence_out(&announcement, output);
+        for (r, row) in table.rows.iter().enumerate() {
+            for (c, par) in row.items.iter().enumerate() {
+                let mut label = format!("Row {}, column {}", r + 1, column_name(c));
+                if !row.is_header
+                    && let Some(name) = header.and_then(|header| header.items.get(c))
+                {
+                    let mut text = String::new();
+                    for item in &name.items {
+                        match item {
+                            ParagraphItem::Text(string) => text.push_str(string),
+                            ParagraphItem::MText(mtext) => text.push_str(&mtext.text),
+                            ParagraphItem::Em(em) => text.push_str(&em.text),
+                            ParagraphItem::Link(link) => text.push_str(&link.items.deemphasise()),
+                            _ => { },
+                        }
+                    }
+                    if !text.trim().is_empty() {
+                        label.push_str(", ");
+                        label.push_str(text.trim());
+                    }
+                }
+                label.push('.');
+                pause_out(self.profile.item_pause, output);
+                self.paragraph(par, Some(&label), output);
+            }
+        }
+    }
+
+    /// Speak a paragraph, as `p` elements for its text and the blocks in between.
+    /// The label goes first, as a sentence of its own.
+    fn paragraph(&self, par: &Paragraph, label: Option<&str>, output: &mut String) {
+        let mut label = label;
+        let mut run = String::new();
+        let flush = |run: &mut String, label: &mut Option<&str>, output: &mut String| {
+            if run.trim().is_empty() && label.is_none() {
+                run.clear();
+                return;
+            }
+            output.push_str("<p>");
+            if let Some(label) = label.take() {
+                output.push_str("<s>");
+                escaped_out(label, output);
+                output.push_str("</s>");
+                if !run.trim().is_empty() {
+                    output.push(' ');
+                }
+            }
+            output.push_str(run.trim());
+            output.push_str("</p>\n");
+            run.clear();
+        };
+        for item in &par.items {
+            match item {
+                ParagraphItem::Code(Ok(code)) => {
+                    flush(&mut run, &mut label, output);
+                    self.code(code, output);
+                },
+                ParagraphItem::Code(Err(_)) => { },
+                ParagraphItem::List(list) => {
+                    flush(&mut run, &mut label, output);
+                    self.list(list, output);
+                },
+                ParagraphItem::Table(table) => {
+                    flush(&mut run, &mut label, output);
+                    self.table(table, output);
+                },
+                ParagraphItem::Data(data) => {
+                    flush(&mut run, &mut label, output);
+                    self.table(&data.to_table(), output);
+                },
+                item => self.inline(item, &mut run),
+            }
+        }
+        flush(&mut run, &mut label, output);
+    }
+
+    fn section_items(&self, section: &Section, output: &mut String) {
+        for item in &section.items {
+            match item {
+                SectionItem::Paragraph(par) => self.paragraph(par, None, output),
+                SectionItem::Section(section) => self.section(section, output),
+                SectionItem::Quote(quote) => self.quote(quote, output),
+                SectionItem::Footnote(footnote) => self.footnote(footnote, output),
+            }
+        }
+    }
+
+    fn footnote(&self, footnote: &FootnoteDef, output: &mut String) {
+        pause_out(self.profile.item_pause, output);
+        match self.footnotes.get(&format!("footnote-{}", footnote.label)) {
+            Some(number) => sentence_out(&format!("Footnote {number}."), output),
+            None => sentence_out(&format!("Footnote {}.", footnote.label), output),
+        }
+        for par in &footnote.items {
+            self.paragraph(par, None, output);
+        }
+    }
+
+    fn quote(&self, quote: &Quote, output: &mut String) {
+        sentence_out(&format!("{}.", quote_name(&quote.kind)), output);
+        for item in &quote.items {
+            match item {
+                QuoteItem::Paragraph(par) => self.paragraph(par, None, output),
+                QuoteItem::Quote(quote) => self.quote(quote, output),
+            }
+        }
+        if !quote.attribution.is_empty() || quote.source.is_some() {
+            output.push_str("<p>");
+            if !quote.attribution.is_empty() {
+                escaped_out("By ", output);
+                self.em_or_text(&quote.attribution, output);
+            }
+            if let Some(source) = &quote.source {
+                let from = if quote.attribution.is_empty() { "From " } else { ", from " };
+                escaped_out(from, output);
+                self.link(source, output);
+            }
+            output.push_str(".</p>\n");
+        }
+        sentence_out("End of quote.", output);
+    }
+
+    fn section(&self, section: &Section, output: &mut String) {
+        let footnote = section.tags.contains("footnote-def").then(|| {
+            prop_text(&section.props, "id").and_then(|id| self.footnotes.get(id))
+        }).flatten();
+        if let Some(number) = footnote {
+            pause_out(self.profile.item_pause, output);
+            sentence_out(&format!("Footnote {number}."), output);
+            self.section_items(section, output);
+        } else if section.tags.contains("blockquote") {
+            sentence_out("Quote.", output);
+            self.section_items(section, output);
+            sentence_out("End of quote.", output);
+        } else if section.tags.contains("blockquote-typed") {
+            output.push_str("<p>");
+            self.em_or_text(&section.heading.items, output);
+            output.push_str(".</p>\n");
+            self.section_items(section, output);
+            sentence_out("End of quote.", output);
+        } else {
+            pause_out(self.profile.heading_pause, output);
+            output.push_str("<p>");
+            if self.profile.announce_levels {
+                output.push_str(&format!(
+                    "<s>Heading level {}.</s> ", section.heading.level as usize + 1
+                ));
+            }
+            self.profile.heading.open_out(output);
+            self.em_or_text(&section.heading.items, output);
+            self.profile.heading.close_out(output);
+            output.push_str("</p>\n");
+            pause_out(self.profile.heading_pause, output);
+            self.section_items(section, output);
+        }
+    }
+}
+
+/// Write a document as SSML, to be spoken as the profile prescribes.
+pub fn doc_to_ssml(doc: &Doc, profile: &SpeechProfile, output: &mut String) {
+    let speaker = Speaker { profile, footnotes: number_footnotes(doc) };
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str("<speak version=\"1.1\" xmlns=\"http://www.w3.org/2001/10/synthesis\"");
+    if let Some(language) = prop_text(&doc.props, "language") {
+        output.push_str(" xml:lang=\"");
+        escaped_out(language, output);
+        output.push('"');
+    }
+    output.push_str(">\n");
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => speaker.paragraph(par, None, output),
+            DocItem::Section(section) => speaker.section(section, output),
+            DocItem::Quote(quote) => speaker.quote(quote, output),
+            DocItem::Footnote(footnote) => speaker.footnote(footnote, output),
+        }
+    }
+    output.push_str("</speak>\n");
+}
@@ -0,0 +1,385 @@
+//! Typst, to typeset documents for print.
+//!
+//! - The `title`, `author`, `date` and `language` properties of the document are set on the
+//!   document and its text.
+//! - Sections become `=` headings by how deep they are nested. The `id` property becomes a
+//!   label.
+//! - Light emphasis becomes `#emph`, medium emphasis `#strong` and strong emphasis both.
+//!   Deemphasis makes text smaller. Text with the `code` tag becomes raw text. Line breaks in
+//!   text become `\`.
+//! - Links become `#link`.
+//! - Lists become `-` and `+` lists; items of checked lists start with a ballot box.
+//! - Tables become `#table`, with a header row as `table.header`. Only the inline content of
+//!   cells is written. Datasets become tables as well, see [`Data::to_table`].
+//! - Code becomes raw blocks. Code in `replace` mode with `typst` as language, or a language
+//!   starting with `typst-`, is written as is; with `typst-formula` as language it becomes a
+//!   block equation.
+//! - Quotes become block quotes, starting with their kind in bold, with their attribution and
+//!   source as the attribution of the quote. Quotes in paragraphs become inline quotes.
+//!   Sections with the `blockquote` tag become block quotes without their heading; sections
+//!   with the `blockquote-typed` tag start with their heading in bold.
+//! - Footnote references become their label in superscript. Footnote definitions are written
+//!   where they are, starting with their label in superscript; see [`Doc::place_footnotes`] to
+//!   move them.
+//! - Navigations and other metadata are left out.
+
+use crate::*;
+use crate::export::plain::quote_name;
+
+fn is_quote(section: &Section) -> bool {
+    section.tags.contains("blockquote") || section.tags.contains("blockquote-typed")
+}
+
+fn prop_text<'a>(props: &'a Props, key: &str) -> Option<&'a str> {
+    match props.get(key) {
+        Some(PropVal::String(val) | PropVal::Text(val)) => Some(val),
+        _ => None,
+    }
+}
+
+/// Code that is Typst, to write as is.
+fn is_raw(code: &CodeBlock) -> bool {
+    code.mode == CodeModeHint::Replace
+        && (code.language == "typst" || code.language.starts_with("typst-"))
+}
+
+fn string_typst(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+/// Escape text for markup. Spaces at the start of a line are left out, as they would change
+/// what list item the line belongs to.
+fn escaped_typst(text: &str, output: &mut String) {
+    let mut chars = text.chars().peekable();
+    let mut digits = false;
+    while let Some(c) = chars.next() {
+        let line_start = output.is_empty() || output.ends_with('\n');
+        // text right after a function call would be read as more of the call
+        let after_call = output.ends_with(']') && !output.ends_with("\\]");
+        let next = chars.peek().copied();
+        let escape = match c {
+            '\\' | '#' | '*' | '_' | '`' | '$' | '<' | '>' | '@' | '[' | ']' | '~' => true,
+            '/' => line_start || matches!(next, Some('/' | '*')),
+            '-' => line_start || matches!(next, Some('-' | '?')),
+            '+' | '=' => line_start,
+            '.' => digits || after_call,
+            '(' => after_call,
+            _ => false,
+        };
+        if c == '\n' {
+            output.push_str(" \\\n");
+            digits = false;
+            continue;
+        }
+        if line_start && c.is_whitespace() {
+            continue;
+        }
+        if escape {
+            output.push('\\');
+        }
+        output.push(c);
+        digits = c.is_ascii_digit() && (line_start || digits);
+    }
+}
+
+fn emphasis(em: &Emphasis, output: &mut String) {
+    let (open, close) = match (em.etype, em.strength) {
+        (EmType::Emphasis, EmStrength::Light) => ("#emph[", "]"),
+        (EmType::Emphasis, EmStrength::Medium) => ("#strong[", "]"),
+        (EmType::Emphasis, EmStrength::Strong) => ("#strong[#emph[", "]]"),
+        (EmType::Deemphasis, EmStrength::Light) => ("#text(size: 0.9em)[", "]"),
+        (EmType::Deemphasis, EmStrength::Medium) => ("#text(size: 0.8em)[", "]"),
+        (EmType::Deemphasis, EmStrength::Strong) => ("#text(size: 0.7em)[", "]"),
+    };
+    output.push_str(open);
+    escaped_typst(&em.text, output);
+    output.push_str(close);
+}
+
+fn em_or_text(items: &[EmOrText], output: &mut String) {
+    for item in items {
+        match item {
+            EmOrText::Text(text) => escaped_typst(text, output),
+            EmOrText::Em(em) => emphasis(em, output),
+        }
+    }
+}
+
+fn link(link: &Link, output: &mut String) {
+    output.push_str("#link(");
+    output.push_str(&string_typst(&link.url));
+    output.push_str(")[");
+    em_or_text(&link.items, output);
+    output.push(']');
+}
+
+fn inline(item: &ParagraphItem, output: &mut String) {
+    match item {
+        ParagraphItem::Text(text) => escaped_typst(text, output),
+        ParagraphItem::MText(mtext) if mtext.tags.contains("code") => {
+            if mtext.text.contains('`') || mtext.text.is_empty() {
+                output.push_str(&format!("#raw({})", string_typst(&mtext.text)));
+            } else {
+                output.push_str(&format!("`{}`", mtext.text));
+            }
+        },
+        ParagraphItem::MText(mtext) => escaped_typst(&mtext.text, output),
+        ParagraphItem::Em(em) => emphasis(em, output),
+        ParagraphItem::Link(l) => link(l, output),
+        ParagraphItem::FootnoteRef(footnote_ref) => {
+            output.push_str("#super[");
+            escaped_typst(&footnote_ref.label, output);
+            output.push(']');
+        },
+        ParagraphItem::Quote(quote) => {
+            output.push_str("#quote");
+            if !quote.attribution.is_empty() || quote.source.is_some() {
+                output.push('(');
+                quote_attribution(&quote.attribution, &quote.source, output);
+                output.push(')');
+            }
+            output.push('[');
+            em_or_text(&quote.items, output);
+            output.push(']');
+        },
+        _ => { },
+    }
+}
+
+/// The `attribution` argument of `#quote`.
+fn quote_attribution(attribution: &[EmOrText], source: &Option<Link>, output: &mut String) {
+    output.push_str("attribution: [");
+    em_or_text(attribution, output);
+    if let Some(source) = source {
+        if !attribution.is_empty() {
+            output.push_str(", ");
+        }
+        link(source, output);
+    }
+    output.push(']');
+}
+
+fn code(code: &CodeBlock) -> String {
+    if is_raw(code) {
+        return if code.language == "typst-formula" {
+            format!("$ {} $", code.code.trim())
+        } else {
+            code.code.trim_end().to_string()
+        };
+    }
+    let mut longest = 0;
+    let mut run = 0;
+    for c in code.code.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat((longest + 1).max(3));
+    let language = if code.language == "plain" { "" } else { &code.language };
+    format!("{fence}{language}\n{}\n{fence}", code.code.trim_end())
+}
+
+/// Put a prefix before each line, leaving out trailing spaces on empty lines.
+fn prefixed(text: &str, first: &str, rest: &str) -> String {
+    let mut output = String::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        let prefix = if i == 0 { first } else { rest };
+        if line.is_empty() {
+            output.push_str(prefix.trim_end());
+        } else {
+            output.push_str(prefix);
+            output.push_str(line);
+        }
+    }
+    output
+}
+
+fn list(list: &List) -> String {
+    let mut items = Vec::new();
+    for par in &list.items {
+        let marker = match list.ltype {
+            ListType::Identical => "- ",
+            ListType::Distinct => "+ ",
+            ListType::Checked if par.tags.contains("checked") => "- ☒ ",
+            ListType::Checked => "- ☐ ",
+        };
+        let mut blocks = Vec::new();
+        paragraph_blocks(par, &mut blocks);
+        items.push(prefixed(&blocks.join("\n\n"), marker, "  "));
+    }
+    items.join("\n")
+}
+
+fn cell(par: &Paragraph) -> String {
+    let mut cell = String::new();
+    for item in &par.items {
+        inline(item, &mut cell);
+    }
+    format!("[{}]", cell.trim_end())
+}
+
+fn table(table: &Table) -> String {
+    let columns = table.rows.iter().map(|row| row.items.len()).max().unwrap_or(0).max(1);
+    let mut output = format!("#table(\n  columns: {columns},\n");
+    for row in &table.rows {
+        let mut cells: Vec<String> = row.items.iter().map(cell).collect();
+        cells.resize(columns, "[]".to_string());
+        if row.is_header {
+            output.push_str(&format!("  table.header({}),\n", cells.join(", ")));
+        } else {
+            output.push_str(&format!("  {},\n", cells.join(", ")));
+        }
+    }
+    output.push(')');
+    output
+}
+
+/// Line breaks at the end of a paragraph have no line to end.
+fn push_run(run: &mut String, blocks: &mut Vec<String>) {
+    let text = run.trim_end().trim_end_matches(" \\").trim_end();
+    if !text.is_empty() {
+        blocks.push(text.to_string());
+    }
+    run.clear();
+}
+
+/// Runs of inline items and the code, lists and tables in between, as blocks.
+fn paragraph_blocks(par: &Paragraph, blocks: &mut Vec<String>) {
+    let mut run = String::new();
+    for item in &par.items {
+        let block = match item {
+            ParagraphItem::Code(Ok(c)) => code(c),
+            ParagraphItem::Code(Err(_)) => continue,
+            ParagraphItem::List(l) => list(l),
+            ParagraphItem::Table(t) => table(t),
+            ParagraphItem::Data(d) => table(&d.to_table()),
+            _ => {
+                inline(item, &mut run);
+                continue;
+            },
+        };
+        push_run(&mut run, blocks);
+        blocks.push(block);
+    }
+    push_run(&mut run, blocks);
+}
+
+fn quote_blocks(quote: &Quote, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    if quote.kind.is_some() {
+        let mut kind = "#strong[".to_string();
+        escaped_typst(&quote_name(&quote.kind), &mut kind);
+        kind.push(']');
+        inner.push(kind);
+    }
+    for item in &quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            QuoteItem::Quote(quote) => quote_blocks(quote, &mut inner),
+        }
+    }
+    let mut output = "#quote(block: true".to_string();
+    if !quote.attribution.is_empty() || quote.source.is_some() {
+        output.push_str(", ");
+        quote_attribution(&quote.attribution, &quote.source, &mut output);
+    }
+    output.push_str(")[\n");
+    output.push_str(&inner.join("\n\n"));
+    output.push_str("\n]");
+    blocks.push(output);
+}
+
+fn footnote_blocks(footnote: &FootnoteDef, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    for par in &footnote.items {
+        paragraph_blocks(par, &mut inner);
+    }
+    let mut label = "#super[".to_string();
+    escaped_typst(&footnote.label, &mut label);
+    label.push_str("] ");
+    match inner.first_mut() {
+        Some(first) => first.insert_str(0, &label),
+        None => inner.push(label.trim_end().to_string()),
+    }
+    blocks.extend(inner);
+}
+
+fn section_blocks(section: &Section, depth: usize, blocks: &mut Vec<String>) {
+    let mut inner = Vec::new();
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => paragraph_blocks(par, &mut inner),
+            SectionItem::Section(section) => section_blocks(section, depth + 1, &mut inner),
+            SectionItem::Quote(quote) => quote_blocks(quote, &mut inner),
+            SectionItem::Footnote(footnote) => footnote_blocks(footnote, &mut inner),
+        }
+    }
+    if is_quote(section) {
+        let mut quote = "#quote(block: true)[\n".to_string();
+        if section.tags.contains("blockquote-typed") {
+            quote.push_str("#strong[");
+            em_or_text(&section.heading.items, &mut quote);
+            quote.push_str("]\n\n");
+        }
+        quote.push_str(&inner.join("\n\n"));
+        quote.push_str("\n]");
+        blocks.push(quote);
+        return;
+    }
+    let mut heading = "=".repeat(depth + 1);
+    heading.push(' ');
+    em_or_text(&section.heading.items, &mut heading);
+    if let Some(id) = prop_text(&section.props, "id") {
+        let label: String = id.chars()
+            .map(|c| if c.is_alphanumeric() || "-_.:".contains(c) { c } else { '-' })
+            .collect();
+        heading.push_str(&format!(" <{label}>"));
+    }
+    blocks.push(heading);
+    blocks.extend(inner);
+}
+
+fn set_rules(doc: &Doc, output: &mut String) {
+    let mut document = Vec::new();
+    if let Some(title) = prop_text(&doc.props, "title") {
+        document.push(format!("title: {}", string_typst(title)));
+    }
+    if let Some(author) = prop_text(&doc.props, "author") {
+        document.push(format!("author: {}", string_typst(author)));
+    }
+    if let Some(PropVal::Date(date)) = doc.props.get("date") {
+        document.push(format!(
+            "date: datetime(year: {}, month: {}, day: {})", date.year, date.month, date.day
+        ));
+    }
+    if !document.is_empty() {
+        output.push_str(&format!("#set document({})\n", document.join(", ")));
+    }
+    if let Some(language) = prop_text(&doc.props, "language") {
+        let language = language.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        output.push_str(&format!("#set text(lang: {})\n", string_typst(&language)));
+    }
+}
+
+/// Write a document as Typst.
+pub fn doc_to_typst(doc: &Doc, output: &mut String) {
+    set_rules(doc, output);
+    let mut blocks = Vec::new();
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => paragraph_blocks(par, &mut blocks),
+            DocItem::Section(section) => section_blocks(section, 0, &mut blocks),
+            DocItem::Quote(quote) => quote_blocks(quote, &mut blocks),
+            DocItem::Footnote(footnote) => footnote_blocks(footnote, &mut blocks),
+        }
+    }
+    if !output.is_empty() && !blocks.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(&blocks.join("\n\n"));
+    if !blocks.is_empty() {
+        output.push('\n');
+    }
+}
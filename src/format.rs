@@ -0,0 +1,98 @@
+use crate::{
+    *,
+    parsing::{ comment_count, parse_with_comments, ParseError },
+    output::{ doc_out_with, OutputOptions },
+};
+
+use std::fmt;
+
+/// Error when an incodoc can not be formatted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FormatError {
+    /// The input could not be parsed.
+    Parse(ParseError),
+    /// The output would not parse back into the same document. This happens when the input has
+    /// values that could not be parsed, like props with broken dates or badly indented code, or
+    /// comments that were not kept.
+    Lossy,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{error}"),
+            Self::Lossy => write!(f, "formatting would drop values that could not be parsed"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError { }
+
+/// Parse an incodoc, comments included, and lay it out again according to the options.
+/// Refuses to format when that would lose anything that is in the input.
+pub fn format(input: &str, options: &OutputOptions) -> Result<String, FormatError> {
+    let doc = parse_with_comments(input).map_err(FormatError::Parse)?;
+    // the check below compares two parses, so it can't see comments that both leave out
+    if comment_count(input) != doc_comment_count(&doc) {
+        return Err(FormatError::Lossy);
+    }
+    let mut output = String::new();
+    // writing into a string does not fail
+    let _ = doc_out_with(&doc, options, &mut output);
+    if parse_with_comments(&output).as_ref() != Ok(&doc) {
+        return Err(FormatError::Lossy);
+    }
+    Ok(output)
+}
+
+fn doc_comment_count(doc: &Doc) -> usize {
+    doc.comments.len()
+        + doc.navs.iter().map(nav_comment_count).sum::<usize>()
+        + doc.items.iter().map(|item| match item {
+            DocItem::Paragraph(par) => par_comment_count(par),
+            DocItem::Section(section) => section_comment_count(section),
+            DocItem::Quote(quote) => quote_comment_count(quote),
+            DocItem::Footnote(footnote) => footnote_comment_count(footnote),
+        }).sum::<usize>()
+}
+
+fn section_comment_count(section: &Section) -> usize {
+    section.comments.len() + section.items.iter().map(|item| match item {
+        SectionItem::Paragraph(par) => par_comment_count(par),
+        SectionItem::Section(section) => section_comment_count(section),
+        SectionItem::Quote(quote) => quote_comment_count(quote),
+        SectionItem::Footnote(footnote) => footnote_comment_count(footnote),
+    }).sum::<usize>()
+}
+
+fn quote_comment_count(quote: &Quote) -> usize {
+    quote.comments.len() + quote.items.iter().map(|item| match item {
+        QuoteItem::Paragraph(par) => par_comment_count(par),
+        QuoteItem::Quote(quote) => quote_comment_count(quote),
+    }).sum::<usize>()
+}
+
+fn footnote_comment_count(footnote: &FootnoteDef) -> usize {
+    footnote.comments.len() + footnote.items.iter().map(par_comment_count).sum::<usize>()
+}
+
+fn par_comment_count(par: &Paragraph) -> usize {
+    par.comments.len() + par.items.iter().map(|item| match item {
+        ParagraphItem::List(list) => {
+            list.comments.len() + list.items.iter().map(par_comment_count).sum::<usize>()
+        },
+        ParagraphItem::Table(table) => {
+            table.comments.len() + table.rows.iter().map(row_comment_count).sum::<usize>()
+        },
+        ParagraphItem::Data(data) => data.comments.len(),
+        _ => 0,
+    }).sum::<usize>()
+}
+
+fn row_comment_count(row: &TableRow) -> usize {
+    row.comments.len() + row.items.iter().map(par_comment_count).sum::<usize>()
+}
+
+fn nav_comment_count(nav: &Nav) -> usize {
+    nav.comments.len() + nav.subs.iter().map(nav_comment_count).sum::<usize>()
+}
@@ -0,0 +1,219 @@
+//! Import gemtext, the document format of the Gemini protocol.
+//!
+//! - Every text line and link line becomes a paragraph of its own; empty lines are dropped.
+//! - `#`, `##` and `###` headings become nested sections. The level of a heading is one more
+//!   than the level of the section it is in, no matter how many levels gemtext skips.
+//!   Headings without text are dropped, sections without content hold an empty paragraph.
+//! - Links without a label get their URL as text.
+//! - Consecutive `*` lines become a list.
+//! - Consecutive `>` lines become a quote. Empty quote lines separate paragraphs, the other
+//!   lines are joined by line breaks. A quote of only empty lines is dropped.
+//! - Preformatted blocks become code with the alt text as language, or `plain` without one.
+
+use crate::*;
+
+/// Section that is still being read, with the level of its gemtext heading.
+struct SectionFrame {
+    section: Section,
+    gem_level: usize,
+}
+
+/// Run of lines of the same kind that is still being read.
+enum Run {
+    Bullets(Vec<String>),
+    Quote(Vec<String>),
+}
+
+#[derive(Default)]
+struct Importer {
+    doc: Doc,
+    sections: Vec<SectionFrame>,
+    run: Option<Run>,
+}
+
+fn text_par(text: String) -> Paragraph {
+    Paragraph { items: vec![ParagraphItem::Text(text)], ..Default::default() }
+}
+
+fn link_line(line: &str) -> Link {
+    let line = line.trim();
+    let (url, label) = match line.split_once(char::is_whitespace) {
+        Some((url, label)) => (url, label.trim()),
+        None => (line, ""),
+    };
+    let text = if label.is_empty() { url } else { label };
+    Link {
+        url: url.to_string(),
+        items: vec![EmOrText::Text(text.to_string())],
+        ..Default::default()
+    }
+}
+
+impl Importer {
+    fn push_paragraph(&mut self, par: Paragraph) {
+        match self.sections.last_mut() {
+            Some(frame) => frame.section.items.push(SectionItem::Paragraph(par)),
+            None => self.doc.items.push(DocItem::Paragraph(par)),
+        }
+    }
+
+    fn push_section(&mut self, section: Section) {
+        match self.sections.last_mut() {
+            Some(frame) => frame.section.items.push(SectionItem::Section(section)),
+            None => self.doc.items.push(DocItem::Section(section)),
+        }
+    }
+
+    fn level(&self) -> u8 {
+        self.sections.last().map(|frame| frame.section.heading.level + 1).unwrap_or(0)
+    }
+
+    fn close_run(&mut self) {
+        match self.run.take() {
+            Some(Run::Bullets(bullets)) => {
+                let list = List {
+                    ltype: ListType::Identical,
+                    items: bullets.into_iter().map(text_par).collect(),
+                    ..Default::default()
+                };
+                self.push_paragraph(Paragraph {
+                    items: vec![ParagraphItem::List(list)],
+                    ..Default::default()
+                });
+            },
+            Some(Run::Quote(lines)) => {
+                let mut section = Section {
+                    heading: Heading {
+                        level: self.level(),
+                        items: vec![EmOrText::Text("Quote".to_string())],
+                        ..Default::default()
+                    },
+                    tags: Tags::from(["blockquote".to_string()]),
+                    ..Default::default()
+                };
+                for par in lines.split(|line| line.is_empty()).filter(|par| !par.is_empty()) {
+                    section.items.push(SectionItem::Paragraph(text_par(par.join("\n"))));
+                }
+                // a quote of only empty lines has nothing to say
+                if !section.items.is_empty() {
+                    self.push_section(section);
+                }
+            },
+            None => { },
+        }
+    }
+
+    /// A section without content holds an empty paragraph, as a section can't be empty.
+    fn close_section(&mut self) {
+        if let Some(mut frame) = self.sections.pop() {
+            if frame.section.items.is_empty() {
+                frame.section.items.push(SectionItem::Paragraph(text_par(String::new())));
+            }
+            self.push_section(frame.section);
+        }
+    }
+
+    fn heading(&mut self, gem_level: usize, text: &str) {
+        self.close_run();
+        while self.sections.last().is_some_and(|frame| frame.gem_level >= gem_level) {
+            self.close_section();
+        }
+        let section = Section {
+            heading: Heading {
+                level: self.level(),
+                items: vec![EmOrText::Text(text.to_string())],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        self.sections.push(SectionFrame { section, gem_level });
+    }
+
+    fn link(&mut self, link: Link) {
+        self.close_run();
+        self.push_paragraph(Paragraph {
+            items: vec![ParagraphItem::Link(link)],
+            ..Default::default()
+        });
+    }
+
+    fn bullet(&mut self, text: String) {
+        if let Some(Run::Bullets(bullets)) = &mut self.run {
+            bullets.push(text);
+        } else {
+            self.close_run();
+            self.run = Some(Run::Bullets(vec![text]));
+        }
+    }
+
+    fn quote(&mut self, text: String) {
+        if let Some(Run::Quote(lines)) = &mut self.run {
+            lines.push(text);
+        } else {
+            self.close_run();
+            self.run = Some(Run::Quote(vec![text]));
+        }
+    }
+
+    fn code(&mut self, language: &str, code: String) {
+        self.close_run();
+        let language = if language.is_empty() { "plain" } else { language };
+        self.push_paragraph(Paragraph {
+            items: vec![ParagraphItem::Code(Ok(CodeBlock {
+                language: language.to_string(),
+                code,
+                ..Default::default()
+            }))],
+            ..Default::default()
+        });
+    }
+
+    fn finish(mut self) -> Doc {
+        self.close_run();
+        while !self.sections.is_empty() {
+            self.close_section();
+        }
+        self.doc.migrate_quotes();
+        self.doc
+    }
+}
+
+/// Convert gemtext to a document.
+pub fn parse_gemtext(input: &str) -> Doc {
+    let mut importer = Importer::default();
+    let mut lines = input.lines();
+    while let Some(line) = lines.next() {
+        if let Some(alt) = line.strip_prefix("```") {
+            let mut code = Vec::new();
+            for line in lines.by_ref() {
+                if line.starts_with("```") {
+                    break;
+                }
+                code.push(line);
+            }
+            importer.code(alt.trim(), code.join("\n"));
+        } else if let Some(link) = line.strip_prefix("=>")
+            // a link line without a url is just text
+            && !link.trim().is_empty()
+        {
+            importer.link(link_line(link));
+        } else if let Some(text) = line.strip_prefix("* ") {
+            importer.bullet(text.trim().to_string());
+        } else if let Some(text) = line.strip_prefix('>') {
+            importer.quote(text.trim().to_string());
+        } else if line.starts_with('#') {
+            let text = line.trim_start_matches('#');
+            let gem_level = (line.len() - text.len()).min(3);
+            if text.trim().is_empty() {
+                continue;
+            }
+            importer.heading(gem_level, text.trim());
+        } else if line.trim().is_empty() {
+            importer.close_run();
+        } else {
+            importer.close_run();
+            importer.push_paragraph(text_par(line.trim().to_string()));
+        }
+    }
+    importer.finish()
+}
@@ -0,0 +1,722 @@
+//! Import the semantic structure of HTML pages, enabled with the `html` feature.
+//!
+//! Only what is incorporeal is kept; styling, scripts, forms and layout are dropped.
+//! - `h1` to `h6` become nested sections. The level of a heading is one more than the level of
+//!   the section it is in, no matter how many levels HTML skips. The `id` of a heading is kept.
+//! - `em`, `strong` and `mark` become light, medium and strong emphasis, `small`, `s` and `del`
+//!   light, medium and strong deemphasis.
+//! - `ul` and `ol` become lists. Lists with checkboxes become checked lists where the items with
+//!   a checked checkbox get the `checked` tag.
+//! - `table` becomes a table. The first row is a header row when it is in `thead` or has only
+//!   `th` cells.
+//! - `pre` becomes code in the language of a `language-*` class, or `plain` without one.
+//!   Inline `code`, `kbd` and `samp` become text with the `code` tag.
+//! - `a` becomes a link. `img` becomes a link with the `type-hint` property set to `image`, its
+//!   alternative text as `bg-text` property and its title, alternative text or source as text.
+//! - `nav` becomes a navigation of the document, nested lists of links become sub-navigations.
+//! - `blockquote` becomes a quote. One with headings in it becomes a section with the
+//!   `blockquote` tag and `Quote` as heading instead, as quotes can not hold sections.
+//! - `title` and the `lang` of `html` become the `title` and `language` properties.
+//!
+//! Everything else, like `div`, `span` or `article`, is looked through for its content.
+//! Elements that are `hidden` or `aria-hidden` are left out.
+//!
+//! With [`HtmlOptions::readability`] only the main content is kept: the `main` element or else the
+//! largest `article`, without navigations, headers, footers and asides.
+
+use crate::*;
+
+use scraper::{ ElementRef, Html, Node };
+
+/// Options for [`parse_html`].
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct HtmlOptions {
+    /// Keep only the main content of the page, like reader modes of browsers do.
+    pub readability: bool,
+}
+
+/// Section that is still being read.
+struct SectionFrame {
+    section: Section,
+    /// Level of the HTML heading, none for block quotes.
+    /// Headings only close sections of a level at least their own up to such a boundary.
+    html_level: Option<u8>,
+}
+
+struct Importer<'a> {
+    options: &'a HtmlOptions,
+    doc: Doc,
+    sections: Vec<SectionFrame>,
+}
+
+/// Inline content that is still being read, with whitespace collapsed as HTML does.
+#[derive(Default)]
+struct Inline {
+    items: Vec<ParagraphItem>,
+    /// Whitespace was read that is not written yet.
+    space: bool,
+    /// Something was written since the start or the last line break.
+    started: bool,
+}
+
+type EmKind = (EmStrength, EmType);
+
+const SKIPPED: [&str; 17] = [
+    "head", "script", "style", "noscript", "template", "iframe", "object", "embed", "svg",
+    "canvas", "video", "audio", "button", "input", "select", "textarea", "dialog",
+];
+
+const LAYOUT: [&str; 5] = ["header", "footer", "aside", "form", "nav"];
+
+const BLOCKS: [&str; 35] = [
+    "html", "body", "main", "article", "section", "header", "footer", "aside", "nav", "div",
+    "form", "fieldset", "figure", "figcaption", "details", "summary", "address", "blockquote",
+    "p", "pre", "ul", "ol", "li", "dl", "dt", "dd", "table", "hr", "h1", "h2", "h3", "h4", "h5",
+    "h6", "caption",
+];
+
+fn tagged(tag: &str) -> Tags {
+    Tags::from([tag.to_string()])
+}
+
+fn name<'a>(element: &ElementRef<'a>) -> &'a str {
+    element.value().name()
+}
+
+fn heading_level(name: &str) -> Option<u8> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn emphasis_kind(name: &str) -> Option<EmKind> {
+    match name {
+        "em" | "i" | "cite" | "dfn" => Some((EmStrength::Light, EmType::Emphasis)),
+        "strong" | "b" => Some((EmStrength::Medium, EmType::Emphasis)),
+        "mark" => Some((EmStrength::Strong, EmType::Emphasis)),
+        "small" => Some((EmStrength::Light, EmType::Deemphasis)),
+        "s" | "strike" => Some((EmStrength::Medium, EmType::Deemphasis)),
+        "del" => Some((EmStrength::Strong, EmType::Deemphasis)),
+        _ => None,
+    }
+}
+
+fn is_hidden(element: &ElementRef) -> bool {
+    element.value().attr("hidden").is_some()
+        || element.value().attr("aria-hidden") == Some("true")
+}
+
+fn child_elements<'a>(element: &ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element.children().filter_map(ElementRef::wrap)
+}
+
+fn descendant_elements<'a>(element: &ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element.descendants().filter_map(ElementRef::wrap)
+}
+
+fn text_len(element: &ElementRef) -> usize {
+    element.text().map(|text| text.trim().len()).sum()
+}
+
+fn collapsed(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn code_language(element: &ElementRef) -> Option<String> {
+    element.value().classes()
+        .find_map(|class| class.strip_prefix("language-").or(class.strip_prefix("lang-")))
+        .filter(|language| !language.is_empty())
+        .map(str::to_string)
+}
+
+fn to_em_or_text(items: Vec<ParagraphItem>) -> Vec<EmOrText> {
+    let mut res: Vec<EmOrText> = Vec::new();
+    for item in items {
+        let item = match item {
+            ParagraphItem::Text(text) => EmOrText::Text(text),
+            ParagraphItem::MText(mtext) => EmOrText::Text(mtext.text),
+            ParagraphItem::Em(em) => EmOrText::Em(em),
+            ParagraphItem::Link(link) => {
+                res.extend(link.items);
+                continue;
+            },
+            _ => continue,
+        };
+        if let EmOrText::Text(text) = &item
+            && let Some(EmOrText::Text(last)) = res.last_mut()
+        {
+            last.push_str(text);
+        } else {
+            res.push(item);
+        }
+    }
+    res
+}
+
+impl Inline {
+    fn push(&mut self, item: ParagraphItem) {
+        match (self.items.last_mut(), &item) {
+            (Some(ParagraphItem::Text(last)), ParagraphItem::Text(text)) => last.push_str(text),
+            (Some(ParagraphItem::Em(last)), ParagraphItem::Em(em))
+                if last.strength == em.strength && last.etype == em.etype =>
+            {
+                last.text.push_str(&em.text);
+            },
+            _ => self.items.push(item),
+        }
+    }
+
+    /// Write the pending space, inside emphasis only when the emphasis goes on after it.
+    fn space(&mut self, em: Option<EmKind>) {
+        if self.space && self.started {
+            match (self.items.last_mut(), em) {
+                (Some(ParagraphItem::Em(last)), Some((strength, etype)))
+                    if last.strength == strength && last.etype == etype =>
+                {
+                    last.text.push(' ');
+                },
+                _ => self.push(ParagraphItem::Text(" ".to_string())),
+            }
+        }
+        self.space = false;
+    }
+
+    fn text(&mut self, text: &str, em: Option<EmKind>) {
+        if text.starts_with(char::is_whitespace) {
+            self.space = true;
+        }
+        for word in text.split_whitespace() {
+            self.space(em);
+            let item = match em {
+                Some((strength, etype)) => ParagraphItem::Em(Emphasis {
+                    strength,
+                    etype,
+                    text: word.to_string(),
+                    ..Default::default()
+                }),
+                None => ParagraphItem::Text(word.to_string()),
+            };
+            self.push(item);
+            self.started = true;
+            self.space = true;
+        }
+        if !text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+            self.space = false;
+        }
+    }
+
+    /// Write an item that is not text, like a link.
+    fn item(&mut self, item: ParagraphItem) {
+        self.space(None);
+        self.push(item);
+        self.started = true;
+    }
+
+    /// Write a line break or a block, after which whitespace is dropped again.
+    fn line(&mut self, item: ParagraphItem) {
+        self.trim_end();
+        if !self.items.is_empty() || !matches!(item, ParagraphItem::Text(_)) {
+            self.push(item);
+        }
+        self.space = false;
+        self.started = false;
+    }
+
+    fn trim_end(&mut self) {
+        if let Some(ParagraphItem::Text(last)) = self.items.last_mut() {
+            let len = last.trim_end().len();
+            last.truncate(len);
+            if last.is_empty() {
+                self.items.pop();
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<ParagraphItem> {
+        while let Some(ParagraphItem::Text(last)) = self.items.last_mut() {
+            let len = last.trim_end().len();
+            if len > 0 {
+                last.truncate(len);
+                break;
+            }
+            self.items.pop();
+        }
+        self.items
+    }
+}
+
+impl Importer<'_> {
+    fn skipped(&self, element: &ElementRef) -> bool {
+        let name = name(element);
+        SKIPPED.contains(&name)
+            || (self.options.readability && LAYOUT.contains(&name))
+            || is_hidden(element)
+    }
+
+    fn push_paragraph(&mut self, par: Paragraph) {
+        if par.items.is_empty() {
+            return;
+        }
+        match self.sections.last_mut() {
+            Some(frame) => frame.section.items.push(SectionItem::Paragraph(par)),
+            None => self.doc.items.push(DocItem::Paragraph(par)),
+        }
+    }
+
+    fn push_run(&mut self, inline: &mut Inline) {
+        let items = std::mem::take(inline).finish();
+        self.push_paragraph(Paragraph { items, ..Default::default() });
+    }
+
+    fn open_section(&mut self, mut section: Section, html_level: Option<u8>) {
+        section.heading.level = self.sections.last()
+            .map(|frame| frame.section.heading.level + 1)
+            .unwrap_or(0);
+        self.sections.push(SectionFrame { section, html_level });
+    }
+
+    /// A section without content holds an empty paragraph, as a section can't be empty.
+    fn close_section(&mut self) -> Option<u8> {
+        let mut frame = self.sections.pop()?;
+        if frame.section.items.is_empty() {
+            frame.section.items.push(SectionItem::Paragraph(Paragraph {
+                items: vec![ParagraphItem::Text(String::new())],
+                ..Default::default()
+            }));
+        }
+        match self.sections.last_mut() {
+            Some(parent) => parent.section.items.push(SectionItem::Section(frame.section)),
+            None => self.doc.items.push(DocItem::Section(frame.section)),
+        }
+        frame.html_level
+    }
+
+    fn close_to_level(&mut self, level: u8) {
+        while let Some(frame) = self.sections.last()
+            && frame.html_level.is_some_and(|open| open >= level)
+        {
+            self.close_section();
+        }
+    }
+
+    fn close_boundary(&mut self) {
+        while let Some(frame) = self.sections.last() {
+            let boundary = frame.html_level.is_none();
+            self.close_section();
+            if boundary {
+                break;
+            }
+        }
+    }
+
+    fn inline_children(&self, element: &ElementRef, em: Option<EmKind>, inline: &mut Inline) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => inline.text(text, em),
+                Node::Element(_) => if let Some(child) = ElementRef::wrap(child) {
+                    self.inline(&child, em, inline);
+                },
+                _ => { },
+            }
+        }
+    }
+
+    fn image(&self, element: &ElementRef) -> Option<Link> {
+        let src = element.value().attr("src").filter(|src| !src.is_empty())?;
+        let alt = element.value().attr("alt").map(collapsed).filter(|alt| !alt.is_empty());
+        let title = element.value().attr("title").map(collapsed).filter(|t| !t.is_empty());
+        let text = title.or(alt.clone()).unwrap_or(src.to_string());
+        let mut props = Props::from([
+            ("type-hint".to_string(), PropVal::Text("image".to_string())),
+        ]);
+        if let Some(alt) = alt {
+            props.insert("bg-text".to_string(), PropVal::Text(alt));
+        }
+        Some(Link {
+            url: src.to_string(),
+            items: vec![EmOrText::Text(text)],
+            props,
+            ..Default::default()
+        })
+    }
+
+    fn link(&self, element: &ElementRef) -> Option<Link> {
+        let url = element.value().attr("href").filter(|url| !url.is_empty())?;
+        if text_len(element) == 0
+            && let Some(mut image) = descendant_elements(element)
+                .find(|child| name(child) == "img")
+                .and_then(|img| self.image(&img))
+        {
+            image.url = url.to_string();
+            return Some(image);
+        }
+        let mut inline = Inline::default();
+        self.inline_children(element, None, &mut inline);
+        let mut items = to_em_or_text(inline.finish());
+        if items.is_empty() {
+            items.push(EmOrText::Text(url.to_string()));
+        }
+        Some(Link { url: url.to_string(), items, ..Default::default() })
+    }
+
+    fn inline(&self, element: &ElementRef, em: Option<EmKind>, inline: &mut Inline) {
+        if self.skipped(element) {
+            return;
+        }
+        let name = name(element);
+        match name {
+            "br" => inline.line(ParagraphItem::Text("\n".to_string())),
+            "img" => if let Some(link) = self.image(element) {
+                inline.item(ParagraphItem::Link(link));
+            },
+            "a" => match self.link(element) {
+                Some(link) => inline.item(ParagraphItem::Link(link)),
+                None => self.inline_children(element, em, inline),
+            },
+            "code" | "kbd" | "samp" | "tt" => {
+                let text = collapsed(&element.text().collect::<String>());
+                if !text.is_empty() {
+                    inline.item(ParagraphItem::MText(TextWithMeta {
+                        text,
+                        tags: tagged("code"),
+                        ..Default::default()
+                    }));
+                }
+            },
+            _ => {
+                let em = emphasis_kind(name).or(em);
+                self.inline_children(element, em, inline);
+            },
+        }
+    }
+
+    /// Content of a list item or table cell: inline content and the structure within it.
+    /// An empty one holds an empty text, as a paragraph can't be empty.
+    fn paragraph(&self, element: &ElementRef) -> Paragraph {
+        let mut inline = Inline::default();
+        self.paragraph_content(element, &mut inline);
+        let mut items = inline.finish();
+        if items.is_empty() {
+            items.push(ParagraphItem::Text(String::new()));
+        }
+        Paragraph { items, ..Default::default() }
+    }
+
+    fn paragraph_content(&self, element: &ElementRef, inline: &mut Inline) {
+        for child in element.children() {
+            let child = match child.value() {
+                Node::Text(text) => {
+                    inline.text(text, None);
+                    continue;
+                },
+                Node::Element(_) => match ElementRef::wrap(child) {
+                    Some(child) => child,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if self.skipped(&child) {
+                continue;
+            }
+            match name(&child) {
+                "ul" | "ol" => if let Some(list) = self.list(&child) {
+                    inline.line(ParagraphItem::List(list));
+                },
+                "table" => if let Some(table) = self.table(&child) {
+                    inline.line(ParagraphItem::Table(table));
+                },
+                "pre" => inline.line(ParagraphItem::Code(Ok(self.code(&child)))),
+                "nav" | "hr" => { },
+                name if BLOCKS.contains(&name) => {
+                    if inline.started {
+                        inline.line(ParagraphItem::Text("\n".to_string()));
+                    }
+                    self.paragraph_content(&child, inline);
+                    if inline.started {
+                        inline.line(ParagraphItem::Text("\n".to_string()));
+                    }
+                },
+                _ => self.inline(&child, None, inline),
+            }
+        }
+    }
+
+    fn checkbox(&self, element: &ElementRef) -> Option<bool> {
+        descendant_elements(element)
+            .find(|child| name(child) == "input" && child.value().attr("type") == Some("checkbox"))
+            .map(|input| input.value().attr("checked").is_some())
+    }
+
+    fn list(&self, element: &ElementRef) -> Option<List> {
+        let mut list = List {
+            ltype: if name(element) == "ol" { ListType::Distinct } else { ListType::Identical },
+            ..Default::default()
+        };
+        for item in child_elements(element).filter(|child| name(child) == "li") {
+            if self.skipped(&item) {
+                continue;
+            }
+            let mut par = self.paragraph(&item);
+            if let Some(checked) = self.checkbox(&item) {
+                list.ltype = ListType::Checked;
+                if checked {
+                    par.tags.insert("checked".to_string());
+                }
+            }
+            list.items.push(par);
+        }
+        (!list.items.is_empty()).then_some(list)
+    }
+
+    fn rows(&self, element: &ElementRef, header: bool, rows: &mut Vec<TableRow>) {
+        for child in child_elements(element) {
+            match name(&child) {
+                "thead" => self.rows(&child, true, rows),
+                "tbody" | "tfoot" => self.rows(&child, false, rows),
+                "tr" => {
+                    let cells: Vec<ElementRef> = child_elements(&child)
+                        .filter(|cell| matches!(name(cell), "th" | "td"))
+                        .collect();
+                    if cells.is_empty() {
+                        continue;
+                    }
+                    rows.push(TableRow {
+                        is_header: rows.is_empty()
+                            && (header || cells.iter().all(|cell| name(cell) == "th")),
+                        items: cells.iter().map(|cell| self.paragraph(cell)).collect(),
+                        ..Default::default()
+                    });
+                },
+                _ => { },
+            }
+        }
+    }
+
+    fn table(&self, element: &ElementRef) -> Option<Table> {
+        let mut rows = Vec::new();
+        self.rows(element, false, &mut rows);
+        (!rows.is_empty()).then(|| Table { rows, ..Default::default() })
+    }
+
+    fn code(&self, element: &ElementRef) -> CodeBlock {
+        let language = code_language(element)
+            .or_else(|| descendant_elements(element).find_map(|child| code_language(&child)))
+            .unwrap_or("plain".to_string());
+        let mut code: String = element.text().collect();
+        if code.ends_with('\n') {
+            code.pop();
+        }
+        CodeBlock { language, code, ..Default::default() }
+    }
+
+    fn nav_content(&self, element: &ElementRef, nav: &mut Nav) {
+        for child in child_elements(element) {
+            if self.skipped(&child) || heading_level(name(&child)).is_some() {
+                continue;
+            }
+            match name(&child) {
+                "a" => nav.links.extend(self.link(&child)),
+                "li" => {
+                    let nested: Vec<ElementRef> = child_elements(&child)
+                        .filter(|list| matches!(name(list), "ul" | "ol"))
+                        .collect();
+                    let mut description = String::new();
+                    for grandchild in child.children() {
+                        match grandchild.value() {
+                            Node::Text(text) => description.push_str(text),
+                            Node::Element(_) => if let Some(grandchild) =
+                                ElementRef::wrap(grandchild)
+                                && !matches!(name(&grandchild), "ul" | "ol")
+                            {
+                                description.extend(grandchild.text());
+                                if name(&grandchild) == "a" {
+                                    nav.links.extend(self.link(&grandchild));
+                                } else {
+                                    self.nav_content(&grandchild, nav);
+                                }
+                            },
+                            _ => { },
+                        }
+                    }
+                    for list in nested {
+                        let description = collapsed(&description);
+                        let mut sub = Nav {
+                            description: if description.is_empty() {
+                                "Navigation".to_string()
+                            } else {
+                                description
+                            },
+                            ..Default::default()
+                        };
+                        self.nav_content(&list, &mut sub);
+                        if !sub.links.is_empty() || !sub.subs.is_empty() {
+                            nav.subs.push(sub);
+                        }
+                    }
+                },
+                _ => self.nav_content(&child, nav),
+            }
+        }
+    }
+
+    /// Navigation of the document; one with a label or heading gets it as description.
+    fn nav(&self, element: &ElementRef) -> Option<Nav> {
+        let description = element.value().attr("aria-label")
+            .map(collapsed)
+            .or_else(|| descendant_elements(element)
+                .find(|child| heading_level(name(child)).is_some())
+                .map(|heading| collapsed(&heading.text().collect::<String>())))
+            .unwrap_or_default();
+        let mut nav = Nav { description, ..Default::default() };
+        self.nav_content(element, &mut nav);
+        if nav.links.is_empty() && nav.subs.is_empty() {
+            None
+        } else if nav.description.is_empty() {
+            Some(nav)
+        } else {
+            Some(Nav { subs: vec![nav], ..Default::default() })
+        }
+    }
+
+    fn heading(&mut self, element: &ElementRef, level: u8) {
+        let mut inline = Inline::default();
+        self.inline_children(element, None, &mut inline);
+        let items = to_em_or_text(inline.finish());
+        if items.is_empty() {
+            return;
+        }
+        self.close_to_level(level);
+        let mut section = Section {
+            heading: Heading { items, ..Default::default() },
+            ..Default::default()
+        };
+        if let Some(id) = element.value().attr("id").filter(|id| !id.is_empty()) {
+            section.props.insert("id".to_string(), PropVal::String(id.to_string()));
+        }
+        self.open_section(section, Some(level));
+    }
+
+    fn block(&mut self, element: &ElementRef) {
+        let name = name(element);
+        if let Some(level) = heading_level(name) {
+            self.heading(element, level);
+            return;
+        }
+        match name {
+            "p" | "figcaption" | "summary" | "address" | "dt" | "dd" | "li" | "caption" => {
+                let par = self.paragraph(element);
+                self.push_paragraph(par);
+            },
+            "ul" | "ol" => if let Some(list) = self.list(element) {
+                self.push_paragraph(Paragraph {
+                    items: vec![ParagraphItem::List(list)],
+                    ..Default::default()
+                });
+            },
+            "table" => if let Some(table) = self.table(element) {
+                self.push_paragraph(Paragraph {
+                    items: vec![ParagraphItem::Table(table)],
+                    ..Default::default()
+                });
+            },
+            "pre" => {
+                let code = self.code(element);
+                self.push_paragraph(Paragraph {
+                    items: vec![ParagraphItem::Code(Ok(code))],
+                    ..Default::default()
+                });
+            },
+            "nav" => self.doc.navs.extend(self.nav(element)),
+            "blockquote" => {
+                let section = Section {
+                    heading: Heading {
+                        items: vec![EmOrText::Text("Quote".to_string())],
+                        ..Default::default()
+                    },
+                    tags: tagged("blockquote"),
+                    ..Default::default()
+                };
+                self.open_section(section, None);
+                self.container(element);
+                self.close_boundary();
+            },
+            "hr" => { },
+            _ => self.container(element),
+        }
+    }
+
+    fn container(&mut self, element: &ElementRef) {
+        let mut inline = Inline::default();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => inline.text(text, None),
+                Node::Element(_) => if let Some(child) = ElementRef::wrap(child) {
+                    if self.skipped(&child) {
+                        continue;
+                    }
+                    if BLOCKS.contains(&name(&child)) {
+                        self.push_run(&mut inline);
+                        self.block(&child);
+                    } else {
+                        self.inline(&child, None, &mut inline);
+                    }
+                },
+                _ => { },
+            }
+        }
+        self.push_run(&mut inline);
+    }
+
+    fn head(&mut self, html: &Html) {
+        let root = html.root_element();
+        if let Some(lang) = root.value().attr("lang").filter(|lang| !lang.is_empty()) {
+            self.doc.props.insert("language".to_string(), PropVal::String(lang.to_string()));
+        }
+        let title = descendant_elements(&root)
+            .find(|element| name(element) == "title")
+            .map(|title| collapsed(&title.text().collect::<String>()))
+            .filter(|title| !title.is_empty());
+        if let Some(title) = title {
+            self.doc.props.insert("title".to_string(), PropVal::Text(title));
+        }
+    }
+
+    /// The element with the main content of the page.
+    fn main<'a>(&self, html: &'a Html) -> ElementRef<'a> {
+        let root = html.root_element();
+        let main = descendant_elements(&root).find(|element| name(element) == "main");
+        let article = || descendant_elements(&root)
+            .filter(|element| name(element) == "article")
+            .max_by_key(text_len);
+        let body = || descendant_elements(&root).find(|element| name(element) == "body");
+        main.or_else(article).or_else(body).unwrap_or(root)
+    }
+
+    fn finish(mut self) -> Doc {
+        while !self.sections.is_empty() {
+            self.close_section();
+        }
+        self.doc.migrate_quotes();
+        self.doc
+    }
+}
+
+/// Convert an HTML page to a document, keeping only its semantic structure.
+pub fn parse_html(input: &str, options: &HtmlOptions) -> Doc {
+    let html = Html::parse_document(input);
+    let mut importer = Importer {
+        options,
+        doc: Doc::default(),
+        sections: Vec::new(),
+    };
+    importer.head(&html);
+    let root = if options.readability { importer.main(&html) } else { html.root_element() };
+    importer.container(&root);
+    importer.finish()
+}
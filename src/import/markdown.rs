@@ -0,0 +1,488 @@
+//! Import CommonMark with the GitHub flavoured extensions, enabled with the `markdown` feature.
+//!
+//! - Headings become nested sections. The level of a heading is one more than the level of the
+//!   section it is in, no matter how many levels markdown skips.
+//! - `*text*`, `**text**` and `***text***` become light, medium and strong emphasis,
+//!   `~~text~~` becomes medium deemphasis.
+//! - Inline code becomes text with the `code` tag, inline HTML text with the `html` tag.
+//!   HTML blocks become code in the `html` language.
+//! - Task lists become checked lists where the checked items have the `checked` tag.
+//! - Images become links with the `type-hint` property set to `image`.
+//! - Code blocks without a language get the `plain` language.
+//! - Block quotes become quotes. GitHub alerts like `> [!NOTE]` become quotes with the type as
+//!   kind. Block quotes with headings in them become sections with the `blockquote` tag and
+//!   `Quote` as heading, alerts sections with the `blockquote-typed` tag, a `blockquote-type`
+//!   property and the type as heading, as quotes can not hold sections.
+//!   Inside list items and tables the content of a block quote is taken over as is.
+//! - Footnote references become footnote references and footnote definitions become footnote
+//!   definitions, in the section they are defined in. Footnote definitions with more than
+//!   paragraphs in them become sections with the `footnote-def` tag and `footnote-{label}` as
+//!   id; references to them become links to `#footnote-{label}` with the `footnote-ref` tag.
+//! - What is left empty is filled, as incodoc has no empty paragraphs, sections or links:
+//!   empty paragraphs, list items and table cells hold an empty text, sections without content
+//!   an empty paragraph. Links without text have their url as text, links without a url become
+//!   their text. Headings without text are left out, their content goes to the section around
+//!   them.
+
+use crate::*;
+
+use std::mem;
+
+use pulldown_cmark::{ Parser, Options, Event, Tag, TagEnd, CodeBlockKind, HeadingLevel };
+
+/// Section that is still being read.
+struct SectionFrame {
+    section: Section,
+    /// Level of the markdown heading, none for block quotes and footnote definitions.
+    /// Headings only close sections of a level at least their own up to such a boundary.
+    md_level: Option<HeadingLevel>,
+}
+
+/// Paragraph level structure that is still being read.
+enum Block {
+    Paragraph(Paragraph),
+    List(List),
+    Table(Table),
+    Row(TableRow),
+}
+
+#[derive(Default)]
+struct Importer {
+    doc: Doc,
+    sections: Vec<SectionFrame>,
+    blocks: Vec<Block>,
+    /// For each open block quote, whether it became a section.
+    quotes: Vec<bool>,
+    /// Links being read, more than one for images inside links.
+    links: Vec<Link>,
+    /// Inline content goes into the heading of the innermost section.
+    heading: bool,
+    /// Code or HTML block being read.
+    code: Option<CodeBlock>,
+    /// Text not yet added, read with the current emphasis.
+    text: String,
+    /// One for each `*` that is open, two for each `**`.
+    emphasis: u8,
+    strikethrough: u8,
+}
+
+fn tagged(tag: &str) -> Tags {
+    Tags::from([tag.to_string()])
+}
+
+/// Add an inline item, merging neighbouring text.
+fn push_em_or_text(items: &mut Vec<EmOrText>, item: EmOrText) {
+    if let EmOrText::Text(text) = &item
+        && let Some(EmOrText::Text(last)) = items.last_mut()
+    {
+        last.push_str(text);
+    } else {
+        items.push(item);
+    }
+}
+
+/// Flatten an inline item for places that only hold emphasis and text, like headings and links.
+fn push_flattened(items: &mut Vec<EmOrText>, item: ParagraphItem) {
+    match item {
+        ParagraphItem::Text(text) => push_em_or_text(items, EmOrText::Text(text)),
+        ParagraphItem::MText(mtext) => push_em_or_text(items, EmOrText::Text(mtext.text)),
+        ParagraphItem::Em(em) => items.push(EmOrText::Em(em)),
+        ParagraphItem::Link(link) => {
+            for item in link.items {
+                push_em_or_text(items, item);
+            }
+        },
+        ParagraphItem::Quote(quote) => {
+            for item in quote.items {
+                push_em_or_text(items, item);
+            }
+        },
+        ParagraphItem::Code(_)
+        | ParagraphItem::List(_)
+        | ParagraphItem::Table(_)
+        | ParagraphItem::FootnoteRef(_)
+        | ParagraphItem::Data(_) => {},
+    }
+}
+
+impl Importer {
+    /// Level of a section started now.
+    fn section_level(&self) -> u8 {
+        self.sections.last().map_or(0, |frame| frame.section.heading.level.saturating_add(1))
+    }
+
+    fn open_section(&mut self, mut section: Section, md_level: Option<HeadingLevel>) {
+        section.heading.level = self.section_level();
+        self.sections.push(SectionFrame { section, md_level });
+    }
+
+    /// A section without content holds an empty paragraph, as a section can't be empty.
+    fn close_section(&mut self) {
+        if let Some(mut frame) = self.sections.pop() {
+            if frame.section.items.is_empty() {
+                frame.section.items.push(SectionItem::Paragraph(Paragraph {
+                    items: vec![ParagraphItem::Text(String::new())],
+                    ..Default::default()
+                }));
+            }
+            match self.sections.last_mut() {
+                Some(parent) => parent.section.items.push(SectionItem::Section(frame.section)),
+                None => self.doc.items.push(DocItem::Section(frame.section)),
+            }
+        }
+    }
+
+    /// Close the sections of headings, up to and including the first boundary.
+    fn close_boundary(&mut self) {
+        while let Some(frame) = self.sections.last() {
+            let boundary = frame.md_level.is_none();
+            self.close_section();
+            if boundary { break; }
+        }
+    }
+
+    fn commit_paragraph(&mut self, par: Paragraph) {
+        match self.sections.last_mut() {
+            Some(frame) => frame.section.items.push(SectionItem::Paragraph(par)),
+            None => self.doc.items.push(DocItem::Paragraph(par)),
+        }
+    }
+
+    /// The paragraph that paragraph items go into, started if there is none.
+    fn paragraph(&mut self) -> &mut Paragraph {
+        if !matches!(self.blocks.last(), Some(Block::Paragraph(_))) {
+            self.blocks.push(Block::Paragraph(Paragraph::default()));
+        }
+        match self.blocks.last_mut() {
+            Some(Block::Paragraph(par)) => par,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Take the innermost paragraph, adding it to the document if it is not part of a list or
+    /// table. An empty one holds an empty text, as a paragraph can't be empty.
+    fn end_paragraph(&mut self) -> Option<Paragraph> {
+        let Some(Block::Paragraph(mut par)) = self.blocks.pop() else { return None; };
+        if par.items.is_empty() {
+            par.items.push(ParagraphItem::Text(String::new()));
+        }
+        if self.blocks.is_empty() {
+            self.commit_paragraph(par);
+            None
+        } else {
+            Some(par)
+        }
+    }
+
+    /// Add a code block, list or table.
+    fn push_block(&mut self, item: ParagraphItem) {
+        self.paragraph().items.push(item);
+        if self.blocks.len() == 1 {
+            self.end_paragraph();
+        }
+    }
+
+    fn push_inline(&mut self, item: ParagraphItem) {
+        if let Some(link) = self.links.last_mut() {
+            push_flattened(&mut link.items, item);
+        } else if self.heading && let Some(frame) = self.sections.last_mut() {
+            push_flattened(&mut frame.section.heading.items, item);
+        } else {
+            let items = &mut self.paragraph().items;
+            if let ParagraphItem::Text(text) = &item
+                && let Some(ParagraphItem::Text(last)) = items.last_mut()
+            {
+                last.push_str(text);
+            } else {
+                items.push(item);
+            }
+        }
+    }
+
+    /// Add the text read so far with the emphasis it was read with.
+    fn flush_text(&mut self) {
+        if self.text.is_empty() { return; }
+        let text = mem::take(&mut self.text);
+        let (strength, etype) = match (self.strikethrough, self.emphasis) {
+            (0, 0) => return self.push_inline(ParagraphItem::Text(text)),
+            (0, 1) => (EmStrength::Light, EmType::Emphasis),
+            (0, 2) => (EmStrength::Medium, EmType::Emphasis),
+            (0, _) => (EmStrength::Strong, EmType::Emphasis),
+            _ => (EmStrength::Medium, EmType::Deemphasis),
+        };
+        let em = Emphasis { strength, etype, text, ..Default::default() };
+        self.push_inline(ParagraphItem::Em(em));
+    }
+
+    fn end_code(&mut self) {
+        if let Some(mut code) = self.code.take() {
+            if code.code.ends_with('\n') {
+                code.code.pop();
+            }
+            self.push_block(ParagraphItem::Code(Ok(code)));
+        }
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Text(text) => match &mut self.code {
+                Some(code) => code.code.push_str(&text),
+                None => self.text.push_str(&text),
+            },
+            Event::SoftBreak => self.text.push(' '),
+            Event::HardBreak => self.text.push('\n'),
+            Event::Html(html) => {
+                if let Some(code) = &mut self.code {
+                    code.code.push_str(&html);
+                }
+            },
+            event => {
+                self.flush_text();
+                self.structure(event);
+            },
+        }
+    }
+
+    fn structure(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(Tag::Paragraph) => {
+                // paragraphs in the same list item or table cell are put on separate lines
+                if let Some(Block::Paragraph(par)) = self.blocks.last_mut()
+                    && !par.items.is_empty()
+                {
+                    self.text.push('\n');
+                }
+            },
+            Event::End(TagEnd::Paragraph) if self.blocks.len() == 1 => {
+                self.end_paragraph();
+            },
+            Event::Start(Tag::Heading { level, id, classes, attrs }) => {
+                self.heading = self.blocks.is_empty();
+                if !self.heading { return; }
+                while let Some(frame) = self.sections.last()
+                    && frame.md_level.is_some_and(|md_level| md_level >= level)
+                {
+                    self.close_section();
+                }
+                let mut section = Section::default();
+                if let Some(id) = id {
+                    section.props.insert("id".to_string(), PropVal::String(id.to_string()));
+                }
+                section.heading.tags.extend(classes.iter().map(|class| class.to_string()));
+                for (key, val) in attrs {
+                    match val {
+                        Some(val) => {
+                            section.heading.props.insert(
+                                key.to_string(),
+                                PropVal::String(val.to_string()),
+                            );
+                        },
+                        None => { section.heading.tags.insert(key.to_string()); },
+                    }
+                }
+                self.open_section(section, Some(level));
+            },
+            Event::End(TagEnd::Heading(_)) => {
+                // a heading without text can't be written, so its content goes to the section
+                // around it
+                if mem::take(&mut self.heading)
+                    && let Some(frame) = self.sections.last()
+                    && frame.section.heading.items.is_empty()
+                {
+                    self.sections.pop();
+                }
+            },
+            Event::Start(Tag::BlockQuote(kind)) => {
+                let section = self.blocks.is_empty();
+                self.quotes.push(section);
+                if !section { return; }
+                let mut section = Section::default();
+                match kind {
+                    Some(kind) => {
+                        let kind = format!("{kind:?}");
+                        section.heading.items.push(EmOrText::Text(kind.clone()));
+                        section.tags.insert("blockquote-typed".to_string());
+                        section.props.insert("blockquote-type".to_string(), PropVal::String(kind));
+                    },
+                    None => {
+                        section.heading.items.push(EmOrText::Text("Quote".to_string()));
+                        section.tags.insert("blockquote".to_string());
+                    },
+                }
+                self.open_section(section, None);
+            },
+            Event::End(TagEnd::BlockQuote(_)) => {
+                let section = self.quotes.pop();
+                if section == Some(true) {
+                    self.close_boundary();
+                }
+            },
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                let mut section = Section {
+                    tags: tagged("footnote-def"),
+                    ..Default::default()
+                };
+                section.heading.items.push(EmOrText::Text(label.to_string()));
+                section.props.insert(
+                    "id".to_string(),
+                    PropVal::String(format!("footnote-{label}")),
+                );
+                self.open_section(section, None);
+            },
+            Event::End(TagEnd::FootnoteDefinition) => self.close_boundary(),
+            Event::FootnoteReference(label) => self.push_inline(ParagraphItem::Link(Link {
+                url: format!("#footnote-{label}"),
+                items: vec![EmOrText::Text(label.to_string())],
+                tags: tagged("footnote-ref"),
+                ..Default::default()
+            })),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match &kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next(),
+                    CodeBlockKind::Indented => None,
+                };
+                let language = language.unwrap_or("plain").to_string();
+                self.code = Some(CodeBlock { language, ..Default::default() });
+            },
+            Event::Start(Tag::HtmlBlock) => {
+                self.code = Some(CodeBlock { language: "html".to_string(), ..Default::default() });
+            },
+            Event::End(TagEnd::CodeBlock | TagEnd::HtmlBlock) => self.end_code(),
+            Event::Code(code) => self.push_inline(ParagraphItem::MText(TextWithMeta {
+                text: code.to_string(),
+                tags: tagged("code"),
+                ..Default::default()
+            })),
+            Event::InlineHtml(html) => self.push_inline(ParagraphItem::MText(TextWithMeta {
+                text: html.to_string(),
+                tags: tagged("html"),
+                ..Default::default()
+            })),
+            Event::Start(Tag::Emphasis) => self.emphasis += 1,
+            Event::End(TagEnd::Emphasis) => self.emphasis -= 1,
+            Event::Start(Tag::Strong) => self.emphasis += 2,
+            Event::End(TagEnd::Strong) => self.emphasis -= 2,
+            Event::Start(Tag::Strikethrough) => self.strikethrough += 1,
+            Event::End(TagEnd::Strikethrough) => self.strikethrough -= 1,
+            Event::Start(Tag::Link { dest_url, title, .. }) => {
+                let mut link = Link { url: dest_url.to_string(), ..Default::default() };
+                if !title.is_empty() {
+                    link.props.insert("title".to_string(), PropVal::String(title.to_string()));
+                }
+                self.links.push(link);
+            },
+            Event::Start(Tag::Image { dest_url, title, .. }) => {
+                let mut link = Link { url: dest_url.to_string(), ..Default::default() };
+                link.props.insert("type-hint".to_string(), PropVal::Text("image".to_string()));
+                if !title.is_empty() {
+                    link.props.insert("title".to_string(), PropVal::String(title.to_string()));
+                }
+                self.links.push(link);
+            },
+            Event::End(TagEnd::Link | TagEnd::Image) => match self.links.pop() {
+                // a link needs a url, without one only its text is left
+                Some(link) if link.url.is_empty() => for item in link.items {
+                    self.push_inline(match item {
+                        EmOrText::Text(text) => ParagraphItem::Text(text),
+                        EmOrText::Em(em) => ParagraphItem::Em(em),
+                    });
+                },
+                // and it needs text, which is the url when there is none
+                Some(mut link) => {
+                    if link.items.is_empty() {
+                        link.items.push(EmOrText::Text(link.url.clone()));
+                    }
+                    self.push_inline(ParagraphItem::Link(link));
+                },
+                None => {},
+            },
+            Event::Start(Tag::List(start)) => {
+                if self.blocks.is_empty() {
+                    self.blocks.push(Block::Paragraph(Paragraph::default()));
+                }
+                let ltype = if start.is_some() { ListType::Distinct } else { ListType::Identical };
+                self.blocks.push(Block::List(List { ltype, ..Default::default() }));
+            },
+            Event::Start(Tag::Item | Tag::TableCell) => {
+                self.blocks.push(Block::Paragraph(Paragraph::default()));
+            },
+            Event::TaskListMarker(checked) => {
+                if let [.., Block::List(list), Block::Paragraph(par)] = &mut self.blocks[..] {
+                    list.ltype = ListType::Checked;
+                    if checked {
+                        par.tags.insert("checked".to_string());
+                    }
+                }
+            },
+            Event::End(TagEnd::Item) => {
+                if let Some(par) = self.end_paragraph()
+                    && let Some(Block::List(list)) = self.blocks.last_mut()
+                {
+                    list.items.push(par);
+                }
+            },
+            Event::End(TagEnd::List(_)) => {
+                if let Some(Block::List(list)) = self.blocks.pop() {
+                    self.push_block(ParagraphItem::List(list));
+                }
+            },
+            Event::Start(Tag::Table(_)) => {
+                if self.blocks.is_empty() {
+                    self.blocks.push(Block::Paragraph(Paragraph::default()));
+                }
+                self.blocks.push(Block::Table(Table::default()));
+            },
+            Event::Start(Tag::TableHead) => self.blocks.push(Block::Row(TableRow {
+                is_header: true,
+                ..Default::default()
+            })),
+            Event::Start(Tag::TableRow) => self.blocks.push(Block::Row(TableRow::default())),
+            Event::End(TagEnd::TableCell) => {
+                if let Some(par) = self.end_paragraph()
+                    && let Some(Block::Row(row)) = self.blocks.last_mut()
+                {
+                    row.items.push(par);
+                }
+            },
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => {
+                if let Some(Block::Row(row)) = self.blocks.pop()
+                    && let Some(Block::Table(table)) = self.blocks.last_mut()
+                {
+                    table.rows.push(row);
+                }
+            },
+            Event::End(TagEnd::Table) => {
+                if let Some(Block::Table(table)) = self.blocks.pop() {
+                    self.push_block(ParagraphItem::Table(table));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn finish(mut self) -> Doc {
+        // every block markdown starts is also ended, only the sections of headings remain open
+        self.flush_text();
+        while !self.sections.is_empty() {
+            self.close_section();
+        }
+        self.doc.migrate_quotes();
+        self.doc.migrate_footnotes();
+        self.doc
+    }
+}
+
+/// Parse CommonMark with the GitHub flavoured extensions into a document.
+/// Markdown has no syntax errors, so this always results in a document.
+pub fn parse_markdown(input: &str) -> Doc {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM;
+    let mut importer = Importer::default();
+    for event in Parser::new_ext(input, options) {
+        importer.event(event);
+    }
+    importer.finish()
+}
@@ -0,0 +1,6 @@
+pub mod gemtext;
+pub mod org;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+#[cfg(feature = "html")]
+pub mod html;
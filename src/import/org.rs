@@ -0,0 +1,605 @@
+//! Import Org mode documents.
+//!
+//! - Headlines become nested sections. The level of a headline is one more than the level of
+//!   the section it is in, no matter how many levels Org skips. The tags of a headline become
+//!   the tags of its section and a `CUSTOM_ID` in its property drawer becomes its `id`.
+//!   Headlines without text are left out, their content goes to the section around them.
+//! - What is left empty is filled, as incodoc has no empty paragraphs or sections: empty
+//!   paragraphs, list items and table cells hold an empty text, sections without content an
+//!   empty paragraph.
+//! - `/italic/` and `*bold*` become light and medium emphasis, `+strike+` becomes medium
+//!   deemphasis. `=verbatim=` and `~code~` become text with the `code` tag.
+//! - `[[url][description]]` and `[[url]]` become links. Links to images without a description
+//!   get the `type-hint` property set to `image`.
+//! - Plain lists become lists, ordered lists numbered lists and lists with checkboxes checked
+//!   lists where the items checked with `[X]` have the `checked` tag. A bullet of the other kind
+//!   starts a new list.
+//! - Tables become tables. When a separator line follows the first row, it is a header row.
+//! - `#+BEGIN_SRC` blocks become code in their language, or `plain` without one.
+//!   `#+BEGIN_EXAMPLE` blocks become `plain` code.
+//! - `#+BEGIN_QUOTE` blocks become quotes.
+//! - `#+TITLE`, `#+AUTHOR` and `#+DATE` become the `title`, `author` and `date` properties,
+//!   `#+LANGUAGE` the `language` property. A date like `<2024-05-01 Wed>` becomes a date.
+//!
+//! Comments, other keywords, drawers and other blocks are left out.
+
+use crate::*;
+
+/// Section that is still being read, with the level of its Org headline.
+struct SectionFrame {
+    section: Section,
+    org_level: usize,
+}
+
+#[derive(Default)]
+struct Importer {
+    doc: Doc,
+    sections: Vec<SectionFrame>,
+}
+
+/// Content that goes in a section.
+enum Block {
+    Paragraph(Paragraph),
+    Quote(Vec<Block>),
+}
+
+const IMAGE_EXTENSIONS: [&str; 7] = [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp", ".bmp"];
+
+fn tagged(tag: &str) -> Tags {
+    Tags::from([tag.to_string()])
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Level and text of a headline.
+fn headline(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start_matches('*');
+    let level = line.len() - text.len();
+    (level > 0 && text.starts_with(' ')).then(|| (level, text.trim()))
+}
+
+/// Indentation of a list item and the text after its bullet, with whether it is ordered.
+fn bullet(line: &str) -> Option<(usize, bool, &str)> {
+    let indent = indentation(line);
+    let trimmed = line.trim_start();
+    let (ordered, rest) = if let Some(rest) = trimmed.strip_prefix(['-', '+']) {
+        (false, rest)
+    } else if indent > 0 && let Some(rest) = trimmed.strip_prefix('*') {
+        (false, rest)
+    } else {
+        let digits = trimmed.len() - trimmed.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let rest = trimmed[digits..].strip_prefix(['.', ')']).filter(|_| digits > 0)?;
+        (true, rest)
+    };
+    if rest.is_empty() {
+        return Some((indent, ordered, ""));
+    }
+    rest.starts_with(' ').then(|| (indent, ordered, rest.trim()))
+}
+
+fn is_table(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed == "#" || trimmed.starts_with("# ")
+}
+
+/// Name of the block started by a line like `#+BEGIN_SRC rust`, with its parameters.
+fn block_start(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim();
+    let rest = trimmed.get(..8)
+        .filter(|start| start.eq_ignore_ascii_case("#+begin_"))
+        .map(|_| &trimmed[8..])?;
+    let (name, params) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((name.to_lowercase(), params.trim()))
+}
+
+fn is_block_end(line: &str, name: &str) -> bool {
+    line.trim().to_lowercase() == format!("#+end_{name}")
+}
+
+/// Keyword and value of a line like `#+TITLE: value`.
+fn keyword(line: &str) -> Option<(String, &str)> {
+    let rest = line.trim().strip_prefix("#+")?;
+    let (key, value) = rest.split_once(':')?;
+    (!key.is_empty() && !key.contains(char::is_whitespace))
+        .then(|| (key.to_lowercase(), value.trim()))
+}
+
+fn is_drawer(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.len() > 2
+        && trimmed.starts_with(':')
+        && trimmed.ends_with(':')
+        && !trimmed[1..trimmed.len() - 1].contains(char::is_whitespace)
+}
+
+/// Whether a line starts something other than paragraph text.
+fn is_special(line: &str) -> bool {
+    line.trim().is_empty()
+        || headline(line).is_some()
+        || bullet(line).is_some()
+        || is_table(line)
+        || is_comment(line)
+        || line.trim_start().starts_with("#+")
+        || is_drawer(line)
+}
+
+fn org_date(value: &str) -> PropVal {
+    let date = value.trim_matches(['<', '>', '[', ']']).split_whitespace().next().unwrap_or("");
+    let mut parts = date.split('-');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(y), Some(m), Some(d), None) => match (y.parse(), m.parse(), d.parse()) {
+            (Ok(y), Ok(m), Ok(d)) => match Date::new(y, m, d) {
+                Ok(date) => PropVal::Date(date),
+                Err(_) => PropVal::Text(value.to_string()),
+            },
+            _ => PropVal::Text(value.to_string()),
+        },
+        _ => PropVal::Text(value.to_string()),
+    }
+}
+
+/// Can emphasis start after this character.
+fn is_pre(c: Option<char>) -> bool {
+    c.is_none_or(|c| c.is_whitespace() || "-({'\"".contains(c))
+}
+
+/// Can emphasis end before this character.
+fn is_post(c: Option<char>) -> bool {
+    c.is_none_or(|c| c.is_whitespace() || "-.,;:!?')}[\"\\".contains(c))
+}
+
+fn push_text(items: &mut Vec<ParagraphItem>, text: &str) {
+    if let Some(ParagraphItem::Text(last)) = items.last_mut() {
+        last.push_str(text);
+    } else if !text.is_empty() {
+        items.push(ParagraphItem::Text(text.to_string()));
+    }
+}
+
+fn org_link(inner: &str) -> Link {
+    let (url, description) = match inner.split_once("][") {
+        Some((url, description)) => (url, Some(description).filter(|text| !text.is_empty())),
+        None => (inner, None),
+    };
+    let mut link = Link { url: url.to_string(), ..Default::default() };
+    match description {
+        Some(description) => link.items = to_em_or_text(inline(description)),
+        None => {
+            let lower = url.to_lowercase();
+            if IMAGE_EXTENSIONS.iter().any(|extension| lower.ends_with(extension)) {
+                link.props.insert(
+                    "type-hint".to_string(),
+                    PropVal::Text("image".to_string()),
+                );
+            }
+            let text = url.strip_prefix("file:").unwrap_or(url);
+            link.items.push(EmOrText::Text(text.to_string()));
+        },
+    }
+    link
+}
+
+fn to_em_or_text(items: Vec<ParagraphItem>) -> Vec<EmOrText> {
+    items.into_iter().flat_map(|item| match item {
+        ParagraphItem::Text(text) => vec![EmOrText::Text(text)],
+        ParagraphItem::MText(mtext) => vec![EmOrText::Text(mtext.text)],
+        ParagraphItem::Em(em) => vec![EmOrText::Em(em)],
+        ParagraphItem::Link(link) => link.items,
+        _ => Vec::new(),
+    }).collect()
+}
+
+/// Read markup and links in a line of text.
+fn inline(text: &str) -> Vec<ParagraphItem> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut items = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '['
+            && chars.get(i + 1) == Some(&'[')
+            && let Some(end) = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == ']' && chars[j + 1] == ']')
+        {
+            push_text(&mut items, &plain);
+            plain.clear();
+            let inner: String = chars[i + 2..end].iter().collect();
+            items.push(ParagraphItem::Link(org_link(&inner)));
+            i = end + 2;
+            continue;
+        }
+        if "*/+=~".contains(c)
+            && is_pre(i.checked_sub(1).map(|p| chars[p]))
+            && chars.get(i + 1).is_some_and(|next| !next.is_whitespace())
+            && let Some(end) = (i + 1..chars.len()).find(|&j| chars[j] == c
+                && j > i + 1
+                && !chars[j - 1].is_whitespace()
+                && is_post(chars.get(j + 1).copied()))
+        {
+            push_text(&mut items, &plain);
+            plain.clear();
+            let inner: String = chars[i + 1..end].iter().collect();
+            let (strength, etype) = match c {
+                '/' => (EmStrength::Light, EmType::Emphasis),
+                '*' => (EmStrength::Medium, EmType::Emphasis),
+                '+' => (EmStrength::Medium, EmType::Deemphasis),
+                _ => {
+                    items.push(ParagraphItem::MText(TextWithMeta {
+                        text: inner,
+                        tags: tagged("code"),
+                        ..Default::default()
+                    }));
+                    i = end + 1;
+                    continue;
+                },
+            };
+            items.push(ParagraphItem::Em(Emphasis {
+                strength,
+                etype,
+                text: inner,
+                ..Default::default()
+            }));
+            i = end + 1;
+            continue;
+        }
+        plain.push(c);
+        i += 1;
+    }
+    push_text(&mut items, &plain);
+    items
+}
+
+/// A paragraph of a line of text. An empty one holds an empty text, as a paragraph can't be empty.
+fn inline_par(text: &str) -> Paragraph {
+    let mut items = inline(text);
+    if items.is_empty() {
+        items.push(ParagraphItem::Text(String::new()));
+    }
+    Paragraph { items, ..Default::default() }
+}
+
+/// A section without content holds an empty paragraph, as a section can't be empty.
+fn filled(mut section: Section) -> Section {
+    if section.items.is_empty() {
+        section.items.push(SectionItem::Paragraph(inline_par("")));
+    }
+    section
+}
+
+/// Lines of a block, without the indentation they have in common.
+fn dedented(lines: &[&str]) -> String {
+    let indent = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or(0);
+    lines.iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn table(lines: &[&str], pos: &mut usize) -> Table {
+    let mut table = Table::default();
+    while let Some(line) = lines.get(*pos).filter(|line| is_table(line)) {
+        *pos += 1;
+        let trimmed = line.trim();
+        if trimmed.starts_with("|-") {
+            if table.rows.len() == 1 {
+                table.rows[0].is_header = true;
+            }
+            continue;
+        }
+        let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+        let inner = inner.strip_suffix('|').unwrap_or(inner);
+        table.rows.push(TableRow {
+            items: inner.split('|').map(|cell| inline_par(cell.trim())).collect(),
+            ..Default::default()
+        });
+    }
+    table
+}
+
+fn list(lines: &[&str], pos: &mut usize) -> List {
+    let (indent, ordered, _) = bullet(lines[*pos]).unwrap_or_default();
+    let mut list = List {
+        ltype: if ordered { ListType::Distinct } else { ListType::Identical },
+        ..Default::default()
+    };
+    while let Some(line) = lines.get(*pos) {
+        if line.trim().is_empty() {
+            let next = lines[*pos..].iter().find(|line| !line.trim().is_empty());
+            match next.and_then(|line| bullet(line)) {
+                Some((next_indent, next_ordered, _))
+                    if next_indent > indent || (next_indent == indent && next_ordered == ordered) =>
+                {
+                    *pos += 1;
+                    continue;
+                },
+                _ => break,
+            }
+        }
+        let Some((item_indent, _, text)) = bullet(line)
+            .filter(|(i, o, _)| *i == indent && *o == ordered)
+        else {
+            break;
+        };
+        *pos += 1;
+        let mut text = text;
+        let mut checked = None;
+        for (mark, state) in [("[ ]", false), ("[X]", true), ("[x]", true), ("[-]", false)] {
+            if let Some(rest) = text.strip_prefix(mark) {
+                checked = Some(state);
+                text = rest.trim_start();
+            }
+        }
+        let mut content = text.to_string();
+        let mut nested = Vec::new();
+        while let Some(line) = lines.get(*pos) {
+            if line.trim().is_empty() || indentation(line) <= item_indent {
+                if line.trim().is_empty()
+                    && lines[*pos..].iter()
+                        .find(|line| !line.trim().is_empty())
+                        .is_some_and(|line| indentation(line) > item_indent)
+                {
+                    *pos += 1;
+                    continue;
+                }
+                break;
+            }
+            if bullet(line).is_some() {
+                nested.push(ParagraphItem::List(self::list(lines, pos)));
+            } else if is_table(line) {
+                nested.push(ParagraphItem::Table(table(lines, pos)));
+            } else if let Some((name, params)) = block_start(line) {
+                nested.extend(code_block(lines, pos, &name, params));
+            } else {
+                if !content.is_empty() {
+                    content.push(' ');
+                }
+                content.push_str(line.trim());
+                *pos += 1;
+            }
+        }
+        let mut par = Paragraph { items: inline(&content), ..Default::default() };
+        par.items.extend(nested);
+        if par.items.is_empty() {
+            par.items.push(ParagraphItem::Text(String::new()));
+        }
+        if let Some(checked) = checked {
+            list.ltype = ListType::Checked;
+            if checked {
+                par.tags.insert("checked".to_string());
+            }
+        }
+        list.items.push(par);
+    }
+    list
+}
+
+/// Read a `#+BEGIN_` block up to its end; only source and example blocks become code.
+fn code_block(lines: &[&str], pos: &mut usize, name: &str, params: &str) -> Option<ParagraphItem> {
+    let start = *pos + 1;
+    let end = (start..lines.len()).find(|&i| is_block_end(lines[i], name)).unwrap_or(lines.len());
+    *pos = (end + 1).min(lines.len());
+    let language = match name {
+        "src" => params.split_whitespace().next().unwrap_or("plain"),
+        "example" => "plain",
+        _ => return None,
+    };
+    Some(ParagraphItem::Code(Ok(CodeBlock {
+        language: language.to_string(),
+        code: dedented(&lines[start..end]),
+        ..Default::default()
+    })))
+}
+
+/// Read one block at the position, or nothing for lines that are left out.
+fn block(lines: &[&str], pos: &mut usize) -> Option<Block> {
+    let line = lines[*pos];
+    let single = |item| Some(Block::Paragraph(Paragraph {
+        items: vec![item],
+        ..Default::default()
+    }));
+    if let Some((name, params)) = block_start(line) {
+        if name == "quote" {
+            let start = *pos + 1;
+            let end = (start..lines.len())
+                .find(|&i| is_block_end(lines[i], "quote"))
+                .unwrap_or(lines.len());
+            *pos = (end + 1).min(lines.len());
+            return Some(Block::Quote(blocks(&lines[start..end])));
+        }
+        return code_block(lines, pos, &name, params).and_then(single);
+    }
+    if bullet(line).is_some() {
+        return single(ParagraphItem::List(list(lines, pos)));
+    }
+    if is_table(line) {
+        return single(ParagraphItem::Table(table(lines, pos)));
+    }
+    if is_drawer(line) {
+        while let Some(line) = lines.get(*pos) {
+            *pos += 1;
+            if line.trim().eq_ignore_ascii_case(":end:") {
+                break;
+            }
+        }
+        return None;
+    }
+    if is_special(line) {
+        *pos += 1;
+        return None;
+    }
+    let mut text = Vec::new();
+    while let Some(line) = lines.get(*pos).filter(|line| text.is_empty() || !is_special(line)) {
+        text.push(line.trim());
+        *pos += 1;
+    }
+    Some(Block::Paragraph(inline_par(&text.join(" "))))
+}
+
+/// Read the blocks of lines without headlines, like the lines of a quote.
+fn blocks(lines: &[&str]) -> Vec<Block> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while pos < lines.len() {
+        res.extend(block(lines, &mut pos));
+    }
+    res
+}
+
+impl Importer {
+    fn level(&self) -> u8 {
+        self.sections.last().map(|frame| frame.section.heading.level + 1).unwrap_or(0)
+    }
+
+    fn quote_section(level: u8, blocks: Vec<Block>) -> Section {
+        let mut section = Section {
+            heading: Heading {
+                level,
+                items: vec![EmOrText::Text("Quote".to_string())],
+                ..Default::default()
+            },
+            tags: tagged("blockquote"),
+            ..Default::default()
+        };
+        for block in blocks {
+            section.items.push(match block {
+                Block::Paragraph(par) => SectionItem::Paragraph(par),
+                Block::Quote(blocks) => {
+                    SectionItem::Section(Self::quote_section(level + 1, blocks))
+                },
+            });
+        }
+        filled(section)
+    }
+
+    fn push_section(&mut self, section: Section) {
+        match self.sections.last_mut() {
+            Some(frame) => frame.section.items.push(SectionItem::Section(section)),
+            None => self.doc.items.push(DocItem::Section(section)),
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        match block {
+            Block::Paragraph(par) => match self.sections.last_mut() {
+                Some(frame) => frame.section.items.push(SectionItem::Paragraph(par)),
+                None => self.doc.items.push(DocItem::Paragraph(par)),
+            },
+            Block::Quote(blocks) => {
+                let section = Self::quote_section(self.level(), blocks);
+                self.push_section(section);
+            },
+        }
+    }
+
+    fn close_section(&mut self) {
+        if let Some(frame) = self.sections.pop() {
+            self.push_section(filled(frame.section));
+        }
+    }
+
+    /// Start the section of a headline. A headline without text is left out, as a heading can't
+    /// be empty; returns whether there is a section.
+    fn headline(&mut self, org_level: usize, text: &str) -> bool {
+        while self.sections.last().is_some_and(|frame| frame.org_level >= org_level) {
+            self.close_section();
+        }
+        let mut text = text;
+        let mut tags = Tags::new();
+        if let Some((rest, last)) = text.rsplit_once(char::is_whitespace)
+            && last.len() > 2
+            && last.starts_with(':')
+            && last.ends_with(':')
+        {
+            tags.extend(last.split(':').filter(|tag| !tag.is_empty()).map(str::to_string));
+            text = rest.trim_end();
+        }
+        let items = to_em_or_text(inline(text));
+        if items.is_empty() {
+            return false;
+        }
+        let section = Section {
+            heading: Heading { level: self.level(), items, ..Default::default() },
+            tags,
+            ..Default::default()
+        };
+        self.sections.push(SectionFrame { section, org_level });
+        true
+    }
+
+    fn keyword(&mut self, key: &str, value: &str) {
+        let prop = match key {
+            "title" => ("title", PropVal::Text(value.to_string())),
+            "author" => ("author", PropVal::Text(value.to_string())),
+            "date" => ("date", org_date(value)),
+            "language" => ("language", PropVal::String(value.to_string())),
+            _ => return,
+        };
+        if !value.is_empty() {
+            self.doc.props.insert(prop.0.to_string(), prop.1);
+        }
+    }
+
+    /// Read a property drawer of the current headline for its `CUSTOM_ID`.
+    fn properties(&mut self, lines: &[&str], pos: &mut usize) {
+        *pos += 1;
+        while let Some(line) = lines.get(*pos) {
+            *pos += 1;
+            let trimmed = line.trim();
+            if trimmed.eq_ignore_ascii_case(":end:") {
+                break;
+            }
+            if let Some(id) = trimmed.strip_prefix(":CUSTOM_ID:").map(str::trim)
+                && !id.is_empty()
+                && let Some(frame) = self.sections.last_mut()
+            {
+                frame.section.props.insert("id".to_string(), PropVal::String(id.to_string()));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Doc {
+        while !self.sections.is_empty() {
+            self.close_section();
+        }
+        self.doc.migrate_quotes();
+        self.doc
+    }
+}
+
+/// Convert an Org mode document to a document.
+pub fn parse_org(input: &str) -> Doc {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut importer = Importer::default();
+    let mut pos = 0;
+    while pos < lines.len() {
+        let line = lines[pos];
+        if let Some((level, text)) = headline(line) {
+            let opened = importer.headline(level, text);
+            pos += 1;
+            let drawer = lines.get(pos)
+                .is_some_and(|line| line.trim().eq_ignore_ascii_case(":properties:"));
+            if opened && drawer {
+                importer.properties(&lines, &mut pos);
+            }
+        } else if block_start(line).is_none()
+            && let Some((key, value)) = keyword(line)
+        {
+            importer.keyword(&key, value);
+            pos += 1;
+        } else if let Some(block) = block(&lines, &mut pos) {
+            importer.push_block(block);
+        }
+    }
+    importer.finish()
+}
@@ -0,0 +1,233 @@
+//! JSON interchange format, enabled with the `json` feature.
+//!
+//! A document is wrapped together with the version of the format:
+//! `{"version": 1, "doc": {...}}`.
+//! The document itself uses the encoding described in [`crate::serde_impls`].
+//! [`JSON_SCHEMA`] is the JSON Schema of the current version.
+
+use crate::*;
+
+use std::fmt;
+
+use serde::{ Serialize, Deserialize };
+use serde_json::Value;
+
+/// Version of the JSON format that is written and read.
+pub const JSON_VERSION: u64 = 1;
+
+/// JSON Schema of the current version of the JSON format.
+pub const JSON_SCHEMA: &str = include_str!("../schema/incodoc-v1.schema.json");
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    doc: &'a Doc,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OwnedEnvelope {
+    version: Value,
+    doc: Value,
+}
+
+/// Error when JSON could not be read as a document.
+#[derive(Debug)]
+pub enum JsonError {
+    /// The input is not JSON or does not have the shape of a document.
+    Json(serde_json::Error),
+    /// The version is not supported.
+    Version(Value),
+    /// A date that is not a date.
+    Date(DateError),
+    /// A field that is not part of the format, at the path to it.
+    UnknownField(String),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "{error}"),
+            Self::Version(version) => write!(f, "unsupported version: {version}"),
+            Self::Date(error) => write!(f, "invalid date: {error:?}"),
+            Self::UnknownField(path) => write!(f, "unknown field: {path}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError { }
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// Write a document as JSON in the interchange format.
+pub fn to_json(doc: &Doc) -> Result<String, JsonError> {
+    Ok(serde_json::to_string(&Envelope { version: JSON_VERSION, doc })?)
+}
+
+/// Write a document as indented JSON in the interchange format.
+pub fn to_json_pretty(doc: &Doc) -> Result<String, JsonError> {
+    Ok(serde_json::to_string_pretty(&Envelope { version: JSON_VERSION, doc })?)
+}
+
+/// Read a document from JSON in the interchange format.
+/// Besides the shape of the document, the version and all dates are checked, and fields that are
+/// not part of the format are an error.
+pub fn from_json(input: &str) -> Result<Doc, JsonError> {
+    let envelope: OwnedEnvelope = serde_json::from_str(input)?;
+    if envelope.version.as_u64() != Some(JSON_VERSION) {
+        return Err(JsonError::Version(envelope.version));
+    }
+    let doc: Doc = serde_json::from_value(envelope.doc.clone())?;
+    // the model ignores unknown fields, every field it knows comes back when writing it again
+    validate_fields(&envelope.doc, &serde_json::to_value(&doc)?, "doc")?;
+    validate_doc(&doc)?;
+    Ok(doc)
+}
+
+fn validate_fields(input: &Value, known: &Value, path: &str) -> Result<(), JsonError> {
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => {
+            for (key, value) in input {
+                let path = format!("{path}.{key}");
+                match known.get(key) {
+                    Some(known) => validate_fields(value, known, &path)?,
+                    None => return Err(JsonError::UnknownField(path)),
+                }
+            }
+        },
+        (Value::Array(input), Value::Array(known)) => {
+            for (i, (value, known)) in input.iter().zip(known).enumerate() {
+                validate_fields(value, known, &format!("{path}[{i}]"))?;
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+fn validate_props(props: &Props) -> Result<(), JsonError> {
+    for val in props.values() {
+        if let PropVal::Date(date) = val {
+            Date::new(date.year.into(), date.month.into(), date.day.into())
+                .map_err(JsonError::Date)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_em_or_text(items: &[EmOrText]) -> Result<(), JsonError> {
+    for item in items {
+        if let EmOrText::Em(em) = item {
+            validate_props(&em.props)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_link(link: &Link) -> Result<(), JsonError> {
+    validate_em_or_text(&link.items)?;
+    validate_props(&link.props)
+}
+
+fn validate_nav(nav: &Nav) -> Result<(), JsonError> {
+    for link in &nav.links {
+        validate_link(link)?;
+    }
+    for sub in &nav.subs {
+        validate_nav(sub)?;
+    }
+    validate_props(&nav.props)
+}
+
+fn validate_paragraph(par: &Paragraph) -> Result<(), JsonError> {
+    for item in &par.items {
+        match item {
+            ParagraphItem::Text(_) | ParagraphItem::Code(Err(_)) => {},
+            ParagraphItem::MText(mtext) => validate_props(&mtext.props)?,
+            ParagraphItem::Em(em) => validate_props(&em.props)?,
+            ParagraphItem::Code(Ok(code)) => validate_props(&code.props)?,
+            ParagraphItem::Link(link) => validate_link(link)?,
+            ParagraphItem::List(list) => {
+                for par in &list.items {
+                    validate_paragraph(par)?;
+                }
+                validate_props(&list.props)?;
+            },
+            ParagraphItem::Table(table) => {
+                for row in &table.rows {
+                    for par in &row.items {
+                        validate_paragraph(par)?;
+                    }
+                    validate_props(&row.props)?;
+                }
+                validate_props(&table.props)?;
+            },
+            ParagraphItem::Quote(quote) => {
+                validate_em_or_text(&quote.items)?;
+                validate_em_or_text(&quote.attribution)?;
+                if let Some(source) = &quote.source {
+                    validate_link(source)?;
+                }
+                validate_props(&quote.props)?;
+            },
+            ParagraphItem::FootnoteRef(footnote_ref) => validate_props(&footnote_ref.props)?,
+            ParagraphItem::Data(data) => validate_props(&data.props)?,
+        }
+    }
+    validate_props(&par.props)
+}
+
+fn validate_footnote(footnote: &FootnoteDef) -> Result<(), JsonError> {
+    for par in &footnote.items {
+        validate_paragraph(par)?;
+    }
+    validate_props(&footnote.props)
+}
+
+fn validate_quote(quote: &Quote) -> Result<(), JsonError> {
+    for item in &quote.items {
+        match item {
+            QuoteItem::Paragraph(par) => validate_paragraph(par)?,
+            QuoteItem::Quote(quote) => validate_quote(quote)?,
+        }
+    }
+    validate_em_or_text(&quote.attribution)?;
+    if let Some(source) = &quote.source {
+        validate_link(source)?;
+    }
+    validate_props(&quote.props)
+}
+
+fn validate_section(section: &Section) -> Result<(), JsonError> {
+    validate_em_or_text(&section.heading.items)?;
+    validate_props(&section.heading.props)?;
+    for item in &section.items {
+        match item {
+            SectionItem::Paragraph(par) => validate_paragraph(par)?,
+            SectionItem::Section(section) => validate_section(section)?,
+            SectionItem::Quote(quote) => validate_quote(quote)?,
+            SectionItem::Footnote(footnote) => validate_footnote(footnote)?,
+        }
+    }
+    validate_props(&section.props)
+}
+
+fn validate_doc(doc: &Doc) -> Result<(), JsonError> {
+    validate_props(&doc.props)?;
+    for nav in &doc.navs {
+        validate_nav(nav)?;
+    }
+    for item in &doc.items {
+        match item {
+            DocItem::Paragraph(par) => validate_paragraph(par)?,
+            DocItem::Section(section) => validate_section(section)?,
+            DocItem::Quote(quote) => validate_quote(quote)?,
+            DocItem::Footnote(footnote) => validate_footnote(footnote)?,
+        }
+    }
+    Ok(())
+}
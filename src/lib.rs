@@ -1,8 +1,15 @@
 mod tests;
 pub mod parsing;
 pub mod output;
+pub mod format;
 pub mod actions;
+pub mod export;
+pub mod import;
 pub mod reference_doc;
+#[cfg(feature = "serde")]
+pub mod serde_impls;
+#[cfg(feature = "json")]
+pub mod json;
 
 use std::{
     num::ParseIntError,
@@ -18,18 +25,26 @@ pub struct IncodocParser;
 
 /// Document.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Doc {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
     pub navs: Vec<Nav>,
     pub items: Vec<DocItem>,
+    pub comments: Vec<Comment>,
 }
 
 /// Document item.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DocItem {
     Paragraph(Paragraph),
     Section(Section),
+    Quote(Quote),
+    Footnote(FootnoteDef),
 }
 
 /// Tags metadata. Each tag is a string.
@@ -40,6 +55,7 @@ pub type Props = HashMap<String, PropVal>;
 
 /// Value properties can take.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PropVal {
     String(String),
     Text(String),
@@ -50,8 +66,12 @@ pub enum PropVal {
 
 /// Error when no valid value could be parsed.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PropValError {
-    Int(ParseIntError),
+    Int(
+        #[cfg_attr(feature = "serde", serde(with = "serde_impls::parse_int_error"))]
+        ParseIntError
+    ),
     Date(DateError),
 }
 
@@ -73,39 +93,57 @@ fn insert_prop(props: &mut Props, (k, v): (String, PropVal)) {
 
 /// A section is a heading followed by content that goes with it.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Section {
     pub heading: Heading,
     pub items: Vec<SectionItem>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
-/// Section items are either paragraphs or sub-sections.
+/// Section items are paragraphs, sub-sections, quotes or footnotes.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SectionItem {
     Paragraph(Paragraph),
     Section(Section),
+    Quote(Quote),
+    Footnote(FootnoteDef),
 }
 
 /// Heading, a title for the accompanying content.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Heading {
     pub level: u8,
     pub items: Vec<EmOrText>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
 }
 
 /// Paragraph is a grouping of content.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Paragraph {
     pub items: Vec<ParagraphItem>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
 /// Paragraphs can have content and further structure but no smaller sub-sections.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParagraphItem {
     Text(String),
     MText(TextWithMeta),
@@ -114,20 +152,144 @@ pub enum ParagraphItem {
     Link(Link),
     List(List),
     Table(Table),
+    Quote(InlineQuote),
+    FootnoteRef(FootnoteRef),
+    Data(Data),
 }
 
+/// Block quote: paragraphs taken from elsewhere.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Quote {
+    /// What kind of quote it is, like `note` or `warning` for a callout.
+    pub kind: Option<String>,
+    pub items: Vec<QuoteItem>,
+    /// Who or what is quoted.
+    pub attribution: Vec<EmOrText>,
+    /// Where the quote was taken from.
+    pub source: Option<Link>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
+    pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
+    pub props: Props,
+    pub comments: Vec<Comment>,
+}
+
+/// Quote items are paragraphs or quotes within the quote.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuoteItem {
+    Paragraph(Paragraph),
+    Quote(Quote),
+}
+
+/// Quote within a paragraph.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InlineQuote {
+    /// What kind of quote it is.
+    pub kind: Option<String>,
+    pub items: Vec<EmOrText>,
+    /// Who or what is quoted.
+    pub attribution: Vec<EmOrText>,
+    /// Where the quote was taken from.
+    pub source: Option<Link>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
+    pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
+    pub props: Props,
+}
+
+/// Footnote: paragraphs that references in the text point to by its label.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FootnoteDef {
+    /// Name that references use for this footnote. It is not its number: footnotes are
+    /// numbered when they are resolved, see [`Doc::resolve_footnotes`].
+    pub label: String,
+    pub items: Vec<Paragraph>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
+    pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
+    pub props: Props,
+    pub comments: Vec<Comment>,
+}
+
+/// Reference in the text to the footnote with the same label.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FootnoteRef {
+    pub label: String,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
+    pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
+    pub props: Props,
+}
+
+/// Dataset: rows of typed values. Consumers choose how to show it, as a chart for example, and
+/// those that can't can fall back on [`Data::to_table`]. The `type-hint` property says how it is
+/// meant to be shown, like `line-graph` or `truth-table`.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Data {
+    /// Names of the columns. It is empty if the dataset has no header.
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<DataCell>>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
+    pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
+    pub props: Props,
+    pub comments: Vec<Comment>,
+}
+
+/// Value in a dataset.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataCell {
+    Text(String),
+    Int(i64),
+    Float(#[cfg_attr(feature = "serde", serde(with = "serde_impls::float"))] f64),
+    Bool(bool),
+}
+
+/// Floats are equal when they have the same bits, so that `NaN` equals itself and datasets can be
+/// compared like the rest of the document.
+impl PartialEq for DataCell {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DataCell::Text(a), DataCell::Text(b)) => a == b,
+            (DataCell::Int(a), DataCell::Int(b)) => a == b,
+            (DataCell::Float(a), DataCell::Float(b)) => a.to_bits() == b.to_bits(),
+            (DataCell::Bool(a), DataCell::Bool(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DataCell {}
+
 /// Emphasised or de-emphasised piece of text.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Emphasis {
     pub strength: EmStrength,
     pub etype: EmType,
     pub text: String,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
 }
 
 /// Degree of emphasis or de-emphasis.
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EmStrength {
     #[default]
     Light,
@@ -137,6 +299,7 @@ pub enum EmStrength {
 
 /// Whether it is an emphasis or de-emphasis.
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EmType {
     #[default]
     Emphasis,
@@ -145,14 +308,20 @@ pub enum EmType {
 
 /// Lists are fine-grained structure in a document.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct List {
     pub ltype: ListType,
     pub items: Vec<Paragraph>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListType {
     /// Each item has a distinct denotation.
     Distinct,
@@ -164,25 +333,36 @@ pub enum ListType {
 
 /// Navigation structure has a description, sub-navigation structures and links to navigate to.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Nav {
     pub description: String,
     pub subs: Vec<Nav>,
     pub links: Vec<Link>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
 /// Links are pieces of text with an accompanying URL.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Link {
     pub url: String,
     pub items: Vec<EmOrText>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
 }
 
 /// `CodeBlock` contains computer code.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct CodeBlock {
     /// Computer language in which the code is written.
     pub language: String,
@@ -190,12 +370,15 @@ pub struct CodeBlock {
     pub mode: CodeModeHint,
     /// The code.
     pub code: String,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
 }
 
 /// Behavioural hint: the block hints what to do with the code.
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CodeModeHint {
     /// Hint to show the code in the document.
     #[default] Show,
@@ -209,22 +392,33 @@ pub enum CodeModeHint {
 
 /// `Table` contains rows of paragraphs.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Table {
     pub rows: Vec<TableRow>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
 /// Tables contain rows of paragraphs
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct TableRow {
     pub items: Vec<Paragraph>,
     pub is_header: bool,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
+    pub comments: Vec<Comment>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EmOrText {
     Text(String),
     Em(Emphasis),
@@ -232,9 +426,13 @@ pub enum EmOrText {
 
 /// Text that has metadata: tags and/or properties.
 #[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct TextWithMeta {
     pub text: String,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_tags"))]
     pub tags: Tags,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serde_impls::sorted_props"))]
     pub props: Props,
 }
 
@@ -246,10 +444,27 @@ impl TextWithMeta {
 
 /// Error to signal that the code was not formatted properly.
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeIdentError;
 
+/// Comment written in the source, kept so it can be written out again.
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Comment {
+    /// Text between the comment markers.
+    pub text: String,
+    /// Whether it is a `/* */` block comment instead of a `//` line comment.
+    pub block: bool,
+    /// Number of items of the parent that come before the comment.
+    /// The heading of a section counts as its first item. Navigations of a document count before
+    /// its other items, and links of a navigation count before its sub-navigations.
+    pub position: usize,
+}
+
 /// Simple date: it is not checked if it actually exists on the calendar.
 #[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Date {
     pub year: i16,
     pub month: u8,
@@ -257,6 +472,7 @@ pub struct Date {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DateError {
     /// The year was out of range of i16.
     YearRange(i64),
@@ -265,7 +481,10 @@ pub enum DateError {
     /// The day was out of range: bigger than 31.
     DayRange(u64),
     /// There was an error parsing an integer in the date.
-    Parsing(ParseIntError),
+    Parsing(
+        #[cfg_attr(feature = "serde", serde(with = "serde_impls::parse_int_error"))]
+        ParseIntError
+    ),
 }
 
 impl Date {
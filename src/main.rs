@@ -0,0 +1,163 @@
+use incodoc::{
+    format::{ format, FormatError },
+    output::OutputOptions,
+};
+
+use std::{
+    fs,
+    io::{ self, Read, Write },
+    process::ExitCode,
+};
+
+const USAGE: &str = "\
+usage: incodoc fmt [options] [files]
+
+Format incodoc files in place.
+Without files, or with -, read from stdin and write to stdout.
+
+options:
+    --check         only check if the files are formatted, write nothing
+    --indent <n>    indent with n spaces, default 4
+    --tabs          indent with tabs
+    --compact       put short paragraphs, links and emphasis on a single line
+    --width <n>     widest line in compact mode, default 100
+    -h, --help      print this message
+";
+
+struct Args {
+    help: bool,
+    check: bool,
+    options: OutputOptions,
+    files: Vec<String>,
+}
+
+fn number(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<usize, String> {
+    args.next()
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| format!("{flag} expects a number"))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut res = Args {
+        help: false,
+        check: false,
+        options: OutputOptions::default(),
+        files: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => res.check = true,
+            "--tabs" => res.options.tabs = true,
+            "--compact" => res.options.compact = true,
+            "--indent" => res.options.indent_width = number(&mut args, "--indent")?,
+            "--width" => res.options.max_width = number(&mut args, "--width")?,
+            "-" => res.files.push(arg),
+            "-h" | "--help" => res.help = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}")),
+            _ => res.files.push(arg),
+        }
+    }
+    Ok(res)
+}
+
+/// Format one input. Returns whether it was already formatted.
+fn fmt_input(name: &str, input: &str, args: &Args) -> Result<(bool, String), String> {
+    match format(input, &args.options) {
+        Ok(output) => Ok((output == input, output)),
+        Err(FormatError::Parse(error)) => Err(format!("{name}:{error}")),
+        Err(error) => Err(format!("{name}: refusing to format: {error}")),
+    }
+}
+
+fn fmt(args: &Args) -> ExitCode {
+    let mut ok = true;
+    if args.files.is_empty() || args.files.iter().any(|file| file == "-") {
+        let mut input = String::new();
+        if let Err(error) = io::stdin().read_to_string(&mut input) {
+            eprintln!("<stdin>: {error}");
+            return ExitCode::FAILURE;
+        }
+        match fmt_input("<stdin>", &input, args) {
+            Ok((formatted, _)) if args.check => if !formatted {
+                eprintln!("<stdin> is not formatted");
+                ok = false;
+            },
+            Ok((_, output)) => if let Err(error) = io::stdout().write_all(output.as_bytes()) {
+                eprintln!("<stdout>: {error}");
+                ok = false;
+            },
+            Err(error) => {
+                eprintln!("{error}");
+                ok = false;
+            },
+        }
+    }
+    for file in args.files.iter().filter(|file| *file != "-") {
+        let input = match fs::read_to_string(file) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("{file}: {error}");
+                ok = false;
+                continue;
+            },
+        };
+        match fmt_input(file, &input, args) {
+            Ok((true, _)) => {},
+            Ok((false, _)) if args.check => {
+                eprintln!("{file} is not formatted");
+                ok = false;
+            },
+            Ok((false, output)) => if let Err(error) = fs::write(file, output) {
+                eprintln!("{file}: {error}");
+                ok = false;
+            },
+            Err(error) => {
+                eprintln!("{error}");
+                ok = false;
+            },
+        }
+    }
+    if ok { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("fmt") => match parse_args(args) {
+            Ok(args) if args.help => {
+                print!("{USAGE}");
+                ExitCode::SUCCESS
+            },
+            Ok(args) => fmt(&args),
+            Err(error) => {
+                eprintln!("{error}\n\n{USAGE}");
+                ExitCode::FAILURE
+            },
+        },
+        Some("-h" | "--help") => {
+            print!("{USAGE}");
+            ExitCode::SUCCESS
+        },
+        _ => {
+            eprint!("{USAGE}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn help() {
+        assert!(args(&["--help"]).unwrap().help);
+        assert!(args(&["a.ic", "-h", "--check"]).unwrap().help);
+        assert!(!args(&["a.ic"]).unwrap().help);
+        assert_eq!(args(&["--helps"]).err(), Some("unknown option --helps".to_string()));
+    }
+}
@@ -1,56 +1,147 @@
 use crate::*;
 
-use std::fmt::Write;
+use std::{
+    fmt::{ self, Write },
+    ops::RangeBounds,
+};
 
-fn spaces_out(spaces: usize, output: &mut String) {
-    for _ in 0..spaces {
-        output.push(' ');
+/// Options for how [`doc_out_with`] lays out a document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutputOptions {
+    /// Spaces per level of indentation, or how wide a tab counts as when indenting with tabs.
+    pub indent_width: usize,
+    /// Indent with tabs instead of spaces.
+    pub tabs: bool,
+    /// Put short paragraphs, links and emphasis on a single line.
+    pub compact: bool,
+    /// How wide a line can get when putting a node on a single line in compact mode.
+    pub max_width: usize,
+    /// Write out the comments kept in the document.
+    pub comments: bool,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            tabs: false,
+            compact: false,
+            max_width: 100,
+            comments: true,
+        }
     }
 }
 
-fn str_out(string: &str, spaces: usize, output: &mut String) {
-    spaces_out(spaces, output);
+impl OutputOptions {
+    fn indent_out(&self, depth: usize, output: &mut String) {
+        if self.tabs {
+            chars_out('\t', depth, output);
+        } else {
+            chars_out(' ', depth * self.indent_width, output);
+        }
+    }
+
+    /// Number of characters the indentation takes up.
+    fn indent_len(&self, depth: usize) -> usize {
+        if self.tabs { depth } else { depth * self.indent_width }
+    }
+
+    /// How wide the indentation looks.
+    fn indent_width(&self, depth: usize) -> usize {
+        depth * self.indent_width
+    }
+}
+
+fn chars_out(c: char, count: usize, output: &mut String) {
+    for _ in 0..count {
+        output.push(c);
+    }
+}
+
+fn str_out(string: &str, depth: usize, options: &OutputOptions, output: &mut String) {
+    options.indent_out(depth, output);
     output.push_str(string);
 }
 
-fn string_out(string: &str, spaces: usize, output: &mut String) {
-    spaces_out(spaces, output);
+fn escaped_out(string: &str, quote: char, output: &mut String) {
+    for c in string.chars() {
+        if c == quote || c == '\\' {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+}
+
+fn string_out(string: &str, depth: usize, options: &OutputOptions, output: &mut String) {
+    options.indent_out(depth, output);
     output.push('"');
-    output.push_str(string);
+    escaped_out(string, '"', output);
     output.push('"');
 }
 
-fn text_out(string: &str, spaces: usize, output: &mut String) {
-    spaces_out(spaces, output);
+fn text_out(string: &str, depth: usize, options: &OutputOptions, output: &mut String) {
+    options.indent_out(depth, output);
     output.push('\'');
-    output.push_str(string);
+    escaped_out(string, '\'', output);
     output.push('\'');
 }
 
-fn text_item_out(text: &str, spaces: usize, output: &mut String) {
-    text_out(text, spaces, output);
+fn text_item_out(text: &str, depth: usize, options: &OutputOptions, output: &mut String) {
+    text_out(text, depth, options, output);
     output.push_str(",\n");
 }
 
-fn code_text_out(code: &str, spaces: usize, output: &mut String) {
-    str_out("'\n", spaces, output);
-    spaces_out(spaces, output);
-    for c in code.chars() {
-        output.push(c);
-        if c == '\n' {
-            spaces_out(spaces, output);
+fn code_text_out(code: &str, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("'\n", depth, options, output);
+    // the parser expects the code and the closing quote to line up with the opening quote,
+    // using spaces only, empty lines are left empty so no line ends in whitespace
+    let spaces = options.indent_len(depth);
+    for line in code.split('\n') {
+        if !line.is_empty() {
+            chars_out(' ', spaces, output);
         }
+        escaped_out(line, '\'', output);
+        output.push('\n');
     }
-    output.push('\n');
-    str_out("',\n", spaces, output);
+    chars_out(' ', spaces, output);
+    output.push_str("',\n");
+}
+
+/// Write the comments that were written at the positions in the range.
+fn comments_out(
+    comments: &[Comment], positions: impl RangeBounds<usize>, depth: usize,
+    options: &OutputOptions, output: &mut String,
+) {
+    if !options.comments { return; }
+    for comment in comments.iter().filter(|comment| positions.contains(&comment.position)) {
+        options.indent_out(depth, output);
+        if comment.block {
+            output.push_str("/*");
+            output.push_str(&comment.text);
+            output.push_str("*/\n");
+        } else {
+            output.push_str("//");
+            output.push_str(&comment.text);
+            output.push('\n');
+        }
+    }
+}
+
+/// Whether any of the meta gets written out, props that are errors are not.
+fn has_meta(tags: &Tags, props: &Props) -> bool {
+    !tags.is_empty() || !props.values().all(PropVal::is_error)
 }
 
-fn mtext_out(mtext: &TextWithMeta, spaces: usize, output: &mut String) {
-    text_out(&mtext.text, spaces, output);
+fn mtext_out(mtext: &TextWithMeta, depth: usize, options: &OutputOptions, output: &mut String) {
+    // the text meta can't be empty
+    if !has_meta(&mtext.tags, &mtext.props) {
+        return text_item_out(&mtext.text, depth, options, output);
+    }
+    text_out(&mtext.text, depth, options, output);
     output.push_str(" {\n");
-    tags_out(&mtext.tags, spaces + 4, output);
-    props_out(&mtext.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    tags_out(&mtext.tags, depth + 1, options, output);
+    props_out(&mtext.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
 fn date_out(date: Date, output: &mut String) {
@@ -61,68 +152,155 @@ fn date_out(date: Date, output: &mut String) {
     let _ = write!(output, "{:0>2}", date.day);
 }
 
-fn tags_out(tags: &Tags, spaces: usize, output: &mut String) {
+pub(crate) fn sorted_tags(tags: &Tags) -> Vec<&String> {
+    // sorted so that equal documents always give the same output
+    let mut tags: Vec<_> = tags.iter().collect();
+    tags.sort();
+    tags
+}
+
+pub(crate) fn sorted_props(props: &Props) -> Vec<(&String, &PropVal)> {
+    let mut props: Vec<_> = props.iter()
+        .filter(|(_, v)| !matches!(v, PropVal::Error(_)))
+        .collect();
+    props.sort_by_key(|(k, _)| *k);
+    props
+}
+
+fn tags_out(tags: &Tags, depth: usize, options: &OutputOptions, output: &mut String) {
     if tags.is_empty() { return; }
-    str_out("tags {\n", spaces, output);
-    for tag in tags {
-        string_out(tag, spaces + 4, output);
+    str_out("tags {\n", depth, options, output);
+    for tag in sorted_tags(tags) {
+        string_out(tag, depth + 1, options, output);
         output.push_str(",\n");
     }
-    str_out("},\n", spaces, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn props_out(props: &Props, spaces: usize, output: &mut String) {
-    if props.is_empty() { return; }
-    str_out("props {\n", spaces, output);
-    for kv in props {
-        let not_err = kv_out(kv, spaces + 4, output);
-        if not_err { output.push_str(",\n"); }
+fn props_out(props: &Props, depth: usize, options: &OutputOptions, output: &mut String) {
+    if props.values().all(PropVal::is_error) { return; }
+    str_out("props {\n", depth, options, output);
+    for kv in sorted_props(props) {
+        options.indent_out(depth + 1, output);
+        kv_out(kv, output);
+        output.push_str(",\n");
     }
-    str_out("},\n", spaces, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn kv_out((k, v): (&String, &PropVal), spaces: usize, output: &mut String) -> bool {
-    if matches!(v, PropVal::Error(_)) {
-        return false;
-    }
-    str_out("(", spaces, output);
-    string_out(k, 0, output);
+fn kv_out((k, v): (&String, &PropVal), output: &mut String) {
+    let options = &OutputOptions::default();
+    output.push('(');
+    string_out(k, 0, options, output);
     output.push_str(", ");
     match v {
-        PropVal::String(string) => string_out(string, 0, output),
-        PropVal::Text(text) => text_out(text, 0, output),
+        PropVal::String(string) => string_out(string, 0, options, output),
+        PropVal::Text(text) => text_out(text, 0, options, output),
         PropVal::Int(int) => output.push_str(&int.to_string()),
         PropVal::Date(date) => date_out(*date, output),
         PropVal::Error(_) => (),
     }
     output.push(')');
+}
+
+/// Put a node on a single line when compact mode is on and the line is short enough.
+/// The inline output returns false when the node can not be put on a single line.
+fn compact_out(
+    depth: usize, options: &OutputOptions, output: &mut String,
+    inline_out: impl FnOnce(&mut String) -> bool,
+) -> bool {
+    if !options.compact { return false; }
+    let mut line = String::new();
+    if !inline_out(&mut line)
+        || line.contains('\n')
+        || options.indent_width(depth) + line.chars().count() + 1 > options.max_width
+    {
+        return false;
+    }
+    str_out(&line, depth, options, output);
+    output.push_str(",\n");
     true
 }
 
-fn emphasis_out(em: &Emphasis, spaces: usize, output: &mut String) {
-    str_out("em {\n", spaces, output);
-    let strength = match em.strength {
-        EmStrength::Light => "l",
-        EmStrength::Medium => "m",
-        EmStrength::Strong => "s",
-    };
-    let etype = match em.etype {
-        EmType::Emphasis => 'e',
-        EmType::Deemphasis => 'd',
-    };
-    str_out(strength, spaces + 4, output);
-    output.push(etype);
+fn meta_inline_out(tags: &Tags, props: &Props, output: &mut String) {
+    let options = &OutputOptions::default();
+    if !tags.is_empty() {
+        output.push_str(", tags {");
+        for (i, tag) in sorted_tags(tags).into_iter().enumerate() {
+            output.push_str(if i == 0 { " " } else { ", " });
+            string_out(tag, 0, options, output);
+        }
+        output.push_str(" }");
+    }
+    let props = sorted_props(props);
+    if !props.is_empty() {
+        output.push_str(", props {");
+        for (i, kv) in props.into_iter().enumerate() {
+            output.push_str(if i == 0 { " " } else { ", " });
+            kv_out(kv, output);
+        }
+        output.push_str(" }");
+    }
+}
+
+fn em_type_str(em: &Emphasis) -> &'static str {
+    match (em.strength, em.etype) {
+        (EmStrength::Light, EmType::Emphasis) => "le",
+        (EmStrength::Medium, EmType::Emphasis) => "me",
+        (EmStrength::Strong, EmType::Emphasis) => "se",
+        (EmStrength::Light, EmType::Deemphasis) => "ld",
+        (EmStrength::Medium, EmType::Deemphasis) => "md",
+        (EmStrength::Strong, EmType::Deemphasis) => "sd",
+    }
+}
+
+fn emphasis_inline_out(em: &Emphasis, output: &mut String) -> bool {
+    output.push_str("em { ");
+    output.push_str(em_type_str(em));
+    output.push_str(", ");
+    string_out(&em.text, 0, &OutputOptions::default(), output);
+    meta_inline_out(&em.tags, &em.props, output);
+    output.push_str(" }");
+    true
+}
+
+fn emphasis_out(em: &Emphasis, depth: usize, options: &OutputOptions, output: &mut String) {
+    if compact_out(depth, options, output, |line| emphasis_inline_out(em, line)) { return; }
+    str_out("em {\n", depth, options, output);
+    str_out(em_type_str(em), depth + 1, options, output);
+    output.push_str(",\n");
+    string_out(&em.text, depth + 1, options, output);
     output.push_str(",\n");
-    string_out(&em.text, spaces + 4, output);
+    tags_out(&em.tags, depth + 1, options, output);
+    props_out(&em.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn footnote_ref_inline_out(footnote_ref: &FootnoteRef, output: &mut String) -> bool {
+    output.push_str("fnref { ");
+    string_out(&footnote_ref.label, 0, &OutputOptions::default(), output);
+    meta_inline_out(&footnote_ref.tags, &footnote_ref.props, output);
+    output.push_str(" }");
+    true
+}
+
+fn footnote_ref_out(
+    footnote_ref: &FootnoteRef, depth: usize, options: &OutputOptions, output: &mut String,
+) {
+    if compact_out(depth, options, output, |line| footnote_ref_inline_out(footnote_ref, line)) {
+        return;
+    }
+    str_out("fnref {\n", depth, options, output);
+    string_out(&footnote_ref.label, depth + 1, options, output);
     output.push_str(",\n");
-    tags_out(&em.tags, spaces + 4, output);
-    props_out(&em.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    tags_out(&footnote_ref.tags, depth + 1, options, output);
+    props_out(&footnote_ref.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn code_out(code: &CodeBlock, spaces: usize, output: &mut String) {
-    str_out("code {\n", spaces, output);
-    string_out(&code.language, spaces + 4, output);
+fn code_out(code: &CodeBlock, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("code {\n", depth, options, output);
+    string_out(&code.language, depth + 1, options, output);
     output.push_str(",\n");
     let mode = match code.mode {
         CodeModeHint::Show => "show",
@@ -130,164 +308,421 @@ fn code_out(code: &CodeBlock, spaces: usize, output: &mut String) {
         CodeModeHint::Run => "run",
         CodeModeHint::Replace => "replace",
     };
-    string_out(mode, spaces + 4, output);
+    string_out(mode, depth + 1, options, output);
     output.push_str(",\n");
-    code_text_out(&code.code, spaces + 4, output);
-    tags_out(&code.tags, spaces + 4, output);
-    props_out(&code.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    code_text_out(&code.code, depth + 1, options, output);
+    tags_out(&code.tags, depth + 1, options, output);
+    props_out(&code.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn link_out(link: &Link, spaces: usize, output: &mut String) {
-    str_out("link {\n", spaces, output);
-    string_out(&link.url, spaces + 4, output);
-    output.push_str(",\n");
-    for item in &link.items {
+fn em_or_text_inline_out(items: &[EmOrText], output: &mut String) {
+    for item in items {
+        output.push_str(", ");
+        match item {
+            EmOrText::Text(string) => string_out(string, 0, &OutputOptions::default(), output),
+            EmOrText::Em(em) => { emphasis_inline_out(em, output); },
+        }
+    }
+}
+
+fn em_or_text_out(items: &[EmOrText], depth: usize, options: &OutputOptions, output: &mut String) {
+    for item in items {
         match item {
             EmOrText::Text(string) => {
-                string_out(string, spaces + 4, output);
+                string_out(string, depth, options, output);
                 output.push_str(",\n");
             },
             EmOrText::Em(em) => {
-                emphasis_out(em, spaces + 4, output);
+                emphasis_out(em, depth, options, output);
             },
         }
     }
-    tags_out(&link.tags, spaces + 4, output);
-    props_out(&link.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
 }
 
-fn heading_out(head: &Heading, spaces: usize, plevel: usize, output: &mut String) -> usize {
-    str_out("head {\n", spaces, output);
-    str_out(&(head.level as usize - plevel).to_string(), spaces + 4, output);
+fn link_inline_out(link: &Link, output: &mut String) -> bool {
+    output.push_str("link { ");
+    string_out(&link.url, 0, &OutputOptions::default(), output);
+    em_or_text_inline_out(&link.items, output);
+    meta_inline_out(&link.tags, &link.props, output);
+    output.push_str(" }");
+    true
+}
+
+fn link_out(link: &Link, depth: usize, options: &OutputOptions, output: &mut String) {
+    if compact_out(depth, options, output, |line| link_inline_out(link, line)) { return; }
+    str_out("link {\n", depth, options, output);
+    string_out(&link.url, depth + 1, options, output);
     output.push_str(",\n");
-    for item in &head.items {
+    em_or_text_out(&link.items, depth + 1, options, output);
+    tags_out(&link.tags, depth + 1, options, output);
+    props_out(&link.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn attribution_inline_out(attribution: &[EmOrText], output: &mut String) -> bool {
+    let mut items = String::new();
+    em_or_text_inline_out(attribution, &mut items);
+    output.push_str("by { ");
+    output.push_str(items.strip_prefix(", ").unwrap_or(&items));
+    output.push_str(" }");
+    true
+}
+
+fn attribution_out(
+    attribution: &[EmOrText], depth: usize, options: &OutputOptions, output: &mut String,
+) {
+    if attribution.is_empty() { return; }
+    if compact_out(depth, options, output, |line| attribution_inline_out(attribution, line)) {
+        return;
+    }
+    str_out("by {\n", depth, options, output);
+    em_or_text_out(attribution, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn inline_quote_inline_out(quote: &InlineQuote, output: &mut String) -> bool {
+    let options = &OutputOptions::default();
+    let mut parts = Vec::new();
+    if let Some(kind) = &quote.kind {
+        let mut part = String::new();
+        string_out(kind, 0, options, &mut part);
+        parts.push(part);
+    }
+    for item in &quote.items {
+        let mut part = String::new();
         match item {
-            EmOrText::Text(string) => {
-                string_out(string, spaces + 4, output);
-                output.push_str(",\n");
-            },
-            EmOrText::Em(em) => {
-                emphasis_out(em, spaces + 4, output);
-            },
+            EmOrText::Text(text) => text_out(text, 0, options, &mut part),
+            EmOrText::Em(em) => { emphasis_inline_out(em, &mut part); },
         }
+        parts.push(part);
     }
-    tags_out(&head.tags, spaces + 4, output);
-    props_out(&head.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
-    head.level as usize
+    if !quote.attribution.is_empty() {
+        let mut part = String::new();
+        attribution_inline_out(&quote.attribution, &mut part);
+        parts.push(part);
+    }
+    if let Some(source) = &quote.source {
+        let mut part = String::new();
+        link_inline_out(source, &mut part);
+        parts.push(part);
+    }
+    output.push_str("quote { ");
+    output.push_str(&parts.join(", "));
+    meta_inline_out(&quote.tags, &quote.props, output);
+    output.push_str(" }");
+    true
 }
 
-fn nav_out(nav: &Nav, spaces: usize, output: &mut String) {
-    str_out("nav {\n", spaces, output);
-    if spaces > 0 {
-        string_out(&nav.description, spaces + 4, output);
+fn inline_quote_out(
+    quote: &InlineQuote, depth: usize, options: &OutputOptions, output: &mut String,
+) {
+    if compact_out(depth, options, output, |line| inline_quote_inline_out(quote, line)) { return; }
+    str_out("quote {\n", depth, options, output);
+    if let Some(kind) = &quote.kind {
+        string_out(kind, depth + 1, options, output);
         output.push_str(",\n");
     }
-    for link in &nav.links {
-        link_out(link, spaces + 4, output);
+    for item in &quote.items {
+        match item {
+            EmOrText::Text(text) => text_item_out(text, depth + 1, options, output),
+            EmOrText::Em(em) => emphasis_out(em, depth + 1, options, output),
+        }
     }
-    for sub in &nav.subs {
-        nav_out(sub, spaces + 4, output);
+    attribution_out(&quote.attribution, depth + 1, options, output);
+    if let Some(source) = &quote.source {
+        link_out(source, depth + 1, options, output);
     }
-    tags_out(&nav.tags, spaces, output);
-    props_out(&nav.props, spaces, output);
-    str_out("},\n", spaces, output);
+    tags_out(&quote.tags, depth + 1, options, output);
+    props_out(&quote.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn par_items_out(items: &[ParagraphItem], spaces: usize, output: &mut String) {
-    for item in items {
+fn quote_out(quote: &Quote, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("quote {\n", depth, options, output);
+    if let Some(kind) = &quote.kind {
+        string_out(kind, depth + 1, options, output);
+        output.push_str(",\n");
+    }
+    for (i, item) in quote.items.iter().enumerate() {
+        comments_out(&quote.comments, i..=i, depth + 1, options, output);
         match item {
-            ParagraphItem::Text(text) => text_item_out(text, spaces, output),
-            ParagraphItem::MText(mtext) => mtext_out(mtext, spaces, output),
-            ParagraphItem::Em(em) => emphasis_out(em, spaces, output),
-            ParagraphItem::Link(link) => link_out(link, spaces, output),
-            ParagraphItem::Code(Ok(code)) => code_out(code, spaces, output),
-            ParagraphItem::List(list) => list_out(list, spaces, output),
-            ParagraphItem::Table(table) => table_out(table, spaces, output),
-            ParagraphItem::Code(_) => { },
+            QuoteItem::Paragraph(par) => paragraph_out(par, depth + 1, options, output),
+            QuoteItem::Quote(quote) => quote_out(quote, depth + 1, options, output),
         }
     }
+    comments_out(&quote.comments, quote.items.len().., depth + 1, options, output);
+    attribution_out(&quote.attribution, depth + 1, options, output);
+    if let Some(source) = &quote.source {
+        link_out(source, depth + 1, options, output);
+    }
+    tags_out(&quote.tags, depth + 1, options, output);
+    props_out(&quote.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn footnote_out(
+    footnote: &FootnoteDef, depth: usize, options: &OutputOptions, output: &mut String,
+) {
+    str_out("footnote {\n", depth, options, output);
+    string_out(&footnote.label, depth + 1, options, output);
+    output.push_str(",\n");
+    for (i, par) in footnote.items.iter().enumerate() {
+        comments_out(&footnote.comments, i..=i, depth + 1, options, output);
+        paragraph_out(par, depth + 1, options, output);
+    }
+    comments_out(&footnote.comments, footnote.items.len().., depth + 1, options, output);
+    tags_out(&footnote.tags, depth + 1, options, output);
+    props_out(&footnote.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn heading_out(
+    head: &Heading, depth: usize, plevel: usize, options: &OutputOptions, output: &mut String,
+) -> usize {
+    str_out("head {\n", depth, options, output);
+    str_out(&(head.level as usize - plevel).to_string(), depth + 1, options, output);
+    output.push_str(",\n");
+    em_or_text_out(&head.items, depth + 1, options, output);
+    tags_out(&head.tags, depth + 1, options, output);
+    props_out(&head.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+    head.level as usize
+}
+
+fn nav_out(nav: &Nav, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("nav {\n", depth, options, output);
+    if depth > 0 {
+        string_out(&nav.description, depth + 1, options, output);
+        output.push_str(",\n");
+    }
+    for (i, link) in nav.links.iter().enumerate() {
+        comments_out(&nav.comments, i..=i, depth + 1, options, output);
+        link_out(link, depth + 1, options, output);
+    }
+    for (i, sub) in nav.subs.iter().enumerate() {
+        let i = nav.links.len() + i;
+        comments_out(&nav.comments, i..=i, depth + 1, options, output);
+        nav_out(sub, depth + 1, options, output);
+    }
+    comments_out(&nav.comments, nav.links.len() + nav.subs.len().., depth + 1, options, output);
+    tags_out(&nav.tags, depth, options, output);
+    props_out(&nav.props, depth, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn list_out(list: &List, spaces: usize, output: &mut String) {
-    str_out("list {\n", spaces, output);
-    spaces_out(spaces + 4, output);
+fn par_item_out(item: &ParagraphItem, depth: usize, options: &OutputOptions, output: &mut String) {
+    match item {
+        ParagraphItem::Text(text) => text_item_out(text, depth, options, output),
+        ParagraphItem::MText(mtext) => mtext_out(mtext, depth, options, output),
+        ParagraphItem::Em(em) => emphasis_out(em, depth, options, output),
+        ParagraphItem::Link(link) => link_out(link, depth, options, output),
+        ParagraphItem::Code(Ok(code)) => code_out(code, depth, options, output),
+        ParagraphItem::List(list) => list_out(list, depth, options, output),
+        ParagraphItem::Table(table) => table_out(table, depth, options, output),
+        ParagraphItem::Quote(quote) => inline_quote_out(quote, depth, options, output),
+        ParagraphItem::FootnoteRef(footnote_ref) => {
+            footnote_ref_out(footnote_ref, depth, options, output)
+        },
+        ParagraphItem::Data(data) => data_out(data, depth, options, output),
+        ParagraphItem::Code(_) => { },
+    }
+}
+
+fn list_out(list: &List, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("list {\n", depth, options, output);
     let ltype = match list.ltype {
         ListType::Distinct => "dl",
         ListType::Identical => "il",
         ListType::Checked => "cl",
     };
-    output.push_str(ltype);
+    str_out(ltype, depth + 1, options, output);
     output.push_str(",\n");
-    for par in &list.items {
-        paragraph_out(par, spaces + 4, output);
+    for (i, par) in list.items.iter().enumerate() {
+        comments_out(&list.comments, i..=i, depth + 1, options, output);
+        paragraph_out(par, depth + 1, options, output);
     }
-    tags_out(&list.tags, spaces + 4, output);
-    props_out(&list.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    comments_out(&list.comments, list.items.len().., depth + 1, options, output);
+    tags_out(&list.tags, depth + 1, options, output);
+    props_out(&list.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn table_row_out(row: &TableRow, spaces: usize, output: &mut String) {
+fn table_row_out(row: &TableRow, depth: usize, options: &OutputOptions, output: &mut String) {
     if row.is_header {
-        str_out("throw {\n", spaces, output);
+        str_out("throw {\n", depth, options, output);
     } else {
-        str_out("trow {\n", spaces, output);
+        str_out("trow {\n", depth, options, output);
     }
-    for par in &row.items {
-        paragraph_out(par, spaces + 4, output);
+    for (i, par) in row.items.iter().enumerate() {
+        comments_out(&row.comments, i..=i, depth + 1, options, output);
+        paragraph_out(par, depth + 1, options, output);
     }
-    tags_out(&row.tags, spaces + 4, output);
-    props_out(&row.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    comments_out(&row.comments, row.items.len().., depth + 1, options, output);
+    tags_out(&row.tags, depth + 1, options, output);
+    props_out(&row.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn table_out(table: &Table, spaces: usize, output: &mut String) {
-    str_out("table {\n", spaces, output);
-    for row in &table.rows {
-        table_row_out(row, spaces + 4, output);
+fn table_out(table: &Table, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("table {\n", depth, options, output);
+    for (i, row) in table.rows.iter().enumerate() {
+        comments_out(&table.comments, i..=i, depth + 1, options, output);
+        table_row_out(row, depth + 1, options, output);
     }
-    tags_out(&table.tags, spaces + 4, output);
-    props_out(&table.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    comments_out(&table.comments, table.rows.len().., depth + 1, options, output);
+    tags_out(&table.tags, depth + 1, options, output);
+    props_out(&table.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn paragraph_out(par: &Paragraph, spaces: usize, output: &mut String) {
-    str_out("par {\n", spaces, output);
-    par_items_out(&par.items, spaces + 4, output);
-    tags_out(&par.tags, spaces + 4, output);
-    props_out(&par.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+fn data_out(data: &Data, depth: usize, options: &OutputOptions, output: &mut String) {
+    str_out("data {\n", depth, options, output);
+    // a dataset needs at least one item to be read back
+    if !data.columns.is_empty() || data.rows.is_empty() {
+        str_out("cols {", depth + 1, options, output);
+        for (i, column) in data.columns.iter().enumerate() {
+            output.push_str(if i > 0 { ", " } else { " " });
+            string_out(column, 0, options, output);
+        }
+        output.push_str(" },\n");
+    }
+    for (i, row) in data.rows.iter().enumerate() {
+        comments_out(&data.comments, i..=i, depth + 1, options, output);
+        str_out("[", depth + 1, options, output);
+        for (j, cell) in row.iter().enumerate() {
+            if j > 0 {
+                output.push_str(", ");
+            }
+            match cell {
+                DataCell::Text(text) => text_out(text, 0, options, output),
+                DataCell::Int(int) => output.push_str(&int.to_string()),
+                // debug formatting always reads back as a float
+                DataCell::Float(float) => output.push_str(&format!("{float:?}")),
+                DataCell::Bool(bool) => output.push_str(&bool.to_string()),
+            }
+        }
+        output.push_str("],\n");
+    }
+    comments_out(&data.comments, data.rows.len().., depth + 1, options, output);
+    tags_out(&data.tags, depth + 1, options, output);
+    props_out(&data.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
 }
 
-fn section_out(section: &Section, spaces: usize, plevel: usize, output: &mut String) {
-    str_out("section {\n", spaces, output);
-    let plevel = 1 + heading_out(&section.heading, spaces + 4, plevel, output);
-    for item in &section.items {
+fn paragraph_inline_out(par: &Paragraph, output: &mut String) -> bool {
+    if par.items.is_empty() || !par.comments.is_empty() { return false; }
+    let options = &OutputOptions::default();
+    output.push_str("par { ");
+    for (i, item) in par.items.iter().enumerate() {
+        if i > 0 {
+            output.push_str(", ");
+        }
         match item {
-            SectionItem::Paragraph(par) => paragraph_out(par, spaces + 4, output),
-            SectionItem::Section(section) => section_out(section, spaces + 4, plevel, output),
+            ParagraphItem::Text(text) => text_out(text, 0, options, output),
+            ParagraphItem::MText(mtext) if !has_meta(&mtext.tags, &mtext.props) => {
+                text_out(&mtext.text, 0, options, output);
+            },
+            ParagraphItem::MText(mtext) => {
+                text_out(&mtext.text, 0, options, output);
+                output.push_str(" {");
+                let mut meta = String::new();
+                meta_inline_out(&mtext.tags, &mtext.props, &mut meta);
+                output.push_str(meta.strip_prefix(',').unwrap_or(&meta));
+                output.push_str(" }");
+            },
+            ParagraphItem::Em(em) => { emphasis_inline_out(em, output); },
+            ParagraphItem::Link(link) => { link_inline_out(link, output); },
+            ParagraphItem::Quote(quote) => { inline_quote_inline_out(quote, output); },
+            ParagraphItem::FootnoteRef(footnote_ref) => {
+                footnote_ref_inline_out(footnote_ref, output);
+            },
+            _ => return false,
         }
     }
-    tags_out(&section.tags, spaces + 4, output);
-    props_out(&section.props, spaces + 4, output);
-    str_out("},\n", spaces, output);
+    meta_inline_out(&par.tags, &par.props, output);
+    output.push_str(" }");
+    true
 }
 
-/// Unparse: take abstract documents structure and produce a string that is an incodoc.
-/// Any output of this should be able to be parsed by this crate.
-pub fn doc_out(doc: &Doc, output: &mut String) {
-    tags_out(&doc.tags, 0, output);
-    props_out(&doc.props, 0, output);
-    for nav in &doc.navs {
-        nav_out(nav, 0, output);
+fn paragraph_out(par: &Paragraph, depth: usize, options: &OutputOptions, output: &mut String) {
+    if compact_out(depth, options, output, |line| paragraph_inline_out(par, line)) { return; }
+    str_out("par {\n", depth, options, output);
+    for (i, item) in par.items.iter().enumerate() {
+        comments_out(&par.comments, i..=i, depth + 1, options, output);
+        par_item_out(item, depth + 1, options, output);
+    }
+    comments_out(&par.comments, par.items.len().., depth + 1, options, output);
+    tags_out(&par.tags, depth + 1, options, output);
+    props_out(&par.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn section_out(
+    section: &Section, depth: usize, plevel: usize, options: &OutputOptions, output: &mut String,
+) {
+    str_out("section {\n", depth, options, output);
+    comments_out(&section.comments, 0..=0, depth + 1, options, output);
+    let plevel = 1 + heading_out(&section.heading, depth + 1, plevel, options, output);
+    for (i, item) in section.items.iter().enumerate() {
+        // the heading comes first
+        let i = i + 1;
+        comments_out(&section.comments, i..=i, depth + 1, options, output);
+        match item {
+            SectionItem::Paragraph(par) => paragraph_out(par, depth + 1, options, output),
+            SectionItem::Section(section) => {
+                section_out(section, depth + 1, plevel, options, output)
+            },
+            SectionItem::Quote(quote) => quote_out(quote, depth + 1, options, output),
+            SectionItem::Footnote(footnote) => footnote_out(footnote, depth + 1, options, output),
+        }
+    }
+    comments_out(&section.comments, section.items.len() + 1.., depth + 1, options, output);
+    tags_out(&section.tags, depth + 1, options, output);
+    props_out(&section.props, depth + 1, options, output);
+    str_out("},\n", depth, options, output);
+}
+
+fn doc_items_out(doc: &Doc, options: &OutputOptions, output: &mut String) {
+    // comments at the start of the document stay above everything else
+    comments_out(&doc.comments, 0..=0, 0, options, output);
+    tags_out(&doc.tags, 0, options, output);
+    props_out(&doc.props, 0, options, output);
+    for (i, nav) in doc.navs.iter().enumerate() {
+        if i > 0 {
+            comments_out(&doc.comments, i..=i, 0, options, output);
+        }
+        nav_out(nav, 0, options, output);
     }
-    for item in &doc.items {
+    for (i, item) in doc.items.iter().enumerate() {
+        let i = doc.navs.len() + i;
+        if i > 0 {
+            comments_out(&doc.comments, i..=i, 0, options, output);
+        }
         match item {
-            DocItem::Paragraph(par) => paragraph_out(par, 0, output),
-            DocItem::Section(section) => section_out(section, 0, 0, output),
+            DocItem::Paragraph(par) => paragraph_out(par, 0, options, output),
+            DocItem::Section(section) => section_out(section, 0, 0, options, output),
+            DocItem::Quote(quote) => quote_out(quote, 0, options, output),
+            DocItem::Footnote(footnote) => footnote_out(footnote, 0, options, output),
         }
     }
+    let len = doc.navs.len() + doc.items.len();
+    comments_out(&doc.comments, len.max(1).., 0, options, output);
 }
 
+/// Unparse: take abstract documents structure and produce a string that is an incodoc.
+/// Any output of this should be able to be parsed by this crate.
+/// The output is canonical: tags and props are sorted and the layout puts no whitespace at the end
+/// of a line, so equal documents give identical output.
+pub fn doc_out(doc: &Doc, output: &mut String) {
+    doc_items_out(doc, &OutputOptions::default(), output);
+}
+
+/// Unparse like [`doc_out`], laid out according to the options.
+pub fn doc_out_with(
+    doc: &Doc, options: &OutputOptions, output: &mut impl fmt::Write,
+) -> fmt::Result {
+    let mut res = String::new();
+    doc_items_out(doc, options, &mut res);
+    output.write_str(&res)
+}
@@ -1,13 +1,21 @@
-use crate::actions::absorb::Absorb;
+use crate::actions::{
+    absorb::Absorb,
+    migrate::{ migrate_quotes_doc, migrate_footnotes_doc },
+};
 
 use std::{
+    fmt,
+    sync::Once,
     num::ParseIntError,
     collections::{ HashMap, HashSet },
 };
 
 use pest::{
     Parser,
-    iterators::Pair,
+    Span as PestSpan,
+    Position as PestPosition,
+    iterators::{ Pair, Pairs },
+    error::{ Error as PestError, ErrorVariant, InputLocation, LineColLocation },
 };
 use pest_derive::Parser;
 
@@ -17,60 +25,538 @@ use crate::*;
 #[grammar = "parse/incodoc.pest"]
 pub struct IncodocParser;
 
-pub fn parse(input: &str) -> Result<Doc, String> {
-    let mut doc = Doc::default();
-    let pairs = match IncodocParser::parse(Rule::top, input) {
-        Ok(res) => res,
-        Err(e) => return Err(e.to_string()),
+/// Location in the input.
+#[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Location {
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub col: usize,
+}
+
+/// Range in the input, from start up to end.
+#[derive(Clone, Copy, Default, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    fn from_pest(span: &PestSpan<'_>) -> Self {
+        let (sl, sc) = span.start_pos().line_col();
+        let (el, ec) = span.end_pos().line_col();
+        Self {
+            start: Location { offset: span.start(), line: sl, col: sc },
+            end: Location { offset: span.end(), line: el, col: ec },
+        }
+    }
+}
+
+/// Error when the input could not be parsed into a document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+    /// Where it went wrong.
+    pub span: Span,
+    /// The line of the input on which the error starts.
+    pub snippet: String,
+}
+
+/// Kinds of errors the parser can run into.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// The input does not follow the grammar.
+    Syntax {
+        /// Rules that would have been accepted.
+        expected: Vec<Rule>,
+        /// Literal tokens that would have been accepted.
+        expected_tokens: Vec<String>,
+    },
+    /// The grammar rejected the input with a message.
+    Message(String),
+    /// A rule showed up where it can not be handled.
+    IllegalRule {
+        context: &'static str,
+        rule: Rule,
+    },
+    /// A part that should always be present is missing.
+    MissingPart {
+        context: &'static str,
+        part: &'static str,
+    },
+    /// A part that may show up at most once showed up again.
+    DuplicatePart {
+        context: &'static str,
+        part: &'static str,
+    },
+    /// A value was not one of the allowed values.
+    IllegalValue {
+        context: &'static str,
+        value: String,
+    },
+    /// An integer could not be parsed.
+    Int(ParseIntError),
+}
+
+impl ParseError {
+    fn from_pest(error: PestError<Rule>, input: &str) -> Self {
+        let mut kind = match &error.variant {
+            ErrorVariant::ParsingError { positives, .. } => ParseErrorKind::Syntax {
+                expected: positives.clone(),
+                expected_tokens: Vec::new(),
+            },
+            ErrorVariant::CustomError { message } => ParseErrorKind::Message(message.clone()),
+        };
+        let (start, _) = match error.location {
+            InputLocation::Pos(pos) => (pos, pos),
+            InputLocation::Span(span) => span,
+        };
+        // pest only reports the furthest rule it tried, the attempts also know about the literals
+        // that got furthest: like a missing comma
+        if let Some(attempts) = error.parse_attempts()
+            && attempts.max_position > start
+            && let Some(pos) = PestPosition::new(input, attempts.max_position)
+        {
+            let mut expected = Vec::new();
+            for stack in attempts.call_stacks() {
+                if let Some(rule) = stack.deepest.get_rule().or(stack.parent.as_ref())
+                    && !expected.contains(rule)
+                {
+                    expected.push(*rule);
+                }
+            }
+            kind = ParseErrorKind::Syntax {
+                expected,
+                expected_tokens: attempts.expected_tokens().iter()
+                    .map(|token| token.to_string())
+                    // skipping whitespace and comments is always possible
+                    .filter(|token| !matches!(token.as_str(), "BUILTIN_RULE" | "//" | "/*"))
+                    .collect(),
+            };
+            return Self::at_span(&pos.span(&pos), kind);
+        }
+        let ((sl, sc), (el, ec)) = match error.line_col {
+            LineColLocation::Pos(pos) => (pos, pos),
+            LineColLocation::Span(start, end) => (start, end),
+        };
+        let end = match error.location {
+            InputLocation::Pos(pos) => pos,
+            InputLocation::Span((_, end)) => end,
+        };
+        Self {
+            kind,
+            span: Span {
+                start: Location { offset: start, line: sl, col: sc },
+                end: Location { offset: end, line: el, col: ec },
+            },
+            snippet: error.line().to_string(),
+        }
+    }
+
+    fn at(pair: &Pair<'_, Rule>, kind: ParseErrorKind) -> Self {
+        Self::at_span(&pair.as_span(), kind)
+    }
+
+    fn at_span(span: &PestSpan<'_>, kind: ParseErrorKind) -> Self {
+        Self {
+            kind,
+            span: Span::from_pest(span),
+            snippet: span.start_pos().line_of().trim_end_matches(['\n', '\r']).to_string(),
+        }
+    }
+
+    fn illegal_rule(context: &'static str, pair: &Pair<'_, Rule>) -> Self {
+        Self::at(pair, ParseErrorKind::IllegalRule { context, rule: pair.as_rule() })
+    }
+
+    /// Move the error forward, from a part of an input to the whole of it.
+    /// Only the byte offsets change, `relocate` sets the rest.
+    fn shift(mut self, by: usize) -> Self {
+        self.span.start.offset += by;
+        self.span.end.offset += by;
+        self
+    }
+
+    /// Point the error into another input with the same byte offsets.
+    fn relocate(self, input: &str) -> Self {
+        match (
+            PestPosition::new(input, self.span.start.offset),
+            PestPosition::new(input, self.span.end.offset),
+        ) {
+            (Some(start), Some(end)) if start <= end => Self::at_span(&start.span(&end), self.kind),
+            _ => self,
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { expected_tokens, .. } if !expected_tokens.is_empty() => {
+                write!(f, "expected one of: ")?;
+                for (i, token) in expected_tokens.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{token}`")?;
+                }
+                Ok(())
+            },
+            Self::Syntax { expected, .. } if !expected.is_empty() => {
+                write!(f, "expected {expected:?}")
+            },
+            Self::Syntax { .. } => write!(f, "unknown syntax error"),
+            Self::Message(message) => write!(f, "{message}"),
+            Self::IllegalRule { context, rule } => write!(f, "{context}: illegal rule {rule:?}"),
+            Self::MissingPart { context, part } => write!(f, "{context}: missing {part}"),
+            Self::DuplicatePart { context, part } => write!(f, "{context}: second {part}"),
+            Self::IllegalValue { context, value } => write!(f, "{context}: illegal value {value:?}"),
+            Self::Int(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Location { line, col, .. } = self.span.start;
+        writeln!(f, "{line}:{col}: {}", self.kind)?;
+        writeln!(f, "{}", self.snippet)?;
+        let indent: String = self.snippet.chars()
+            .take(col.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(f, "{indent}^")
+    }
+}
+
+impl std::error::Error for ParseError { }
+
+/// Step on the path from the document down to one of its nodes.
+#[derive(Clone, Copy, Hash, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum NodeStep {
+    /// Index into the items of a document, section, quote, footnote, heading, paragraph, list,
+    /// link, attribution or table row.
+    Item(usize),
+    /// Index into the navigation of a document.
+    Nav(usize),
+    /// Index into the sub-navigation of a navigation.
+    Sub(usize),
+    /// Index into the links of a navigation.
+    Link(usize),
+    /// Index into the rows of a table or dataset.
+    Row(usize),
+    /// The heading of a section.
+    Heading,
+    /// The attribution of a quote.
+    Attribution,
+    /// The source of a quote.
+    Source,
+}
+
+/// Path from the document down to one of its nodes. The empty path is the document itself.
+pub type NodePath = Vec<NodeStep>;
+
+/// Side table that maps the path of each parsed node to where it is found in the input.
+pub type SpanTable = HashMap<NodePath, Span>;
+
+/// State that is carried along while walking the parse tree.
+#[derive(Default)]
+struct Context {
+    spans: Option<SpanTable>,
+    path: NodePath,
+}
+
+impl Context {
+    fn record(&mut self, pair: &Pair<'_, Rule>) {
+        if let Some(spans) = &mut self.spans {
+            spans.insert(self.path.clone(), Span::from_pest(&pair.as_span()));
+        }
+    }
+
+    fn child<T>(
+        &mut self, step: NodeStep, f: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        self.path.push(step);
+        let res = f(self);
+        self.path.pop();
+        res
+    }
+}
+
+/// Parse an incodoc into an abstract document structure.
+/// Quotes and footnotes by convention are turned into quotes and footnotes, see
+/// [`Doc::migrate_quotes`] and [`Doc::migrate_footnotes`].
+/// The first parse, by this or any other parse function here, turns on the error detail of pest
+/// with `pest::set_error_detail`. That switch holds for every pest parser in the process.
+pub fn parse(input: &str) -> Result<Doc, ParseError> {
+    let mut doc = parse_doc(&mut Context::default(), input)?;
+    doc.migrate_quotes();
+    doc.migrate_footnotes();
+    Ok(doc)
+}
+
+/// Parse an incodoc and record where in the input every node of the document was found.
+/// Metadata like tags and props is not recorded separately.
+pub fn parse_with_spans(input: &str) -> Result<(Doc, SpanTable), ParseError> {
+    let (mut doc, mut spans) = parse_doc_with_spans(input)?;
+    let mut left_out = migrate_quotes_doc(&mut doc);
+    left_out.extend(migrate_footnotes_doc(&mut doc));
+    for path in left_out {
+        spans.retain(|node, _| !node.starts_with(&path));
+    }
+    Ok((doc, spans))
+}
+
+/// Parse an incodoc and keep the comments in it.
+/// Every comment is attached to the innermost document, section, quote, footnote, paragraph,
+/// list, table, table row, dataset or navigation that holds it, at the position among its items
+/// where it was written. The rows of a dataset are its items.
+pub fn parse_with_comments(input: &str) -> Result<Doc, ParseError> {
+    let (mut doc, spans) = parse_doc_with_spans(input)?;
+    for token in scan(input).into_iter().filter(|token| token.kind == TokenKind::Comment) {
+        attach_comment(&mut doc, &spans, &input[token.start..token.end], token.start);
+    }
+    // after the comments are attached, the sections they were written in still exist
+    doc.migrate_quotes();
+    doc.migrate_footnotes();
+    Ok(doc)
+}
+
+/// Number of comments in the input, found apart from the parsing that attaches them.
+pub(crate) fn comment_count(input: &str) -> usize {
+    IncodocParser::parse(Rule::comment_scan, input)
+        .map(|pairs| pairs.flatten()
+            .filter(|pair| {
+                matches!(pair.as_rule(), Rule::single_line_comment | Rule::multi_line_comment)
+            })
+            .count())
+        .unwrap_or_default()
+}
+
+fn parse_doc_with_spans(input: &str) -> Result<(Doc, SpanTable), ParseError> {
+    let mut ctx = Context {
+        spans: Some(SpanTable::new()),
+        ..Default::default()
     };
+    let doc = parse_doc(&mut ctx, input)?;
+    Ok((doc, ctx.spans.unwrap_or_default()))
+}
+
+fn attach_comment(doc: &mut Doc, spans: &SpanTable, raw: &str, offset: usize) {
+    let (text, block) = match raw.strip_prefix("/*") {
+        Some(rest) => (rest.strip_suffix("*/").unwrap_or(rest), true),
+        None => (raw.strip_prefix("//").unwrap_or(raw).trim_end_matches('\r'), false),
+    };
+    let mut holders: Vec<&NodePath> = spans.iter()
+        .filter(|(_, span)| span.start.offset <= offset && offset < span.end.offset)
+        .map(|(path, _)| path)
+        .collect();
+    holders.sort_by_key(|path| std::cmp::Reverse(path.len()));
+    for path in holders {
+        let position = spans.iter()
+            .filter(|(child, span)| {
+                child.len() == path.len() + 1
+                    && child.starts_with(path)
+                    && span.start.offset < offset
+                    // the attribution and source of a quote are not among its items
+                    && !matches!(child.last(), Some(NodeStep::Attribution | NodeStep::Source))
+            })
+            .count();
+        if let Some(comments) = comments_mut(doc, path) {
+            comments.push(Comment {
+                text: text.to_string(),
+                block,
+                position,
+            });
+            return;
+        }
+    }
+}
+
+/// Node that can hold comments.
+enum CommentHolder<'a> {
+    Doc(&'a mut Doc),
+    Section(&'a mut Section),
+    Quote(&'a mut Quote),
+    Footnote(&'a mut FootnoteDef),
+    Paragraph(&'a mut Paragraph),
+    List(&'a mut List),
+    Table(&'a mut Table),
+    Row(&'a mut TableRow),
+    Data(&'a mut Data),
+    Nav(&'a mut Nav),
+}
+
+impl<'a> CommentHolder<'a> {
+    fn child(self, step: NodeStep) -> Option<Self> {
+        Some(match (self, step) {
+            (Self::Doc(doc), NodeStep::Nav(i)) => Self::Nav(doc.navs.get_mut(i)?),
+            (Self::Doc(doc), NodeStep::Item(i)) => match doc.items.get_mut(i)? {
+                DocItem::Section(section) => Self::Section(section),
+                DocItem::Paragraph(par) => Self::Paragraph(par),
+                DocItem::Quote(quote) => Self::Quote(quote),
+                DocItem::Footnote(footnote) => Self::Footnote(footnote),
+            },
+            (Self::Section(section), NodeStep::Item(i)) => match section.items.get_mut(i)? {
+                SectionItem::Section(section) => Self::Section(section),
+                SectionItem::Paragraph(par) => Self::Paragraph(par),
+                SectionItem::Quote(quote) => Self::Quote(quote),
+                SectionItem::Footnote(footnote) => Self::Footnote(footnote),
+            },
+            (Self::Quote(quote), NodeStep::Item(i)) => match quote.items.get_mut(i)? {
+                QuoteItem::Paragraph(par) => Self::Paragraph(par),
+                QuoteItem::Quote(quote) => Self::Quote(quote),
+            },
+            (Self::Footnote(footnote), NodeStep::Item(i)) => {
+                Self::Paragraph(footnote.items.get_mut(i)?)
+            },
+            (Self::Paragraph(par), NodeStep::Item(i)) => match par.items.get_mut(i)? {
+                ParagraphItem::List(list) => Self::List(list),
+                ParagraphItem::Table(table) => Self::Table(table),
+                ParagraphItem::Data(data) => Self::Data(data),
+                _ => return None,
+            },
+            (Self::List(list), NodeStep::Item(i)) => Self::Paragraph(list.items.get_mut(i)?),
+            (Self::Table(table), NodeStep::Row(i)) => Self::Row(table.rows.get_mut(i)?),
+            (Self::Row(row), NodeStep::Item(i)) => Self::Paragraph(row.items.get_mut(i)?),
+            (Self::Nav(nav), NodeStep::Sub(i)) => Self::Nav(nav.subs.get_mut(i)?),
+            _ => return None,
+        })
+    }
+
+    fn comments(self) -> &'a mut Vec<Comment> {
+        match self {
+            Self::Doc(doc) => &mut doc.comments,
+            Self::Section(section) => &mut section.comments,
+            Self::Quote(quote) => &mut quote.comments,
+            Self::Footnote(footnote) => &mut footnote.comments,
+            Self::Paragraph(par) => &mut par.comments,
+            Self::List(list) => &mut list.comments,
+            Self::Table(table) => &mut table.comments,
+            Self::Row(row) => &mut row.comments,
+            Self::Data(data) => &mut data.comments,
+            Self::Nav(nav) => &mut nav.comments,
+        }
+    }
+}
+
+fn comments_mut<'a>(doc: &'a mut Doc, path: &[NodeStep]) -> Option<&'a mut Vec<Comment>> {
+    let mut holder = CommentHolder::Doc(doc);
+    for step in path {
+        holder = holder.child(*step)?;
+    }
+    Some(holder.comments())
+}
+
+/// Parse an incodoc, skipping the constructs that are broken.
+/// A broken construct is repaired when it is only missing a comma.
+/// Otherwise the innermost paragraph, section, quote, footnote, navigation or table row that holds
+/// the error is left out, or, when the error is not inside one of those, the top level construct
+/// that holds it.
+/// Returns the document that could be built and an error for every construct that was repaired or
+/// left out.
+/// A broken paragraph in a list is replaced by an empty paragraph.
+pub fn parse_tolerant(input: &str) -> (Doc, Vec<ParseError>) {
+    let mut recovery = Recovery::new(input);
+    for (start, end) in recovery.chunks() {
+        recovery.recover(Region::Top(start, end));
+    }
+    let Recovery { buffer, mut errors, .. } = recovery;
+    // the changes only swap ascii bytes for others or blank whole tokens
+    let buffer = String::from_utf8(buffer).unwrap_or_default();
+    match parse(&buffer) {
+        Ok(doc) => (doc, errors),
+        Err(error) => {
+            errors.push(error.relocate(input));
+            (Doc::default(), errors)
+        },
+    }
+}
+
+static ERROR_DETAIL: Once = Once::new();
+
+fn parse_doc(ctx: &mut Context, input: &str) -> Result<Doc, ParseError> {
+    let mut doc = Doc::default();
+    // collect the extra detail to point errors at the furthest place the parser got to
+    ERROR_DETAIL.call_once(|| pest::set_error_detail(true));
+    let pairs = IncodocParser::parse(Rule::top, input)
+        .map_err(|error| ParseError::from_pest(error, input))?;
+    if let Some(spans) = &mut ctx.spans {
+        let span = PestSpan::new(input, 0, input.len()).expect("IP: parse_doc: whole input;");
+        spans.insert(NodePath::new(), Span::from_pest(&span));
+    }
     for inner in pairs {
         match inner.as_rule() {
-            Rule::tags => doc.tags.absorb(parse_tags(inner)),
-            Rule::props => doc.props.absorb(parse_props(inner)),
-            Rule::paragraph => doc.items.push(DocItem::Paragraph(parse_paragraph(inner))),
-            Rule::section => doc.items.push(DocItem::Section(parse_section(0, inner))),
-            Rule::nav_top => doc.navs.push(parse_nav(inner, true)),
+            Rule::tags => doc.tags.absorb(parse_tags(inner)?),
+            Rule::props => doc.props.absorb(parse_props(inner)?),
+            Rule::paragraph => doc.items.push(DocItem::Paragraph(
+                ctx.child(NodeStep::Item(doc.items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            )),
+            Rule::section => doc.items.push(DocItem::Section(
+                ctx.child(NodeStep::Item(doc.items.len()), |ctx| parse_section_in(ctx, 0, inner))?
+            )),
+            Rule::quote => doc.items.push(DocItem::Quote(
+                ctx.child(NodeStep::Item(doc.items.len()), |ctx| parse_quote(ctx, inner))?
+            )),
+            Rule::footnote => doc.items.push(DocItem::Footnote(
+                ctx.child(NodeStep::Item(doc.items.len()), |ctx| parse_footnote(ctx, inner))?
+            )),
+            Rule::nav_top => doc.navs.push(
+                ctx.child(NodeStep::Nav(doc.navs.len()), |ctx| parse_nav(ctx, inner, true))?
+            ),
             _ => {},
         }
     }
     Ok(doc)
 }
 
-fn parse_tags(pair: Pair<'_, Rule>) -> Option<Tags> {
+fn next_pair<'i>(
+    iter: &mut Pairs<'i, Rule>, span: &PestSpan<'i>, context: &'static str, part: &'static str,
+) -> Result<Pair<'i, Rule>, ParseError> {
+    iter.next().ok_or_else(||
+        ParseError::at_span(span, ParseErrorKind::MissingPart { context, part })
+    )
+}
+
+fn parse_tags(pair: Pair<'_, Rule>) -> Result<Option<Tags>, ParseError> {
     let mut res = HashSet::new();
     for strings in pair.into_inner() {
         if matches!(strings.as_rule(), Rule::prop_tuple) {
-            return None;
+            return Ok(None);
         }
         for string in strings.into_inner() {
-            res.insert(parse_string(string));
+            res.insert(parse_string(string)?);
         }
     }
-    Some(res)
+    Ok(Some(res))
 }
 
-fn parse_props(pair: Pair<'_, Rule>) -> Props {
+fn parse_props(pair: Pair<'_, Rule>) -> Result<Props, ParseError> {
     let mut props = HashMap::new();
     for inner in pair.into_inner() {
         match inner.as_rule() {
-            Rule::prop_tuple => insert_prop(&mut props, parse_prop_tuple(inner)),
-            r => panic!("IP: parse_props: illegal rule: {r:?};"),
+            Rule::prop_tuple => insert_prop(&mut props, parse_prop_tuple(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_props", &inner)),
         }
     }
-    props
+    Ok(props)
 }
 
-fn parse_prop_tuple(pair: Pair<'_, Rule>) -> (String, PropVal) {
+fn parse_prop_tuple(pair: Pair<'_, Rule>) -> Result<(String, PropVal), ParseError> {
+    let span = pair.as_span();
     let mut inners = pair.into_inner();
-    let string = inners.next().expect("IP: parse_prop_tuple: no string;");
-    let prop_val = inners.next().expect("IP: parse_prop_tuple: no prop_val;");
-    (parse_string(string), parse_prop_val(prop_val))
+    let string = next_pair(&mut inners, &span, "parse_prop_tuple", "string")?;
+    let prop_val = next_pair(&mut inners, &span, "parse_prop_tuple", "prop_val")?;
+    Ok((parse_string(string)?, parse_prop_val(prop_val)?))
 }
 
-fn parse_prop_val(pair: Pair<'_, Rule>) -> PropVal {
-    match pair.as_rule() {
-        Rule::string => PropVal::String(parse_string(pair)),
-        Rule::text => PropVal::Text(parse_text(pair)),
+fn parse_prop_val(pair: Pair<'_, Rule>) -> Result<PropVal, ParseError> {
+    Ok(match pair.as_rule() {
+        Rule::string => PropVal::String(parse_string(pair)?),
+        Rule::text => PropVal::Text(parse_text(pair)?),
         Rule::int => match parse_int(&pair) {
             Ok(int) => PropVal::Int(int),
             Err(error) => PropVal::Error(PropValError::Int(error)),
@@ -79,156 +565,410 @@ fn parse_prop_val(pair: Pair<'_, Rule>) -> PropVal {
             Ok(date) => PropVal::Date(date),
             Err(error) => PropVal::Error(PropValError::Date(error)),
         },
-        r => panic!("IP: parse_prop_val: illegal rule: {r:?};"),
-    }
+        _ => return Err(ParseError::illegal_rule("parse_prop_val", &pair)),
+    })
+}
+
+/// Parse a `section` pair. The level in its heading is relative to `heading_level`.
+pub fn parse_section(heading_level: u64, pair: Pair<'_, Rule>) -> Result<Section, ParseError> {
+    parse_section_in(&mut Context::default(), heading_level, pair)
 }
 
-#[must_use]
-pub fn parse_section(mut heading_level: u64, pair: Pair<'_, Rule>) -> Section {
+/// Parse a `heading` pair. The level in it is relative to `heading_level`, which is set to the
+/// level the heading gets.
+pub fn parse_heading(heading_level: &mut u64, pair: Pair<'_, Rule>) -> Result<Heading, ParseError> {
+    parse_heading_in(&mut Context::default(), heading_level, pair)
+}
+
+/// Parse a `paragraph` pair.
+pub fn parse_paragraph(pair: Pair<'_, Rule>) -> Result<Paragraph, ParseError> {
+    parse_paragraph_in(&mut Context::default(), pair)
+}
+
+/// Parse an `emphasis` pair.
+pub fn parse_emphasis(pair: Pair<'_, Rule>) -> Result<Emphasis, ParseError> {
+    parse_emphasis_in(&mut Context::default(), pair)
+}
+
+/// Parse a `list` pair.
+pub fn parse_list(pair: Pair<'_, Rule>) -> Result<List, ParseError> {
+    parse_list_in(&mut Context::default(), pair)
+}
+
+fn parse_section_in(
+    ctx: &mut Context, mut heading_level: u64, pair: Pair<'_, Rule>,
+) -> Result<Section, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let heading
-        = parse_heading(&mut heading_level, iter.next().expect("IP: parse_section: no heading"));
+    let heading_pair = next_pair(&mut iter, &span, "parse_section", "heading")?;
+    let heading = ctx.child(NodeStep::Heading, |ctx|
+        parse_heading_in(ctx, &mut heading_level, heading_pair)
+    )?;
     let mut items = Vec::new();
     let mut tags = Tags::default();
     let mut props = Props::default();
     for inner in iter {
         match inner.as_rule() {
-            Rule::paragraph => items.push(SectionItem::Paragraph(parse_paragraph(inner))),
-            Rule::section
-                => items.push(SectionItem::Section(parse_section(heading_level + 1, inner))),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_section: illegal rule: {r:?};"),
+            Rule::paragraph => items.push(SectionItem::Paragraph(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            )),
+            Rule::section => items.push(SectionItem::Section(
+                ctx.child(NodeStep::Item(items.len()), |ctx|
+                    parse_section_in(ctx, heading_level + 1, inner)
+                )?
+            )),
+            Rule::quote => items.push(SectionItem::Quote(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_quote(ctx, inner))?
+            )),
+            Rule::footnote => items.push(SectionItem::Footnote(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_footnote(ctx, inner))?
+            )),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_section", &inner)),
         }
     }
 
-    Section {
+    Ok(Section {
         heading,
         items,
         tags,
         props,
-    }
+        comments: Vec::new(),
+    })
 }
 
-pub fn parse_heading(heading_level: &mut u64, pair: Pair<'_, Rule>) -> Heading {
+fn parse_heading_in(
+    ctx: &mut Context, heading_level: &mut u64, pair: Pair<'_, Rule>,
+) -> Result<Heading, ParseError> {
+    ctx.record(&pair);
     let mut items = Vec::new();
     let mut tags = Tags::default();
     let mut props = Props::default();
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let rel_level = parse_uint_capped(&iter.next().expect("IP: parse_heading: no strength;"));
-    let level = (rel_level + *heading_level).min(255) as u8;
+    let rel_level = parse_uint_capped(&next_pair(&mut iter, &span, "parse_heading", "level")?)?;
+    let level = rel_level.saturating_add(*heading_level).min(255) as u8;
     *heading_level = u64::from(level);
     for inner in iter {
         match inner.as_rule() {
-            Rule::string => items.push(EmOrText::Text(parse_string(inner))),
-            Rule::emphasis => items.push(EmOrText::Em(parse_emphasis(inner))),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_heading: illegal rule: {r:?};"),
+            Rule::string => items.push(EmOrText::Text(
+                ctx.child(NodeStep::Item(items.len()), |ctx| {
+                    ctx.record(&inner);
+                    parse_string(inner)
+                })?
+            )),
+            Rule::emphasis => items.push(EmOrText::Em(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_emphasis_in(ctx, inner))?
+            )),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_heading", &inner)),
         }
     }
-    Heading {
+    Ok(Heading {
         level,
         items,
         tags,
         props,
-    }
+    })
 }
 
-#[must_use]
-pub fn parse_paragraph(pair: Pair<'_, Rule>) -> Paragraph {
+fn parse_paragraph_in(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Paragraph, ParseError> {
+    ctx.record(&pair);
     let mut items = Vec::new();
     let mut tags = Tags::default();
     let mut props = Props::default();
     for inner in pair.into_inner() {
+        let step = NodeStep::Item(items.len());
         match inner.as_rule() {
             Rule::text_item => {
-                let text = parse_text_item(inner);
+                let text = ctx.child(step, |ctx| {
+                    ctx.record(&inner);
+                    parse_text_item(inner)
+                })?;
                 if text.meta_is_empty() {
                     items.push(ParagraphItem::Text(text.text));
                 } else {
                     items.push(ParagraphItem::MText(text));
                 }
             },
-            Rule::emphasis => items.push(ParagraphItem::Em(parse_emphasis(inner))),
-            Rule::code => items.push(ParagraphItem::Code(parse_code(inner))),
-            Rule::list => items.push(ParagraphItem::List(parse_list(inner))),
-            Rule::link => items.push(ParagraphItem::Link(parse_link(inner))),
-            Rule::table => items.push(ParagraphItem::Table(parse_table(inner))),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_paragraph: illegal rule: {r:?};"),
+            Rule::emphasis => items.push(ParagraphItem::Em(
+                ctx.child(step, |ctx| parse_emphasis_in(ctx, inner))?
+            )),
+            Rule::code => items.push(ParagraphItem::Code(
+                ctx.child(step, |ctx| parse_code(ctx, inner))?
+            )),
+            Rule::list => items.push(ParagraphItem::List(
+                ctx.child(step, |ctx| parse_list_in(ctx, inner))?
+            )),
+            Rule::link => items.push(ParagraphItem::Link(
+                ctx.child(step, |ctx| parse_link(ctx, inner))?
+            )),
+            Rule::table => items.push(ParagraphItem::Table(
+                ctx.child(step, |ctx| parse_table(ctx, inner))?
+            )),
+            Rule::inline_quote => items.push(ParagraphItem::Quote(
+                ctx.child(step, |ctx| parse_inline_quote(ctx, inner))?
+            )),
+            Rule::footnote_ref => items.push(ParagraphItem::FootnoteRef(
+                ctx.child(step, |ctx| parse_footnote_ref(ctx, inner))?
+            )),
+            Rule::data => items.push(ParagraphItem::Data(
+                ctx.child(step, |ctx| parse_data(ctx, inner))?
+            )),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_paragraph", &inner)),
         }
     }
-    Paragraph {
+    Ok(Paragraph {
         items,
         tags,
         props,
+        comments: Vec::new(),
+    })
+}
+
+fn parse_quote(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Quote, ParseError> {
+    ctx.record(&pair);
+    let mut kind = None;
+    let mut items = Vec::new();
+    let mut attribution = Vec::new();
+    let mut source = None;
+    let mut tags = Tags::default();
+    let mut props = Props::default();
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::string => kind = Some(parse_string(inner)?),
+            Rule::paragraph => items.push(QuoteItem::Paragraph(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            )),
+            Rule::quote => items.push(QuoteItem::Quote(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_quote(ctx, inner))?
+            )),
+            Rule::attribution => ctx.child(NodeStep::Attribution, |ctx|
+                parse_attribution(ctx, inner, &mut attribution)
+            )?,
+            Rule::link if source.is_some() => return Err(ParseError::at(
+                &inner,
+                ParseErrorKind::DuplicatePart { context: "parse_quote", part: "source" },
+            )),
+            Rule::link => source = Some(
+                ctx.child(NodeStep::Source, |ctx| parse_link(ctx, inner))?
+            ),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_quote", &inner)),
+        }
     }
+    Ok(Quote {
+        kind,
+        items,
+        attribution,
+        source,
+        tags,
+        props,
+        comments: Vec::new(),
+    })
 }
 
-#[must_use]
-pub fn parse_emphasis(pair: Pair<'_, Rule>) -> Emphasis {
+fn parse_inline_quote(
+    ctx: &mut Context, pair: Pair<'_, Rule>,
+) -> Result<InlineQuote, ParseError> {
+    ctx.record(&pair);
+    let mut kind = None;
+    let mut items = Vec::new();
+    let mut attribution = Vec::new();
+    let mut source = None;
+    let mut tags = Tags::default();
+    let mut props = Props::default();
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::string => kind = Some(parse_string(inner)?),
+            Rule::text => items.push(EmOrText::Text(
+                ctx.child(NodeStep::Item(items.len()), |ctx| {
+                    ctx.record(&inner);
+                    parse_text(inner)
+                })?
+            )),
+            Rule::emphasis => items.push(EmOrText::Em(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_emphasis_in(ctx, inner))?
+            )),
+            Rule::attribution => ctx.child(NodeStep::Attribution, |ctx|
+                parse_attribution(ctx, inner, &mut attribution)
+            )?,
+            Rule::link if source.is_some() => return Err(ParseError::at(
+                &inner,
+                ParseErrorKind::DuplicatePart { context: "parse_inline_quote", part: "source" },
+            )),
+            Rule::link => source = Some(
+                ctx.child(NodeStep::Source, |ctx| parse_link(ctx, inner))?
+            ),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_inline_quote", &inner)),
+        }
+    }
+    Ok(InlineQuote {
+        kind,
+        items,
+        attribution,
+        source,
+        tags,
+        props,
+    })
+}
+
+fn parse_footnote(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<FootnoteDef, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
+    let mut iter = pair.into_inner();
+    let label = parse_string(next_pair(&mut iter, &span, "parse_footnote", "label")?)?;
+    let mut items = Vec::new();
+    let mut tags = Tags::default();
+    let mut props = Props::default();
+    for inner in iter {
+        match inner.as_rule() {
+            Rule::paragraph => items.push(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            ),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_footnote", &inner)),
+        }
+    }
+    Ok(FootnoteDef {
+        label,
+        items,
+        tags,
+        props,
+        comments: Vec::new(),
+    })
+}
+
+fn parse_footnote_ref(
+    ctx: &mut Context, pair: Pair<'_, Rule>,
+) -> Result<FootnoteRef, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
+    let mut iter = pair.into_inner();
+    let label = parse_string(next_pair(&mut iter, &span, "parse_footnote_ref", "label")?)?;
+    let mut tags = Tags::default();
+    let mut props = Props::default();
+    for inner in iter {
+        match inner.as_rule() {
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_footnote_ref", &inner)),
+        }
+    }
+    Ok(FootnoteRef {
+        label,
+        tags,
+        props,
+    })
+}
+
+/// Parse an attribution, adding to the items of the attributions before it.
+fn parse_attribution(
+    ctx: &mut Context, pair: Pair<'_, Rule>, items: &mut Vec<EmOrText>,
+) -> Result<(), ParseError> {
+    ctx.record(&pair);
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::string => items.push(EmOrText::Text(
+                ctx.child(NodeStep::Item(items.len()), |ctx| {
+                    ctx.record(&inner);
+                    parse_string(inner)
+                })?
+            )),
+            Rule::emphasis => items.push(EmOrText::Em(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_emphasis_in(ctx, inner))?
+            )),
+            _ => return Err(ParseError::illegal_rule("parse_attribution", &inner)),
+        }
+    }
+    Ok(())
+}
+
+fn parse_emphasis_in(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Emphasis, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let strength_type_raw = iter.next().expect("IP: parse_emphasis: no strength_type").as_str();
-    let text_raw = iter.next().expect("IP: parse_emphasis: no text");
-    let text = parse_string(text_raw);
-    let (strength, etype) = match strength_type_raw {
+    let strength_type_raw = next_pair(&mut iter, &span, "parse_emphasis", "strength_type")?;
+    let text_raw = next_pair(&mut iter, &span, "parse_emphasis", "text")?;
+    let text = parse_string(text_raw)?;
+    let (strength, etype) = match strength_type_raw.as_str() {
         "le" => (EmStrength::Light, EmType::Emphasis),
         "me" => (EmStrength::Medium, EmType::Emphasis),
         "se" => (EmStrength::Strong, EmType::Emphasis),
         "ld" => (EmStrength::Light, EmType::Deemphasis),
         "md" => (EmStrength::Medium, EmType::Deemphasis),
         "sd" => (EmStrength::Strong, EmType::Deemphasis),
-        _ => panic!("IP: parse_emphasis: wrong strength_type;")
+        value => return Err(ParseError::at(
+            &strength_type_raw,
+            ParseErrorKind::IllegalValue { context: "parse_emphasis", value: value.to_string() },
+        )),
     };
     let mut tags = Tags::default();
     let mut props = Props::default();
     for inner in iter.by_ref() {
         match inner.as_rule() {
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_emphasis: loop: illegal rule: {r:?};"),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_emphasis", &inner)),
         }
     }
-    Emphasis {
+    Ok(Emphasis {
         strength,
         etype,
         text,
         tags,
         props,
-    }
+    })
 }
 
-#[must_use]
-pub fn parse_list(pair: Pair<'_, Rule>) -> List {
+fn parse_list_in(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<List, ParseError> {
+    ctx.record(&pair);
     let mut items = Vec::new();
     let mut tags = Tags::default();
     let mut props = Props::default();
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let ltype = match iter.next().expect("IP: parse_list: no type;").as_str() {
+    let ltype_raw = next_pair(&mut iter, &span, "parse_list", "type")?;
+    let ltype = match ltype_raw.as_str() {
         "dl" => ListType::Distinct,
         "il" => ListType::Identical,
         "cl" => ListType::Checked,
-        _ => panic!("IP: parse_list: impossble list type;"),
+        value => return Err(ParseError::at(
+            &ltype_raw,
+            ParseErrorKind::IllegalValue { context: "parse_list", value: value.to_string() },
+        )),
     };
     for inner in iter {
         match inner.as_rule() {
-            Rule::paragraph => items.push(parse_paragraph(inner)),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_list: illegal rule: {r:?};"),
+            Rule::paragraph => items.push(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            ),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_list", &inner)),
         }
     }
-    List {
+    Ok(List {
         ltype,
         items,
         tags,
         props,
-    }
+        comments: Vec::new(),
+    })
 }
 
-fn parse_nav(pair: Pair<'_, Rule>, top: bool) -> Nav {
+fn parse_nav(ctx: &mut Context, pair: Pair<'_, Rule>, top: bool) -> Result<Nav, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
     let mut tags = Tags::default();
     let mut props = Props::default();
@@ -237,155 +977,237 @@ fn parse_nav(pair: Pair<'_, Rule>, top: bool) -> Nav {
     let description = if top {
         String::new()
     } else {
-        parse_string(iter.next().expect("IP: parse_nav: no description;"))
+        parse_string(next_pair(&mut iter, &span, "parse_nav", "description")?)?
     };
     for inner in iter {
         match inner.as_rule() {
-            Rule::nav => subs.push(parse_nav(inner, false)),
-            Rule::link => links.push(parse_link(inner)),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_nav: illegal rule: {r:?};"),
+            Rule::nav => subs.push(
+                ctx.child(NodeStep::Sub(subs.len()), |ctx| parse_nav(ctx, inner, false))?
+            ),
+            Rule::link => links.push(
+                ctx.child(NodeStep::Link(links.len()), |ctx| parse_link(ctx, inner))?
+            ),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_nav", &inner)),
         }
     }
-    Nav {
+    Ok(Nav {
         description,
         subs,
         links,
         tags,
         props,
-    }
+        comments: Vec::new(),
+    })
 }
 
-fn parse_link(pair: Pair<'_, Rule>) -> Link {
+fn parse_link(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Link, ParseError> {
+    ctx.record(&pair);
     let mut items = Vec::new();
     let mut tags = Tags::default();
     let mut props = Props::default();
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let url = parse_string(iter.next().expect("IP: parse_link: no url;"));
+    let url = parse_string(next_pair(&mut iter, &span, "parse_link", "url")?)?;
     for inner in iter.by_ref() {
         match inner.as_rule() {
-            Rule::emphasis => items.push(EmOrText::Em(parse_emphasis(inner))),
-            Rule::string => items.push(EmOrText::Text(parse_string(inner))),
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_link: illegal rule: {r:?};"),
+            Rule::emphasis => items.push(EmOrText::Em(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_emphasis_in(ctx, inner))?
+            )),
+            Rule::string => items.push(EmOrText::Text(
+                ctx.child(NodeStep::Item(items.len()), |ctx| {
+                    ctx.record(&inner);
+                    parse_string(inner)
+                })?
+            )),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_link", &inner)),
         }
     }
-    Link {
+    Ok(Link {
         url,
         items,
         tags,
         props,
-    }
+    })
 }
 
-fn parse_code(pair: Pair<'_, Rule>) -> Result<CodeBlock, CodeIdentError> {
+fn parse_code(
+    ctx: &mut Context, pair: Pair<'_, Rule>,
+) -> Result<Result<CodeBlock, CodeIdentError>, ParseError> {
+    ctx.record(&pair);
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
     let mut tags = Tags::default();
     let mut props = Props::default();
-    let language = parse_string(iter.next().expect("IP: parse_code: no language;"));
-    let mode = parse_code_mode(iter.next().expect("IP: parse_code: no mode;"));
-    let code = parse_code_text(iter.next().expect("IP: parse_code: no code;"))?;
+    let language = parse_string(next_pair(&mut iter, &span, "parse_code", "language")?)?;
+    let mode = parse_code_mode(next_pair(&mut iter, &span, "parse_code", "mode")?)?;
+    let code = parse_code_text(next_pair(&mut iter, &span, "parse_code", "code")?)?;
     for inner in iter {
         match inner.as_rule() {
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            r => panic!("IP: parse_code: loop: illegal rule: {r:?};"),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            _ => return Err(ParseError::illegal_rule("parse_code", &inner)),
         }
     }
-    Ok(CodeBlock {
+    Ok(code.map(|code| CodeBlock {
         language,
         mode,
         code,
         tags,
         props,
-    })
+    }))
 }
 
-fn parse_code_mode(pair: Pair<'_, Rule>) -> CodeModeHint {
-    let string = parse_string(pair);
-    match string.as_ref() {
+fn parse_code_mode(pair: Pair<'_, Rule>) -> Result<CodeModeHint, ParseError> {
+    let string = parse_string(pair)?;
+    Ok(match string.as_ref() {
         "runnable" => CodeModeHint::Runnable,
         "run" => CodeModeHint::Run,
         "replace" => CodeModeHint::Replace,
         _ => CodeModeHint::Show,
-    }
+    })
 }
 
-fn parse_table(pair: Pair<'_, Rule>) -> Table {
+fn parse_table(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Table, ParseError> {
+    ctx.record(&pair);
     let iter = pair.into_inner();
     let mut tags = Tags::default();
     let mut props = Props::default();
     let mut rows = Vec::new();
     for inner in iter {
+        let step = NodeStep::Row(rows.len());
         match inner.as_rule() {
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            Rule::table_header_row => rows.push(parse_table_row(inner, true)),
-            Rule::table_regular_row => rows.push(parse_table_row(inner, false)),
-            r => panic!("IP: parse_code: loop: illegal rule: {r:?};"),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            Rule::table_header_row => rows.push(
+                ctx.child(step, |ctx| parse_table_row(ctx, inner, true))?
+            ),
+            Rule::table_regular_row => rows.push(
+                ctx.child(step, |ctx| parse_table_row(ctx, inner, false))?
+            ),
+            _ => return Err(ParseError::illegal_rule("parse_table", &inner)),
         }
     }
-    Table {
+    Ok(Table {
         rows,
         tags,
         props,
-    }
+        comments: Vec::new(),
+    })
 }
 
-fn parse_table_row(pair: Pair<'_, Rule>, is_header: bool) -> TableRow {
+fn parse_table_row(
+    ctx: &mut Context, pair: Pair<'_, Rule>, is_header: bool,
+) -> Result<TableRow, ParseError> {
+    ctx.record(&pair);
     let iter = pair.into_inner();
     let mut tags = Tags::default();
     let mut props = Props::default();
     let mut items = Vec::new();
     for inner in iter {
         match inner.as_rule() {
-            Rule::tags => tags.absorb(parse_tags(inner)),
-            Rule::props => props.absorb(parse_props(inner)),
-            Rule::paragraph => items.push(parse_paragraph(inner)),
-            r => panic!("IP: parse_code: loop: illegal rule: {r:?};"),
+            Rule::tags => tags.absorb(parse_tags(inner)?),
+            Rule::props => props.absorb(parse_props(inner)?),
+            Rule::paragraph => items.push(
+                ctx.child(NodeStep::Item(items.len()), |ctx| parse_paragraph_in(ctx, inner))?
+            ),
+            _ => return Err(ParseError::illegal_rule("parse_table_row", &inner)),
         }
     }
-    TableRow {
+    Ok(TableRow {
         items,
         is_header,
         tags,
         props,
+        comments: Vec::new(),
+    })
+}
+
+fn parse_data(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Data, ParseError> {
+    ctx.record(&pair);
+    let mut data = Data::default();
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::tags => data.tags.absorb(parse_tags(inner)?),
+            Rule::props => data.props.absorb(parse_props(inner)?),
+            Rule::data_columns => for strings in inner.into_inner() {
+                for string in strings.into_inner() {
+                    data.columns.push(parse_string(string)?);
+                }
+            },
+            Rule::data_row => data.rows.push(
+                ctx.child(NodeStep::Row(data.rows.len()), |ctx| parse_data_row(ctx, inner))?
+            ),
+            _ => return Err(ParseError::illegal_rule("parse_data", &inner)),
+        }
+    }
+    Ok(data)
+}
+
+fn parse_data_row(ctx: &mut Context, pair: Pair<'_, Rule>) -> Result<Vec<DataCell>, ParseError> {
+    ctx.record(&pair);
+    let mut cells = Vec::new();
+    for inner in pair.into_inner() {
+        cells.push(match inner.as_rule() {
+            Rule::text => DataCell::Text(parse_text(inner)?),
+            Rule::int => DataCell::Int(parse_int(&inner).map_err(|error|
+                ParseError::at(&inner, ParseErrorKind::Int(error))
+            )?),
+            Rule::float => DataCell::Float(inner.as_str().parse()
+                .ok()
+                // a literal too large for a float is not infinity, only `inf` is
+                .filter(|float: &f64| !float.is_infinite() || inner.as_str().ends_with("inf"))
+                .ok_or_else(|| ParseError::at(&inner, ParseErrorKind::IllegalValue {
+                    context: "parse_data_row",
+                    value: inner.as_str().to_string(),
+                }))?
+            ),
+            Rule::bool => DataCell::Bool(inner.as_str() == "true"),
+            _ => return Err(ParseError::illegal_rule("parse_data_row", &inner)),
+        });
     }
+    Ok(cells)
 }
 
-fn parse_string(pair: Pair<'_, Rule>) -> String {
-    let inner = pair.into_inner().next().expect("IP: parse_string: no inner;");
-    inner.as_str().chars().filter(|c| *c != '\n' && *c != '\r').collect()
+fn parse_string(pair: Pair<'_, Rule>) -> Result<String, ParseError> {
+    let span = pair.as_span();
+    let inner = next_pair(&mut pair.into_inner(), &span, "parse_string", "inner")?;
+    let raw: String = inner.as_str().chars().filter(|c| *c != '\n' && *c != '\r').collect();
+    Ok(unescape(&raw, '"'))
 }
 
-fn parse_text(pair: Pair<'_, Rule>) -> String {
-    parse_text_string(pair.into_inner().next().expect("IP: parse_text: no inner;").as_str())
+fn parse_text(pair: Pair<'_, Rule>) -> Result<String, ParseError> {
+    let span = pair.as_span();
+    let inner = next_pair(&mut pair.into_inner(), &span, "parse_text", "inner")?;
+    Ok(parse_text_string(inner.as_str()))
 }
 
-fn parse_text_item(pair: Pair<'_, Rule>) -> TextWithMeta {
+fn parse_text_item(pair: Pair<'_, Rule>) -> Result<TextWithMeta, ParseError> {
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let string_raw = iter.next().expect("IP: parse_text: no inner;").into_inner().as_str();
+    let string_raw = next_pair(&mut iter, &span, "parse_text_item", "text")?.into_inner().as_str();
     let text = parse_text_string(string_raw);
     let (tags, props) = if let Some(next) = iter.next() {
-        if let Some(tags) = parse_tags(next.clone()) {
+        if let Some(tags) = parse_tags(next.clone())? {
             if let Some(next) = iter.next() {
-                (tags, parse_props(next))
+                (tags, parse_props(next)?)
             } else {
                 (tags, Props::default())
             }
         } else {
-            (Tags::default(), parse_props(next))
+            (Tags::default(), parse_props(next)?)
         }
     } else {
         (Tags::default(), Props::default())
     };
-    TextWithMeta {
+    Ok(TextWithMeta {
         text,
         tags,
         props,
-    }
+    })
 }
 
 fn parse_text_string(string: &str) -> String {
@@ -422,13 +1244,14 @@ fn parse_text_string(string: &str) -> String {
     if let Some(last) = res.chars().last() && last == '\n' {
         res.pop();
     }
-    res
+    unescape(&res, '\'')
 }
 
-fn parse_code_text(pair: Pair<'_, Rule>) -> Result<String, CodeIdentError> {
+fn parse_code_text(pair: Pair<'_, Rule>) -> Result<Result<String, CodeIdentError>, ParseError> {
+    let span = pair.as_span();
     let mut iter = pair.into_inner();
-    let start = iter.next().expect("IP: parse_text: no start;");
-    let inner = iter.next().expect("IP: parse_text: no inner;");
+    let start = next_pair(&mut iter, &span, "parse_code_text", "start")?;
+    let inner = next_pair(&mut iter, &span, "parse_code_text", "inner")?;
     let (_, start_col) = start.line_col();
     let raw = inner.as_str().to_string();
     let mut res = String::new();
@@ -454,7 +1277,7 @@ fn parse_code_text(pair: Pair<'_, Rule>) -> Result<String, CodeIdentError> {
             '\r' => {},
             _ => {
                 if identc < start_col - 1 {
-                    return Err(CodeIdentError);
+                    return Ok(Err(CodeIdentError));
                 }
                 res.push(c);
             },
@@ -463,15 +1286,31 @@ fn parse_code_text(pair: Pair<'_, Rule>) -> Result<String, CodeIdentError> {
     if res.ends_with('\n') {
         res.pop();
     }
-    Ok(res)
+    Ok(Ok(unescape(&res, '\'')))
+}
+
+/// Resolve escape sequences: a backslash followed by the quote or another backslash yields just
+/// that character. Any other backslash is kept as is.
+fn unescape(string: &str, quote: char) -> String {
+    let mut res = String::new();
+    let mut iter = string.chars().peekable();
+    while let Some(c) = iter.next() {
+        if c == '\\' && let Some(&next) = iter.peek() && (next == quote || next == '\\') {
+            res.push(next);
+            iter.next();
+        } else {
+            res.push(c);
+        }
+    }
+    res
 }
 
 fn _parse_uint(pair: &Pair<'_, Rule>) -> Result<u64, ParseIntError> {
     pair.as_str().parse()
 }
 
-fn parse_uint_capped(pair: &Pair<'_, Rule>) -> u64 {
-    pair.as_str().parse().expect("IP: parse_uint_capped: uint with more than 19 numbers;")
+fn parse_uint_capped(pair: &Pair<'_, Rule>) -> Result<u64, ParseError> {
+    pair.as_str().parse().map_err(|error| ParseError::at(pair, ParseErrorKind::Int(error)))
 }
 
 fn parse_int(pair: &Pair<'_, Rule>) -> Result<i64, ParseIntError> {
@@ -480,9 +1319,9 @@ fn parse_int(pair: &Pair<'_, Rule>) -> Result<i64, ParseIntError> {
 
 fn parse_date(pair: &Pair<'_, Rule>) -> Result<Date, DateError> {
     let mut iter = pair.as_str().split('/');
-    let ys = iter.next().expect("IP: parse_date: no year;");
-    let ms = iter.next().expect("IP: parse_date: no month;");
-    let ds = iter.next().expect("IP: parse_date: no day;");
+    let ys = iter.next().unwrap_or_default();
+    let ms = iter.next().unwrap_or_default();
+    let ds = iter.next().unwrap_or_default();
     Date::new(
         ys.parse().map_err(DateError::Parsing)?,
         ms.parse().map_err(DateError::Parsing)?,
@@ -490,3 +1329,353 @@ fn parse_date(pair: &Pair<'_, Rule>) -> Result<Date, DateError> {
     )
 }
 
+/// Constructs that are left out as a whole when they are broken.
+const RECOVERY_UNITS: [&str; 7] = ["par", "section", "quote", "footnote", "nav", "trow", "throw"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TokenKind {
+    Word,
+    Open,
+    Close,
+    Comma,
+    Quoted,
+    Comment,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'+' | b'/')
+}
+
+/// Split the input into rough tokens, skipping whitespace.
+/// Strings, texts and code are kept whole, also when they are not closed.
+fn scan(input: &str) -> Vec<Token> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = input[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+                TokenKind::Comment
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = input[i + 2..].find("*/").map(|n| i + n + 4).unwrap_or(bytes.len());
+                TokenKind::Comment
+            },
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::Quoted
+            },
+            b'{' => { i += 1; TokenKind::Open },
+            b'}' => { i += 1; TokenKind::Close },
+            b',' => { i += 1; TokenKind::Comma },
+            b if is_word_byte(b) => {
+                // a comment can follow a word or date without a space
+                while i < bytes.len()
+                    && is_word_byte(bytes[i])
+                    && !(bytes[i] == b'/' && matches!(bytes.get(i + 1), Some(b'/' | b'*')))
+                {
+                    i += 1;
+                }
+                TokenKind::Word
+            },
+            _ => {
+                i += input[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+                TokenKind::Other
+            },
+        };
+        tokens.push(Token { kind, start, end: i });
+    }
+    tokens
+}
+
+/// Construct between braces, together with the word in front of it.
+#[derive(Clone, Copy, Debug)]
+struct Block<'a> {
+    keyword: &'a str,
+    start: usize,
+    /// Byte after the closing brace, or the end of the input when it is never closed.
+    end: usize,
+    closed: bool,
+    parent: Option<usize>,
+}
+
+impl Block<'_> {
+    fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && (offset < self.end || !self.closed)
+    }
+}
+
+fn blocks<'a>(input: &'a str, tokens: &[Token]) -> Vec<Block<'a>> {
+    let mut blocks: Vec<Block<'a>> = Vec::new();
+    let mut open = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Open => {
+                let (keyword, start) = match i.checked_sub(1).map(|j| tokens[j]) {
+                    Some(word) if word.kind == TokenKind::Word => {
+                        (&input[word.start..word.end], word.start)
+                    },
+                    _ => ("", token.start),
+                };
+                open.push(blocks.len());
+                blocks.push(Block {
+                    keyword,
+                    start,
+                    end: input.len(),
+                    closed: false,
+                    parent: open.iter().rev().nth(1).copied(),
+                });
+            },
+            TokenKind::Close => if let Some(block) = open.pop() {
+                blocks[block].end = token.end;
+                blocks[block].closed = true;
+            },
+            _ => {},
+        }
+    }
+    blocks
+}
+
+/// Part of the input that is checked and recovered on its own.
+#[derive(Clone, Copy, Debug)]
+enum Region {
+    /// Top level constructs between two commas, together with the comma after them.
+    Top(usize, usize),
+    /// Recovery unit, by its index in the blocks.
+    Unit(usize),
+}
+
+/// State of `parse_tolerant`.
+/// The tokens and blocks are found once, on the original input.
+/// Every change to the buffer keeps the byte offsets the same, so they stay valid.
+struct Recovery<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    blocks: Vec<Block<'a>>,
+    /// The recovery unit around every block, if any.
+    units: Vec<Option<usize>>,
+    buffer: Vec<u8>,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Recovery<'a> {
+    fn new(input: &'a str) -> Self {
+        let tokens: Vec<_> = scan(input).into_iter()
+            .filter(|token| token.kind != TokenKind::Comment)
+            .collect();
+        let blocks = blocks(input, &tokens);
+        let units = blocks.iter()
+            .map(|block| {
+                let mut parent = block.parent;
+                while let Some(p) = parent && !RECOVERY_UNITS.contains(&blocks[p].keyword) {
+                    parent = blocks[p].parent;
+                }
+                parent
+            })
+            .collect();
+        Self { input, tokens, blocks, units, buffer: input.as_bytes().to_vec(), errors: Vec::new() }
+    }
+
+    fn chunks(&self) -> Vec<(usize, usize)> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let mut depth = 0usize;
+        for token in &self.tokens {
+            match token.kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close => depth = depth.saturating_sub(1),
+                TokenKind::Comma if depth == 0 => {
+                    chunks.push((start, token.end));
+                    start = token.end;
+                },
+                _ => {},
+            }
+        }
+        chunks.push((start, self.input.len()));
+        chunks
+    }
+
+    fn range(&self, region: Region) -> (usize, usize) {
+        match region {
+            Region::Top(start, end) => (start, end),
+            Region::Unit(unit) => (self.blocks[unit].start, self.blocks[unit].end),
+        }
+    }
+
+    /// Recovery units directly in the region, not inside another unit.
+    fn children(&self, region: Region) -> Vec<usize> {
+        let (start, end) = self.range(region);
+        let parent = match region {
+            Region::Top(..) => None,
+            Region::Unit(unit) => Some(unit),
+        };
+        (0..self.blocks.len())
+            .filter(|&i| RECOVERY_UNITS.contains(&self.blocks[i].keyword))
+            .filter(|&i| self.units[i] == parent)
+            .filter(|&i| start <= self.blocks[i].start && self.blocks[i].start < end)
+            .collect()
+    }
+
+    fn unit_rule(&self, unit: usize) -> Rule {
+        let block = &self.blocks[unit];
+        let parent = block.parent.map(|parent| self.blocks[parent].keyword);
+        match block.keyword {
+            "par" => Rule::paragraph,
+            "section" => Rule::section,
+            "quote" if parent == Some("par") => Rule::inline_quote,
+            "quote" => Rule::quote,
+            "footnote" => Rule::footnote,
+            "nav" if parent.is_none() => Rule::nav_top,
+            "nav" => Rule::nav,
+            "throw" => Rule::table_header_row,
+            _ => Rule::table_regular_row,
+        }
+    }
+
+    /// Parse the region on its own.
+    fn check(&self, region: Region) -> Result<(), ParseError> {
+        let (start, end) = self.range(region);
+        let text = std::str::from_utf8(&self.buffer[start..end]).unwrap_or_default();
+        let ctx = &mut Context::default();
+        let result = match region {
+            Region::Top(..) => parse_doc(ctx, text).map(drop),
+            Region::Unit(unit) => check_unit(ctx, self.unit_rule(unit), text),
+        };
+        result.map_err(|error| error.shift(start).relocate(self.input))
+    }
+
+    /// Change the region until it parses.
+    /// The recovery units in it are recovered first, each on its own, so only the parts that are
+    /// broken are parsed more than once.
+    fn recover(&mut self, region: Region) {
+        if self.check(region).is_ok() {
+            return;
+        }
+        for child in self.children(region) {
+            self.recover(Region::Unit(child));
+        }
+        let (start, end) = self.range(region);
+        // every change is towards fewer constructs, the bound only guards against going back and
+        // forth
+        for _ in start..=end {
+            let Err(error) = self.check(region) else { return };
+            let offset = error.span.start.offset;
+            // an error that stays at the same place is still the same error
+            if self.errors.last().is_none_or(|last| last.span.start.offset != offset) {
+                self.errors.push(error.clone());
+            }
+            if self.repair_comma(&error, end) {
+                continue;
+            }
+            match region {
+                Region::Unit(unit) => {
+                    let block = self.blocks[unit];
+                    let in_list = block.keyword == "par"
+                        && block.parent.is_some_and(|parent| self.blocks[parent].keyword == "list");
+                    let replacement = if in_list { ("par{''", "}") } else { ("tags{", "}") };
+                    self.blank(block.start, block.end, replacement);
+                    return;
+                },
+                Region::Top(..) => if !self.blank_top(offset, end) {
+                    break;
+                },
+            }
+        }
+        if self.check(region).is_err() {
+            self.blank(start, end, ("", ""));
+        }
+    }
+
+    /// Put in a comma when one is missing and there is room for it.
+    fn repair_comma(&mut self, error: &ParseError, end: usize) -> bool {
+        let offset = error.span.start.offset;
+        let ParseErrorKind::Syntax { expected, expected_tokens } = &error.kind else {
+            return false;
+        };
+        let repairable = (expected_tokens.iter().any(|token| token == ",")
+            || expected.contains(&Rule::EOI))
+            && offset > 0
+            && offset < end
+            && self.buffer[offset - 1].is_ascii_whitespace()
+            && self.tokens.iter().rev().find(|token| token.end <= offset)
+                .is_some_and(|token| matches!(token.kind, TokenKind::Close | TokenKind::Quoted));
+        if repairable {
+            self.buffer[offset - 1] = b',';
+        }
+        repairable
+    }
+
+    /// Leave out the top level construct that holds the error, or, when it is not inside any
+    /// construct, everything from the error up to the end of the region.
+    /// Returns whether anything changed.
+    fn blank_top(&mut self, offset: usize, end: usize) -> bool {
+        let before = self.buffer.clone();
+        if let Some(top) = self.blocks.iter()
+            .find(|block| block.parent.is_none() && block.contains(offset))
+        {
+            let (start, top_end) = (top.start, top.end.min(end));
+            self.blank(start, top_end, ("", ""));
+        } else if let Some(token) = self.tokens.iter().find(|token| token.end > offset)
+            && token.start < end
+        {
+            self.blank(token.start, end, ("", ""));
+        }
+        self.buffer != before
+    }
+
+    /// Blank the bytes, keeping the byte offsets and lines the same, and put the replacement at
+    /// both ends.
+    fn blank(&mut self, start: usize, end: usize, replacement: (&str, &str)) {
+        for b in &mut self.buffer[start..end] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+        let (head, tail) = replacement;
+        if !head.is_empty() && end - start >= head.len() + tail.len() {
+            self.buffer[start..start + head.len()].copy_from_slice(head.as_bytes());
+            self.buffer[end - tail.len()..end].copy_from_slice(tail.as_bytes());
+        } else if let Some(comma) = self.tokens.iter().find(|token| token.start >= end)
+            && comma.kind == TokenKind::Comma
+        {
+            // nothing took its place, take the comma after it along so no double comma is left
+            self.buffer[comma.start] = b' ';
+        }
+    }
+}
+
+/// Parse a recovery unit with its rule, checking its values as well.
+fn check_unit(ctx: &mut Context, rule: Rule, text: &str) -> Result<(), ParseError> {
+    let pairs = IncodocParser::parse(rule, text)
+        .map_err(|error| ParseError::from_pest(error, text))?;
+    let Some(pair) = pairs.into_iter().next() else { return Ok(()) };
+    match rule {
+        Rule::paragraph => parse_paragraph_in(ctx, pair).map(drop),
+        Rule::section => parse_section_in(ctx, 0, pair).map(drop),
+        Rule::quote => parse_quote(ctx, pair).map(drop),
+        Rule::inline_quote => parse_inline_quote(ctx, pair).map(drop),
+        Rule::footnote => parse_footnote(ctx, pair).map(drop),
+        Rule::nav_top => parse_nav(ctx, pair, true).map(drop),
+        Rule::nav => parse_nav(ctx, pair, false).map(drop),
+        Rule::table_header_row => parse_table_row(ctx, pair, true).map(drop),
+        _ => parse_table_row(ctx, pair, false).map(drop),
+    }
+}
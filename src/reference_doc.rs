@@ -15,6 +15,7 @@ pub const REF_DOC: &str = "
             tags { \"tag\" },
             props { (\"prop\", 0) },
         },
+        'text with an escaped \\' quote and an escaped \\\\ backslash',
         em { le, \"light emphasis\", tags { \"tag\" }, props { (\"prop\", 0) } },
         em { me, \"medium emphasis\", tags { \"tag\" }, props { (\"prop\", 0) } },
         em { se, \"strong emphasis\", tags { \"tag\" }, props { (\"prop\", 0) } },
@@ -26,6 +27,16 @@ pub const REF_DOC: &str = "
         code { \"rust\", \"auto\", 'let x = 0;', tags { \"tag\" }, props { (\"prop\", 0) } },
         code { \"rust\", \"replace\", 'let x = 0;', tags { \"tag\" }, props { (\"prop\", 0) } },
         link { \"url\", \"link string\", tags { \"tag\" }, props { (\"prop\", 0) } },
+        quote {
+            \"kind\",
+            'inline quote',
+            em { le, \"emphasised\" },
+            by { \"someone\" },
+            link { \"url\", \"source\" },
+            tags { \"tag\" },
+            props { (\"prop\", 0) },
+        },
+        fnref { \"note\", tags { \"tag\" }, props { (\"prop\", 0) } },
         list { dl, par { 'item 0' }, par { 'item 1' }, par { 'item 2' }, },
         list { il, par { 'item 0' }, par { 'item 1' }, par { 'item 2' }, },
         list { cl, par { 'item 0' }, par { 'item 1' }, par { 'item 2', tags { \"checked\" }, }, },
@@ -46,6 +57,14 @@ pub const REF_DOC: &str = "
             tags { \"tag\" },
             props { (\"prop\", 0) },
         },
+        data {
+            cols { \"year\", \"happiness\", \"mood\", \"sunny\" },
+            [0, 100.0, 'good', true],
+            [1, 90.5, 'fine', false],
+            [2, -1e-3, 'bad'],
+            tags { \"tag\" },
+            props { (\"type-hint\", \"line-graph\"), (\"prop\", 0) },
+        },
     },
     section {
         head {
@@ -68,6 +87,22 @@ pub const REF_DOC: &str = "
             }
         }
     },
+    quote {
+        \"kind\",
+        par { 'quoted paragraph' },
+        quote { par { 'nested quote' } },
+        by { \"someone \", em { le, \"emphasised\" } },
+        link { \"url\", \"source\" },
+        tags { \"tag\" },
+        props { (\"prop\", 0) },
+    },
+    footnote {
+        \"note\",
+        par { 'footnote paragraph' },
+        par { 'another paragraph' },
+        tags { \"tag\" },
+        props { (\"prop\", 0) },
+    },
     nav {
         nav {
             \"description A\",
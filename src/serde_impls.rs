@@ -0,0 +1,126 @@
+//! Serde support for the document model, enabled with the `serde` feature.
+//!
+//! The encoding is stable and looks like this in JSON:
+//!
+//! - Structs are maps keyed by their field names. Missing fields take their default value,
+//!   except in dates. Unknown fields are ignored, `json::from_json` is the one to reject them.
+//! - Enums are externally tagged: `{"Paragraph": {...}}`. Variants without data are plain
+//!   strings: `"Light"`.
+//! - Tags are a sorted list of strings. Props are a map sorted by key.
+//! - Dates are maps: `{"year": 2000, "month": 1, "day": 11}`.
+//! - Code is a `Result<CodeBlock, CodeIdentError>`: `{"Ok": {...}}` or `{"Err": null}`.
+//! - Props that could not be parsed hold their error: `{"Error": {"Int": "PosOverflow"}}` or
+//!   `{"Error": {"Date": {"MonthRange": 13}}}`. Errors from parsing an integer are one of
+//!   `"Empty"`, `"InvalidDigit"`, `"PosOverflow"`, `"NegOverflow"` and `"Zero"`.
+//! - Values in datasets are enums as well: `{"Float": 0.5}`. JSON has no infinity or NaN, those
+//!   floats are written as strings like in incodoc: `{"Float": "inf"}`, `"-inf"` or `"NaN"`.
+
+use crate::*;
+
+use std::{
+    collections::BTreeMap,
+    num::{ IntErrorKind, NonZeroU8 },
+};
+
+use serde::{ Serialize, Serializer };
+
+pub(crate) fn sorted_tags<S: Serializer>(tags: &Tags, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut tags: Vec<_> = tags.iter().collect();
+    tags.sort();
+    tags.serialize(serializer)
+}
+
+pub(crate) fn sorted_props<S: Serializer>(props: &Props, serializer: S) -> Result<S::Ok, S::Error> {
+    props.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// `ParseIntError` can not be built directly, so it is stored as its kind.
+pub(crate) mod parse_int_error {
+    use super::*;
+
+    use serde::{ Deserialize, Deserializer, de::Error };
+
+    const KINDS: [&str; 5] = ["Empty", "InvalidDigit", "PosOverflow", "NegOverflow", "Zero"];
+
+    pub(crate) fn serialize<S: Serializer>(
+        error: &ParseIntError, serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let kind = match error.kind() {
+            IntErrorKind::Empty => "Empty",
+            IntErrorKind::PosOverflow => "PosOverflow",
+            IntErrorKind::NegOverflow => "NegOverflow",
+            IntErrorKind::Zero => "Zero",
+            _ => "InvalidDigit",
+        };
+        serializer.serialize_str(kind)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ParseIntError, D::Error> {
+        let kind = String::deserialize(deserializer)?;
+        // make the error happen to get one of the right kind
+        let error = match kind.as_str() {
+            "Empty" => "".parse::<i8>().err(),
+            "InvalidDigit" => "x".parse::<i8>().err(),
+            "PosOverflow" => "128".parse::<i8>().err(),
+            "NegOverflow" => "-129".parse::<i8>().err(),
+            "Zero" => "0".parse::<NonZeroU8>().err(),
+            _ => None,
+        };
+        error.ok_or_else(|| D::Error::unknown_variant(&kind, &KINDS))
+    }
+}
+
+/// Floats that JSON can not hold are stored as a string, spelled like in incodoc.
+pub(crate) mod float {
+    use super::*;
+
+    use serde::{ Deserializer, de::{ Error, Visitor } };
+
+    use std::fmt;
+
+    pub(crate) fn serialize<S: Serializer>(float: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        match *float {
+            float if float.is_finite() => serializer.serialize_f64(float),
+            float if float.is_nan() => serializer.serialize_str("NaN"),
+            float if float > 0.0 => serializer.serialize_str("inf"),
+            _ => serializer.serialize_str("-inf"),
+        }
+    }
+
+    struct FloatVisitor;
+
+    impl Visitor<'_> for FloatVisitor {
+        type Value = f64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a number, \"inf\", \"-inf\" or \"NaN\"")
+        }
+
+        fn visit_f64<E: Error>(self, float: f64) -> Result<f64, E> {
+            Ok(float)
+        }
+
+        fn visit_i64<E: Error>(self, int: i64) -> Result<f64, E> {
+            Ok(int as f64)
+        }
+
+        fn visit_u64<E: Error>(self, int: u64) -> Result<f64, E> {
+            Ok(int as f64)
+        }
+
+        fn visit_str<E: Error>(self, text: &str) -> Result<f64, E> {
+            match text {
+                "inf" => Ok(f64::INFINITY),
+                "-inf" => Ok(f64::NEG_INFINITY),
+                "NaN" => Ok(f64::NAN),
+                _ => Err(E::unknown_variant(text, &["inf", "-inf", "NaN"])),
+            }
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod ansi {
+    use crate::parsing::parse;
+    use crate::export::ansi::*;
+
+    fn plain(width: usize) -> StyleProfile {
+        StyleProfile { width, colours: false, bullet: "-".to_string(), ..Default::default() }
+    }
+
+    fn render(input: &str, profile: &StyleProfile) -> String {
+        let mut output = String::new();
+        doc_to_ansi(&parse(input).unwrap(), profile, &mut output);
+        output
+    }
+
+    macro_rules! test_plain {
+        ($name:ident, $width:expr, $input:expr, $text:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(render($input, &plain($width)), $text);
+            }
+        }
+    }
+
+    test_plain!(
+        an_wrap,
+        12,
+        "par {
+            'the quick ',
+            em { me, \"brown fox\" },
+            ' jumps over\nthe extraordinarily lazy dog',
+        }",
+        "the quick\nbrown fox\njumps over\nthe\nextraordinarily\nlazy dog\n"
+    );
+
+    test_plain!(
+        an_sections,
+        80,
+        "section {
+            head { 0, \"Title\" },
+            par { 'a' },
+            section { head { 0, \"Sub\" }, par { 'b' } },
+        }",
+        "Title\n=====\n\na\n\nSub\n---\n\nb\n"
+    );
+
+    test_plain!(
+        an_lists,
+        14,
+        "par {
+            list { dl, par { 'one two three four' }, par { 'b', list { il, par { 'c' } } } },
+            list { cl, par { 'd' }, par { 'e', tags { \"checked\" } } },
+        }",
+        "\
+1. one two
+   three four
+2. b
+
+   - c
+
+[ ] d
+[x] e
+"
+    );
+
+    test_plain!(
+        an_table,
+        20,
+        "par { table {
+            throw { par { 'A' }, par { 'B' } },
+            trow { par { 'a' }, par { 'some longer text' } },
+        } }",
+        "\
+| A | B            |
+|---+--------------|
+| a | some longer  |
+|   | text         |
+"
+    );
+
+    test_plain!(
+        an_code_and_links,
+        80,
+        "par { link { \"u\", \"l\" }, code { \"rust\", \"show\", 'let x = 0;' } }",
+        "l <u>\n\n    let x = 0;\n"
+    );
+
+    test_plain!(
+        an_nav,
+        80,
+        "nav { nav { \"a\", link { \"u\", \"l\" }, nav { \"b\", link { \"v\", \"m\" } } } }",
+        "a\n- l <u>\n    b\n    - m <v>\n"
+    );
+
+    test_plain!(
+        an_control_characters,
+        80,
+        "par { 'a\x1b[2Jb\x07 c\u{9b}2J' }, par { code { \"sh\", \"show\", 'x\x1b[2J\ty' } }",
+        "a\u{fffd}[2Jb\u{fffd} c\u{fffd}2J\n\n    x\u{fffd}[2J\ty\n"
+    );
+
+    #[test]
+    fn an_colours() {
+        let profile = StyleProfile::default();
+        assert_eq!(
+            render("par { 'a ', em { le, \"b c\" }, ' ', em { se, \"d\" } }", &profile),
+            "a \x1b[3mb c\x1b[0m \x1b[1;4md\x1b[0m\n"
+        );
+        assert_eq!(
+            render("section { head { 0, \"h\" }, par { 'x' } }", &profile),
+            "\x1b[1;4mh\x1b[0m\n\nx\n"
+        );
+    }
+
+    #[test]
+    fn an_profile() {
+        let profile = StyleProfile::from_incodoc("props {
+            (\"width\", 40),
+            (\"colours\", \"off\"),
+            (\"light-emphasis\", 'bold bright-red'),
+            (\"bullet\", \"*\"),
+        }").unwrap();
+        assert_eq!(profile.width, 40);
+        assert!(!profile.colours);
+        assert_eq!(profile.emphasis[0], Style {
+            colour: Some(Colour::BrightRed),
+            bold: true,
+            ..Default::default()
+        });
+        assert_eq!(profile.bullet, "*");
+        assert_eq!(profile.heading, StyleProfile::default().heading);
+    }
+
+    #[test]
+    fn an_profile_errors() {
+        assert_eq!(
+            StyleProfile::from_incodoc("props { (\"colour\", \"on\") }"),
+            Err(ProfileError::UnknownKey("colour".to_string()))
+        );
+        assert_eq!(
+            StyleProfile::from_incodoc("props { (\"link\", \"blinking\") }"),
+            Err(ProfileError::InvalidValue("link".to_string()))
+        );
+        assert_eq!(
+            StyleProfile::from_incodoc("props { (\"width\", 0) }"),
+            Err(ProfileError::InvalidValue("width".to_string()))
+        );
+        assert!(matches!(StyleProfile::from_incodoc("props {"), Err(ProfileError::Parse(_))));
+    }
+
+    test_plain!(
+        an_quotes,
+        80,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "│ Tip\n│\n│ a\n│\n│ — Ada, notes <https://x.org>\n\nhe said “hi” (Bob).\n"
+    );
+
+    test_plain!(
+        an_footnotes,
+        80,
+        "par { 'a', fnref { \"x\" }, '.' },
+        footnote { \"x\", par { 'b' }, par { 'c' } }",
+        "a[x].\n\n[x] b\n\n    c\n"
+    );
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod comments {
+    use crate::*;
+    use crate::parsing::*;
+    use crate::output::*;
+
+    fn comment(text: &str, block: bool, position: usize) -> Comment {
+        Comment {
+            text: text.to_string(),
+            block,
+            position,
+        }
+    }
+
+    fn out(doc: &Doc) -> String {
+        let mut output = String::new();
+        doc_out(doc, &mut output);
+        output
+    }
+
+    const INPUT: &str = "\
+// header
+tags { \"a\" },
+nav {
+    link { \"url\", \"text\" },
+    /* between links */
+    link { \"url\", \"text\" },
+},
+par {
+    'a', // after a
+    em { le, /* inside em */ \"b\" },
+    list {
+        dl,
+        // first item
+        par { 'c' },
+    },
+},
+section {
+    head { 0, \"title\" },
+    // before paragraph
+    par { 'd' },
+},
+// footer
+";
+
+    #[test]
+    fn co_parse_ignores() {
+        let doc = parse(INPUT).unwrap();
+        assert!(doc.comments.is_empty());
+        assert_eq!(doc.items.len(), 2);
+    }
+
+    #[test]
+    fn co_attach() {
+        let doc = parse_with_comments(INPUT).unwrap();
+        assert_eq!(
+            doc.comments,
+            vec![comment(" header", false, 0), comment(" footer", false, 3)]
+        );
+        assert_eq!(doc.navs[0].comments, vec![comment(" between links ", true, 1)]);
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        assert_eq!(
+            par.comments,
+            vec![comment(" after a", false, 1), comment(" inside em ", true, 2)]
+        );
+        let ParagraphItem::List(list) = &par.items[2] else { panic!() };
+        assert_eq!(list.comments, vec![comment(" first item", false, 0)]);
+        let DocItem::Section(section) = &doc.items[1] else { panic!() };
+        assert_eq!(section.comments, vec![comment(" before paragraph", false, 1)]);
+        assert_eq!(
+            section.items[0],
+            SectionItem::Paragraph(Paragraph {
+                items: vec![ParagraphItem::Text("d".to_string())],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn co_table() {
+        let input = "\
+par {
+    table {
+        trow { par { 'e' }, /* end of row */ },
+        // last row
+        trow { par { 'f' } },
+    },
+},
+";
+        let doc = parse_with_comments(input).unwrap();
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        let ParagraphItem::Table(table) = &par.items[0] else { panic!() };
+        assert_eq!(table.comments, vec![comment(" last row", false, 1)]);
+        assert_eq!(table.rows[0].comments, vec![comment(" end of row ", true, 1)]);
+    }
+
+    #[test]
+    fn co_round_trip() {
+        let doc = parse_with_comments(INPUT).unwrap();
+        let output = out(&doc);
+        assert_eq!(parse_with_comments(&output), Ok(doc));
+        assert_eq!(out(&parse_with_comments(&output).unwrap()), output);
+        assert!(output.starts_with("// header\ntags {"));
+        assert!(output.ends_with("},\n// footer\n"));
+    }
+
+    #[test]
+    fn co_output_options() {
+        let doc = parse_with_comments(INPUT).unwrap();
+        let mut output = String::new();
+        doc_out_with(&doc, &OutputOptions { comments: false, ..Default::default() }, &mut output)
+            .unwrap();
+        assert_eq!(parse_with_comments(&output), parse(INPUT));
+    }
+
+    #[test]
+    fn co_not_comments() {
+        let doc = parse_with_comments("par { 'http://a /* b */', link { \"//c\", \"d\" } }").unwrap();
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        assert!(doc.comments.is_empty());
+        assert!(par.comments.is_empty());
+    }
+
+    #[test]
+    fn co_multi_line_block() {
+        let input = "/*\n * a\n * b\n */\npar { 'a' }";
+        let doc = parse_with_comments(input).unwrap();
+        assert_eq!(doc.comments, vec![comment("\n * a\n * b\n ", true, 0)]);
+        assert_eq!(out(&doc), "/*\n * a\n * b\n */\npar {\n    'a',\n},\n");
+    }
+
+    #[test]
+    fn co_glued() {
+        let doc = parse_with_comments("\
+props { (\"a\", 1// c1
+), (\"b\", 2000/01/01/* c2 */) },
+par { em { le// c3
+, \"x\" } },
+").unwrap();
+        assert_eq!(doc.comments, vec![comment(" c1", false, 0), comment(" c2 ", true, 0)]);
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        assert_eq!(par.comments, vec![comment(" c3", false, 1)]);
+        assert_eq!(parse_with_comments(&out(&doc)), Ok(doc));
+    }
+}
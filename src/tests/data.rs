@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod data {
+    use crate::*;
+    use crate::parsing::*;
+    use crate::output::doc_out;
+    use crate::actions::{ toc::*, prune::* };
+    use std::collections::{ HashMap, HashSet };
+
+    fn text_par(text: &str) -> Paragraph {
+        Paragraph {
+            items: vec![ParagraphItem::Text(text.to_string())],
+            ..Default::default()
+        }
+    }
+
+    fn out(doc: &Doc) -> String {
+        let mut output = String::new();
+        doc_out(doc, &mut output);
+        output
+    }
+
+    fn first_data(doc: &Doc) -> &Data {
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!("expected a paragraph") };
+        let ParagraphItem::Data(data) = &par.items[0] else { panic!("expected data") };
+        data
+    }
+
+    const DOC: &str = "\
+par {
+    data {
+        cols { \"year\", \"happiness\", \"note\", \"sunny\" },
+        [0, 100.0, 'start', true],
+        [1, 90.5, 'it\\'s fine', false],
+        [],
+        props {
+            (\"type-hint\", \"line-graph\"),
+        },
+    },
+},
+";
+
+    #[test]
+    fn dt_parse() {
+        let doc = parse(DOC).unwrap();
+        assert_eq!(
+            first_data(&doc),
+            &Data {
+                columns: vec![
+                    "year".to_string(),
+                    "happiness".to_string(),
+                    "note".to_string(),
+                    "sunny".to_string(),
+                ],
+                rows: vec![
+                    vec![
+                        DataCell::Int(0),
+                        DataCell::Float(100.0),
+                        DataCell::Text("start".to_string()),
+                        DataCell::Bool(true),
+                    ],
+                    vec![
+                        DataCell::Int(1),
+                        DataCell::Float(90.5),
+                        DataCell::Text("it's fine".to_string()),
+                        DataCell::Bool(false),
+                    ],
+                    vec![],
+                ],
+                props: props!([
+                    ("type-hint".to_string(), PropVal::String("line-graph".to_string()))
+                ]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn dt_parse_without_columns() {
+        let doc = parse("par { data { ['T', 'F'], ['F', 'F'], tags { \"truth\" } } }").unwrap();
+        assert_eq!(
+            first_data(&doc),
+            &Data {
+                rows: vec![
+                    vec![DataCell::Text("T".to_string()), DataCell::Text("F".to_string())],
+                    vec![DataCell::Text("F".to_string()), DataCell::Text("F".to_string())],
+                ],
+                tags: hset!(["truth"]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn dt_parse_errors() {
+        assert!(parse("par { data { } }").is_err());
+        assert!(parse("par { data { [0], cols { \"a\" } } }").is_err());
+        assert!(parse("par { data { [0 1] } }").is_err());
+        assert!(parse("par { data { [yes] } }").is_err());
+        assert!(parse("par { data { [1.] } }").is_err());
+        assert!(parse("par { data { [\"string\"] } }").is_err());
+        assert!(parse("data { [0] }").is_err());
+        let error = parse("par { data { [99999999999999999999] } }").unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::Int(_)));
+        for input in ["par { data { [1e400] } }", "par { data { [-1.5E+309] } }"] {
+            let error = parse(input).unwrap_err();
+            assert!(matches!(error.kind, ParseErrorKind::IllegalValue { .. }), "{input}");
+        }
+    }
+
+    #[test]
+    fn dt_floats() {
+        let doc = parse("par { data { [1e20, -0.0, +2.5E-3, inf, -inf, NaN, 7] } }").unwrap();
+        let row = &first_data(&doc).rows[0];
+        assert_eq!(row[0], DataCell::Float(1e20));
+        assert_eq!(row[1], DataCell::Float(-0.0));
+        assert_ne!(row[1], DataCell::Float(0.0));
+        assert_eq!(row[2], DataCell::Float(2.5e-3));
+        assert_eq!(row[3], DataCell::Float(f64::INFINITY));
+        assert_eq!(row[4], DataCell::Float(f64::NEG_INFINITY));
+        assert_eq!(row[5], DataCell::Float(f64::NAN));
+        assert_eq!(row[6], DataCell::Int(7));
+        assert_ne!(DataCell::Int(7), DataCell::Float(7.0));
+        assert_eq!(parse(&out(&doc)).unwrap(), doc);
+    }
+
+    #[test]
+    fn dt_round_trip() {
+        assert_eq!(out(&parse(DOC).unwrap()), DOC);
+        let empty = Doc {
+            items: vec![DocItem::Paragraph(Paragraph {
+                items: vec![ParagraphItem::Data(Data::default())],
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        assert_eq!(parse(&out(&empty)).unwrap(), empty);
+    }
+
+    #[test]
+    fn dt_comments() {
+        let doc = parse_with_comments("\
+par {
+    data {
+        cols { \"a\" },
+        // first
+        [0],
+        [1, /* inside */ 2],
+        // last
+    },
+}
+").unwrap();
+        assert_eq!(
+            first_data(&doc).comments,
+            // rows don't hold comments, so one in a row goes after it
+            vec![
+                Comment { text: " first".to_string(), block: false, position: 0 },
+                Comment { text: " inside ".to_string(), block: true, position: 2 },
+                Comment { text: " last".to_string(), block: false, position: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dt_spans() {
+        let (_, spans) = parse_with_spans("par { data { [0], [1] } }").unwrap();
+        let data = [NodeStep::Item(0), NodeStep::Item(0)];
+        assert!(spans.contains_key(&data[..]));
+        assert!(spans.contains_key(&[data[0], data[1], NodeStep::Row(1)][..]));
+    }
+
+    #[test]
+    fn dt_to_table() {
+        let doc = parse_with_comments("\
+par {
+    data {
+        cols { \"x\", \"y\" },
+        // values
+        [1, 2.5],
+        ['a', true],
+        tags { \"t\" },
+        props { (\"type-hint\", \"bar-chart\"), (\"id\", \"d\") },
+    },
+}
+").unwrap();
+        let row = |items: &[&str], is_header: bool| TableRow {
+            items: items.iter().map(|text| text_par(text)).collect(),
+            is_header,
+            ..Default::default()
+        };
+        assert_eq!(
+            first_data(&doc).to_table(),
+            Table {
+                rows: vec![
+                    row(&["x", "y"], true),
+                    row(&["1", "2.5"], false),
+                    row(&["a", "true"], false),
+                ],
+                tags: hset!(["t"]),
+                props: props!([("id".to_string(), PropVal::String("d".to_string()))]),
+                comments: vec![Comment { text: " values".to_string(), block: false, position: 1 }],
+            }
+        );
+        let doc = parse("par { data { [0, 1.0, false] } }").unwrap();
+        assert_eq!(first_data(&doc).to_table().rows, vec![row(&["0", "1", "false"], false)]);
+    }
+
+    #[test]
+    fn dt_prune() {
+        let mut doc = parse("par { data { [], [0], [] } }, par { 'a', data { cols { \"x\" } } }")
+            .unwrap();
+        doc.prune_contentless();
+        assert_eq!(doc, parse("par { data { [0] } }, par { 'a' }").unwrap());
+    }
+
+    #[test]
+    fn dt_toc() {
+        let doc = parse("par { data { [0], props { (\"id\", \"d\") } } }").unwrap();
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!("expected a paragraph") };
+        let filter = Some((
+            HashSet::from([TableOfContentsItemType::Paragraph, TableOfContentsItemType::Data]),
+            TableOfContentsFilterType::HardStop,
+        ));
+        assert_eq!(
+            par.get_table_of_contents(&filter).unwrap().children,
+            vec![TableOfContentsItem {
+                title: "d".to_string(),
+                link: "#d".to_string(),
+                item_type: TableOfContentsItemType::Data,
+                children: vec![],
+            }]
+        );
+    }
+}
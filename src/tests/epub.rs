@@ -0,0 +1,190 @@
+#[cfg(all(test, feature = "epub"))]
+mod epub {
+    use crate::parsing::parse;
+    use crate::export::epub::*;
+
+    use std::collections::HashSet;
+    use std::io::{ Cursor, Read };
+
+    use zip::{ CompressionMethod, ZipArchive };
+
+    type Archive = ZipArchive<Cursor<Vec<u8>>>;
+
+    fn book(inputs: &[&str], options: &EpubOptions) -> Archive {
+        let docs: Vec<_> = inputs.iter().map(|input| parse(input).unwrap()).collect();
+        let cursor = docs_to_epub(&docs, options, Cursor::new(Vec::new())).unwrap();
+        ZipArchive::new(Cursor::new(cursor.into_inner())).unwrap()
+    }
+
+    fn file(archive: &mut Archive, name: &str) -> String {
+        let mut res = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut res).unwrap();
+        res
+    }
+
+    /// Check that no attribute is there twice in an element.
+    fn unique_attributes(tag: &str) -> bool {
+        let mut names = HashSet::new();
+        // values are escaped, so the quotes only open and close them
+        let mut rest = tag.split_once(char::is_whitespace).map(|(_, rest)| rest).unwrap_or("");
+        while let Some((name, value)) = rest.split_once("=\"") {
+            if !names.insert(name.trim()) {
+                return false;
+            }
+            rest = value.split_once('"').map(|(_, rest)| rest).unwrap_or("");
+        }
+        true
+    }
+
+    /// Check that all elements are closed in the order they are opened and that their attributes
+    /// are unique.
+    fn well_formed(xml: &str) -> bool {
+        let mut open = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let Some(end) = rest[start..].find('>') else { return false; };
+            let tag = &rest[start + 1..start + end];
+            rest = &rest[start + end + 1..];
+            if !tag.starts_with('!') && !unique_attributes(tag) {
+                return false;
+            }
+            if tag.starts_with('?') || tag.starts_with('!') || tag.ends_with('/') {
+                continue;
+            }
+            let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("");
+            if tag.starts_with('/') {
+                if open.pop() != Some(name.to_string()) {
+                    return false;
+                }
+            } else {
+                open.push(name.to_string());
+            }
+        }
+        open.is_empty()
+    }
+
+    const CHAPTER: &str = "
+        props { (\"title\", 'One'), (\"language\", \"nl\"), (\"date\", 2024/05/01) },
+        section {
+            head { 0, \"First\" },
+            par { 'a', list { cl, par { 'b', tags { \"checked\" } } } },
+            section { head { 0, \"Sub section\" }, par { 'c' } },
+        },
+    ";
+
+    #[test]
+    fn ep_container() {
+        let mut archive = book(&[CHAPTER], &EpubOptions::default());
+        let first = archive.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), CompressionMethod::Stored);
+        drop(first);
+        assert_eq!(file(&mut archive, "mimetype"), "application/epub+zip");
+        assert!(file(&mut archive, "META-INF/container.xml")
+            .contains("full-path=\"OEBPS/content.opf\""));
+        let names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        let xml = names.iter().filter(|name| name.ends_with("ml") || name.ends_with(".opf"));
+        for name in xml {
+            assert!(well_formed(&file(&mut archive, name)), "{name} is not well-formed");
+        }
+        assert!(!names.contains(&"OEBPS/style.css".to_string()));
+    }
+
+    #[test]
+    fn ep_well_formed_meta() {
+        let input = "props { (\"A\", 1), (\"a\", 2), (\"a-2\", 3), (\"tags\", 4) },
+            tags { \"t\" },
+            par { quote { \"note\", 'q', props { (\"Kind\", \"k\") } } },";
+        let mut archive = book(&[input], &EpubOptions::default());
+        let chapter = file(&mut archive, "OEBPS/chapter-1.xhtml");
+        assert!(well_formed(&chapter), "{chapter}");
+        assert!(chapter.contains(
+            "<body data-tags=\"t\" data-a=\"1\" data-a-2=\"2\" data-a-2-2=\"3\" data-tags-2=\"4\">"
+        ));
+        assert!(chapter.contains("data-kind=\"note\" data-kind-2=\"k\""));
+        assert!(!well_formed("<p data-a=\"1\" data-a=\"2\"></p>"));
+    }
+
+    #[test]
+    fn ep_metadata() {
+        let options = EpubOptions {
+            identifier: Some("urn:isbn:123".to_string()),
+            ..Default::default()
+        };
+        let mut archive = book(&[CHAPTER], &options);
+        let opf = file(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("<dc:identifier id=\"book-id\">urn:isbn:123</dc:identifier>"));
+        assert!(opf.contains("<dc:title>One</dc:title>"));
+        assert!(opf.contains("<dc:language>nl</dc:language>"));
+        assert!(opf.contains("<dc:date>2024-05-01</dc:date>"));
+        assert!(opf.contains("<meta property=\"dcterms:modified\">2024-05-01T00:00:00Z</meta>"));
+        assert!(opf.contains(
+            "href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\""
+        ));
+        assert!(opf.contains("<itemref idref=\"chapter-1\"/>"));
+    }
+
+    #[test]
+    fn ep_navigation() {
+        let second = "section { head { 0, \"Other\" }, par { 'd' }, props { (\"id\", \"o\") } },";
+        let mut archive = book(&[CHAPTER, second, "par { 'e' }"], &EpubOptions::default());
+        let nav = file(&mut archive, "OEBPS/nav.xhtml");
+        assert!(nav.contains("<nav epub:type=\"toc\" id=\"toc\">"));
+        assert!(nav.contains("<li><a href=\"chapter-1.xhtml\">One</a>"));
+        assert!(nav.contains("<li><a href=\"chapter-1.xhtml#first\">First</a>"));
+        assert!(nav.contains("<li><a href=\"chapter-1.xhtml#sub-section\">Sub section</a></li>"));
+        assert!(nav.contains("<li><a href=\"chapter-2.xhtml\">Other</a>"));
+        assert!(nav.contains("<li><a href=\"chapter-2.xhtml#o\">Other</a></li>"));
+        assert!(nav.contains("<li><a href=\"chapter-3.xhtml\">Chapter 3</a></li>"));
+        let chapter = file(&mut archive, "OEBPS/chapter-1.xhtml");
+        assert!(chapter.contains("<section id=\"sub-section\">"));
+        assert!(chapter.contains("xml:lang=\"nl\""));
+        let opf = file(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("<itemref idref=\"chapter-3\"/>"));
+    }
+
+    #[test]
+    fn ep_images() {
+        let dir = std::env::temp_dir().join(format!("incodoc-epub-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cat.png"), b"not really a png").unwrap();
+        let input = "par {
+            link { \"cat.png\", \"A cat\", props { (\"type-hint\", 'image') } },
+            link { \"cat.png\", \"The cat\", props { (\"type-hint\", 'image') } },
+            link { \"https://a.org/dog.png\", \"A dog\", props { (\"type-hint\", 'image') } },
+        }";
+        let options = EpubOptions {
+            stylesheet: true,
+            resource_dir: dir.clone(),
+            ..Default::default()
+        };
+        let mut archive = book(&[input], &options);
+        assert_eq!(file(&mut archive, "OEBPS/images/image-1.png"), "not really a png");
+        assert!(archive.by_name("OEBPS/images/image-2.png").is_err());
+        let opf = file(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("href=\"images/image-1.png\" media-type=\"image/png\""));
+        assert!(opf.contains("href=\"style.css\" media-type=\"text/css\""));
+        let chapter = file(&mut archive, "OEBPS/chapter-1.xhtml");
+        assert!(chapter.contains("<img src=\"images/image-1.png\" alt=\"A cat\""));
+        assert!(chapter.contains("<img src=\"https://a.org/dog.png\""));
+        assert!(chapter.contains("href=\"style.css\""));
+        assert!(!file(&mut archive, "OEBPS/style.css").is_empty());
+
+        let missing = "par { link { \"dog.png\", \"d\", props { (\"type-hint\", 'image') } } }";
+        let missing = parse(missing).unwrap();
+        let res = doc_to_epub(&missing, &options, Cursor::new(Vec::new()));
+        assert!(matches!(res, Err(EpubError::Image { url, .. }) if url == "dog.png"));
+        for url in ["../cat.png", "a/../../cat.png", "/etc/cat.png", "file:/etc/cat.png"] {
+            let outside = format!(
+                "par {{ link {{ \"{url}\", \"c\", props {{ (\"type-hint\", 'image') }} }} }}"
+            );
+            let res = doc_to_epub(&parse(&outside).unwrap(), &options, Cursor::new(Vec::new()));
+            assert!(matches!(res, Err(EpubError::ImageOutside { .. })), "{url}");
+        }
+        let inside = parse(
+            "par { link { \"./cat.png\", \"c\", props { (\"type-hint\", 'image') } } }"
+        ).unwrap();
+        assert!(doc_to_epub(&inside, &options, Cursor::new(Vec::new())).is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+#[cfg(test)]
+mod footnote {
+    use crate::*;
+    use crate::parsing::*;
+    use crate::output::doc_out;
+    use crate::actions::{ toc::*, prune::*, footnotes::* };
+    use std::collections::{ HashMap, HashSet };
+
+    fn text_par(text: &str) -> Paragraph {
+        Paragraph {
+            items: vec![ParagraphItem::Text(text.to_string())],
+            ..Default::default()
+        }
+    }
+
+    fn out(doc: &Doc) -> String {
+        let mut output = String::new();
+        doc_out(doc, &mut output);
+        output
+    }
+
+    const DOC: &str = "\
+par {
+    'a',
+    fnref {
+        \"x\",
+        props {
+            (\"id\", \"r\"),
+        },
+    },
+},
+footnote {
+    \"x\",
+    par {
+        'b',
+    },
+    tags {
+        \"t\",
+    },
+},
+";
+
+    #[test]
+    fn ft_parse() {
+        let doc = parse(DOC).unwrap();
+        assert_eq!(
+            doc.items,
+            vec![
+                DocItem::Paragraph(Paragraph {
+                    items: vec![
+                        ParagraphItem::Text("a".to_string()),
+                        ParagraphItem::FootnoteRef(FootnoteRef {
+                            label: "x".to_string(),
+                            props: props!([("id".to_string(), PropVal::String("r".to_string()))]),
+                            ..Default::default()
+                        }),
+                    ],
+                    ..Default::default()
+                }),
+                DocItem::Footnote(FootnoteDef {
+                    label: "x".to_string(),
+                    items: vec![text_par("b")],
+                    tags: hset!(["t"]),
+                    ..Default::default()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn ft_parse_in_section() {
+        let doc = parse("section { head { 0, \"s\" }, footnote { \"x\", par { 'a' } } }").unwrap();
+        let DocItem::Section(section) = &doc.items[0] else { panic!("expected a section") };
+        assert_eq!(
+            section.items,
+            vec![SectionItem::Footnote(FootnoteDef {
+                label: "x".to_string(),
+                items: vec![text_par("a")],
+                ..Default::default()
+            })]
+        );
+    }
+
+    #[test]
+    fn ft_parse_errors() {
+        assert!(parse("footnote { \"x\" }").is_err());
+        assert!(parse("footnote { par { 'a' } }").is_err());
+        assert!(parse("footnote { \"x\", quote { par { 'a' } } }").is_err());
+        assert!(parse("par { fnref { } }").is_err());
+    }
+
+    #[test]
+    fn ft_round_trip() {
+        assert_eq!(out(&parse(DOC).unwrap()), DOC);
+    }
+
+    macro_rules! test_migrate {
+        ($name:ident, $input:expr, $output:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse($input).unwrap(), parse($output).unwrap());
+                assert_eq!(out(&parse($input).unwrap()), out(&parse($output).unwrap()));
+            }
+        }
+    }
+
+    test_migrate!(
+        ft_migrate_id,
+        "par { 'a', link { \"#footnote-x\", \"x\", tags { \"footnote-ref\", \"t\" } } },
+        section {
+            head { 0, \"Notes\" },
+            section {
+                head { 0, \"x\" },
+                par { 'b' },
+                tags { \"footnote-def\" },
+                props { (\"id\", \"footnote-x\"), (\"p\", 0) },
+            },
+        }",
+        "par { 'a', fnref { \"x\", tags { \"t\" } } },
+        section { head { 0, \"Notes\" }, footnote { \"x\", par { 'b' }, props { (\"p\", 0) } } }"
+    );
+
+    test_migrate!(
+        ft_migrate_heading,
+        "section { head { 0, \"y\" }, par { 'b' }, tags { \"footnote-def\" } }",
+        "footnote { \"y\", par { 'b' } }"
+    );
+
+    test_migrate!(
+        ft_migrate_nested_refs,
+        "par { list { il, par { link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } } } } },
+        quote { par { link { \"#footnote-y\", \"y\", tags { \"footnote-ref\" } } } }",
+        "par { list { il, par { fnref { \"x\" } } } },
+        quote { par { fnref { \"y\" } } }"
+    );
+
+    #[test]
+    fn ft_migrate_keeps_other_content() {
+        let doc = parse("\
+par { link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } } },
+par { link { \"#other\", \"o\", tags { \"footnote-ref\" } } },
+section {
+    head { 0, \"x\" },
+    section { head { 0, \"s\" }, par { 'a' } },
+    tags { \"footnote-def\" },
+    props { (\"id\", \"footnote-x\") },
+},
+").unwrap();
+        // the definition stays a section, so the link to it stays a link
+        assert!(matches!(&doc.items[0], DocItem::Paragraph(par)
+            if matches!(par.items[0], ParagraphItem::Link(_))));
+        assert!(matches!(&doc.items[1], DocItem::Paragraph(par)
+            if matches!(par.items[0], ParagraphItem::Link(_))));
+        assert!(matches!(doc.items[2], DocItem::Section(_)));
+    }
+
+    #[test]
+    fn ft_migrate_keeps_empty() {
+        let doc = parse("section { head { 0, \"a\" }, tags { \"footnote-def\" } }").unwrap();
+        assert!(matches!(doc.items[0], DocItem::Section(_)));
+        assert_eq!(parse(&out(&doc)).unwrap(), doc);
+    }
+
+    #[test]
+    fn ft_migrate_keeps_headings() {
+        let doc = parse("\
+par { link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } } },
+section {
+    head { 1, \"Notes\", em { le, \"x\" } },
+    par { 'a' },
+    tags { \"footnote-def\" },
+    props { (\"id\", \"footnote-x\") },
+},
+section { head { 0, \"y\", props { (\"p\", 0) } }, par { 'b' }, tags { \"footnote-def\" } },
+").unwrap();
+        assert!(matches!(&doc.items[0], DocItem::Paragraph(par)
+            if matches!(par.items[0], ParagraphItem::Link(_))));
+        assert!(matches!(doc.items[1], DocItem::Section(_)));
+        assert!(matches!(doc.items[2], DocItem::Section(_)));
+    }
+
+    #[test]
+    fn ft_migrate_comments() {
+        let doc = parse_with_comments("\
+section {
+    head { 0, \"x\" },
+    // first
+    par { 'a' },
+    // last
+    tags { \"footnote-def\" },
+}
+").unwrap();
+        let DocItem::Footnote(footnote) = &doc.items[0] else { panic!("expected a footnote") };
+        assert_eq!(
+            footnote.comments,
+            vec![
+                Comment { text: " first".to_string(), block: false, position: 0 },
+                Comment { text: " last".to_string(), block: false, position: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn ft_migrate_spans() {
+        let input = "\
+par { link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } } },
+section { head { 0, \"x\" }, par { 'a' }, tags { \"footnote-def\" } },
+";
+        let (doc, spans) = parse_with_spans(input).unwrap();
+        assert!(matches!(doc.items[1], DocItem::Footnote(_)));
+        let contains = |path: &[NodeStep]| spans.contains_key(path);
+        assert!(contains(&[NodeStep::Item(0), NodeStep::Item(0)]));
+        assert!(!contains(&[NodeStep::Item(0), NodeStep::Item(0), NodeStep::Item(0)]));
+        assert!(contains(&[NodeStep::Item(1), NodeStep::Item(0)]));
+        assert!(!contains(&[NodeStep::Item(1), NodeStep::Heading]));
+    }
+
+    const RESOLVE: &str = "\
+par { 'a', fnref { \"b\" }, fnref { \"missing\" }, fnref { \"a\" }, fnref { \"b\" } },
+footnote { \"unused\", par { 'u' } },
+section {
+    head { 0, \"s\" },
+    par { 's' },
+    footnote { \"a\", par { 'x', fnref { \"c\" } } },
+    footnote { \"a\", par { 'again' } },
+},
+footnote { \"b\", par { 'y' } },
+footnote { \"c\", par { 'z' } },
+";
+
+    #[test]
+    fn ft_resolve() {
+        let doc = parse(RESOLVE).unwrap();
+        let footnotes = doc.resolve_footnotes();
+        let labels: Vec<&str> = footnotes.definitions.iter()
+            .map(|def| def.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["b", "a", "c", "unused"]);
+        assert_eq!(footnotes.number("b"), Some(1));
+        assert_eq!(footnotes.number("unused"), Some(4));
+        assert_eq!(footnotes.number("missing"), None);
+        assert_eq!(footnotes.definition("a").unwrap().items[0].items.len(), 2);
+        assert_eq!(footnotes.definition("b").unwrap().items, vec![text_par("y")]);
+        assert_eq!(footnotes.dangling, vec!["missing".to_string()]);
+        assert_eq!(footnotes.unused, vec!["unused".to_string()]);
+        assert_eq!(footnotes.duplicates, vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn ft_resolve_empty() {
+        let doc = parse("par { 'a' }").unwrap();
+        assert_eq!(doc.resolve_footnotes(), Footnotes::default());
+    }
+
+    #[test]
+    fn ft_place_defined() {
+        let mut doc = parse(RESOLVE).unwrap();
+        doc.place_footnotes(FootnotePlacement::Defined);
+        assert_eq!(doc, parse(RESOLVE).unwrap());
+    }
+
+    #[test]
+    fn ft_place_end() {
+        let mut doc = parse(RESOLVE).unwrap();
+        doc.place_footnotes(FootnotePlacement::End);
+        assert_eq!(doc, parse("\
+par { 'a', fnref { \"b\" }, fnref { \"missing\" }, fnref { \"a\" }, fnref { \"b\" } },
+section { head { 0, \"s\" }, par { 's' } },
+footnote { \"b\", par { 'y' } },
+footnote { \"a\", par { 'x', fnref { \"c\" } } },
+footnote { \"a\", par { 'again' } },
+footnote { \"c\", par { 'z' } },
+footnote { \"unused\", par { 'u' } },
+").unwrap());
+    }
+
+    #[test]
+    fn ft_place_section() {
+        let mut doc = parse("\
+section { head { 0, \"one\" }, par { fnref { \"a\" } } },
+section { head { 0, \"two\" }, par { fnref { \"b\" } }, footnote { \"a\", par { 'x' } } },
+footnote { \"b\", par { 'y' } },
+par { fnref { \"c\" } },
+footnote { \"c\", par { 'z' } },
+footnote { \"unused\", par { 'u' } },
+").unwrap();
+        doc.place_footnotes(FootnotePlacement::Section);
+        assert_eq!(doc, parse("\
+section { head { 0, \"one\" }, par { fnref { \"a\" } }, footnote { \"a\", par { 'x' } } },
+section { head { 0, \"two\" }, par { fnref { \"b\" } }, footnote { \"b\", par { 'y' } } },
+par { fnref { \"c\" } },
+footnote { \"c\", par { 'z' } },
+footnote { \"unused\", par { 'u' } },
+").unwrap());
+    }
+
+    #[test]
+    fn ft_place_comments() {
+        let mut doc = parse_with_comments("\
+footnote { \"a\", par { 'x' } },
+// before the paragraph
+par { fnref { \"a\" } },
+").unwrap();
+        doc.place_footnotes(FootnotePlacement::End);
+        assert_eq!(
+            out(&doc),
+            "\
+// before the paragraph
+par {
+    fnref {
+        \"a\",
+    },
+},
+footnote {
+    \"a\",
+    par {
+        'x',
+    },
+},
+"
+        );
+    }
+
+    #[test]
+    fn ft_prune() {
+        let mut doc = parse("\
+footnote { \"a\", par { '  ' } },
+footnote { \" \", par { 'b' } },
+footnote { \"c\", par { 'c' } },
+par { fnref { \" \" } },
+").unwrap();
+        doc.prune_contentless();
+        assert_eq!(doc, parse("footnote { \"c\", par { 'c' } }").unwrap());
+    }
+
+    #[test]
+    fn ft_squash() {
+        let mut doc = parse("footnote { \"a\", par { 'b', 'c' } }").unwrap();
+        doc.squash();
+        assert_eq!(doc, parse("footnote { \"a\", par { 'bc' } }").unwrap());
+    }
+
+    #[test]
+    fn ft_links() {
+        let mut doc = parse("footnote { \"a\", par { link { \"u\", \"l\" } } }").unwrap();
+        assert_eq!(doc.links_mut(false).len(), 1);
+    }
+
+    #[test]
+    fn ft_toc() {
+        let doc = parse("\
+par { fnref { \"a\", props { (\"id\", \"ref-a\") } }, fnref { \"b\" } },
+footnote { \"a\", par { 'x' } },
+footnote { \"b\", par { 'y' }, props { (\"id\", \"note-b\") } },
+").unwrap();
+        let toc = doc.get_table_of_contents(&None).unwrap();
+        let titles: Vec<(&str, &str)> = toc.children.iter()
+            .map(|item| (item.title.as_str(), item.link.as_str()))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("paragraph", ""),
+                ("Footnote definition: a", "#footnote-a"),
+                ("Footnote definition: b", "#note-b"),
+            ]
+        );
+        assert_eq!(toc.children[0].children[0].title, "a");
+        assert_eq!(toc.children[0].children[0].link, "#ref-a");
+        assert_eq!(
+            toc.children[0].children[0].item_type,
+            TableOfContentsItemType::FootnoteReference
+        );
+        let definitions = Some((
+            HashSet::from([TableOfContentsItemType::FootnoteDefinition]),
+            TableOfContentsFilterType::IncludeWithChildren,
+        ));
+        assert_eq!(doc.get_table_of_contents(&definitions).unwrap().children.len(), 2);
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod format {
+    use crate::format::*;
+    use crate::output::OutputOptions;
+
+    macro_rules! test_format {
+        ($name:ident, $input:expr, $output:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(format($input, &OutputOptions::default()), $output);
+            }
+        }
+    }
+
+    test_format!(
+        fo_formats,
+        "par{'a',em{le,\"b\"}}",
+        Ok("par {\n    'a',\n    em {\n        le,\n        \"b\",\n    },\n},\n".to_string())
+    );
+
+    test_format!(
+        fo_formatted,
+        "par {\n    'a',\n},\n",
+        Ok("par {\n    'a',\n},\n".to_string())
+    );
+
+    test_format!(
+        fo_line_comment,
+        "// comment\npar { 'a' }",
+        Ok("// comment\npar {\n    'a',\n},\n".to_string())
+    );
+
+    test_format!(
+        fo_block_comment,
+        "par { /* comment */ 'a' }",
+        Ok("par {\n    /* comment */\n    'a',\n},\n".to_string())
+    );
+
+    test_format!(
+        fo_not_a_comment,
+        "par { 'http://url /* */' }",
+        Ok("par {\n    'http://url /* */',\n},\n".to_string())
+    );
+
+    test_format!(
+        fo_prop_error,
+        "props { (\"date\", 2000/13/01) }",
+        Err(FormatError::Lossy)
+    );
+
+    test_format!(
+        fo_glued_comment,
+        "par { em { le// c1\n, \"x\" } }",
+        Ok("par {\n    em {\n        le,\n        \"x\",\n    },\n    // c1\n},\n".to_string())
+    );
+
+    #[test]
+    fn fo_code_ident_error() {
+        let input = "par {\n    code {\n        \"rust\",\n        \"show\",\n        '\n    let x = 0;\n        ',\n    },\n}";
+        assert_eq!(format(input, &OutputOptions::default()), Err(FormatError::Lossy));
+    }
+
+    #[test]
+    fn fo_parse_error() {
+        assert!(matches!(
+            format("par { 'a',, }", &OutputOptions::default()),
+            Err(FormatError::Parse(_))
+        ));
+    }
+}
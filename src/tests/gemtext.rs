@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod gemtext {
+    use crate::parsing::parse;
+    use crate::import::gemtext::parse_gemtext;
+    use crate::export::gemtext::doc_to_gemtext;
+
+    macro_rules! test_import {
+        ($name:ident, $gemtext:expr, $incodoc:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse_gemtext($gemtext), parse($incodoc).unwrap());
+            }
+        }
+    }
+
+    macro_rules! test_export {
+        ($name:ident, $incodoc:expr, $gemtext:expr) => {
+            #[test]
+            fn $name() {
+                let mut output = String::new();
+                doc_to_gemtext(&parse($incodoc).unwrap(), &mut output);
+                assert_eq!(output, $gemtext);
+            }
+        }
+    }
+
+    test_import!(
+        gm_lines,
+        "a\nb\n\n* c\n* d\n=> gemini://x.org x\n=>/y\ne",
+        "par { 'a' },
+        par { 'b' },
+        par { list { il, par { 'c' }, par { 'd' } } },
+        par { link { \"gemini://x.org\", \"x\" } },
+        par { link { \"/y\", \"/y\" } },
+        par { 'e' },"
+    );
+
+    test_import!(
+        gm_bare_link_line,
+        "=>\n=>  \na",
+        "par { '=>' }, par { '=>' }, par { 'a' },"
+    );
+
+    test_import!(
+        gm_empty,
+        "> \n>\na\n# b\n# c\n* ",
+        "par { 'a' },
+        section { head { 0, \"b\" }, par { '' } },
+        section { head { 0, \"c\" }, par { list { il, par { '' } } } },"
+    );
+
+    #[test]
+    fn gm_empty_round_trip() {
+        for input in ["> ", "# a\n# b", "# a\n## b", "* "] {
+            let doc = parse_gemtext(input);
+            let mut output = String::new();
+            crate::output::doc_out(&doc, &mut output);
+            assert_eq!(parse(&output), Ok(doc), "{input}");
+        }
+    }
+
+    test_import!(
+        gm_sections,
+        "p\n# a\nx\n### b\ny\n## c\nz\n# d\nw",
+        "par { 'p' },
+        section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, par { 'y' } },
+            section { head { 0, \"c\" }, par { 'z' } },
+        },
+        section { head { 0, \"d\" }, par { 'w' } },"
+    );
+
+    test_import!(
+        gm_preformatted,
+        "```rust\n# not a heading\n```\n```\nplain\n```",
+        "par { code { \"rust\", \"show\", '# not a heading' } },
+        par { code { \"plain\", \"show\", 'plain' } },"
+    );
+
+    test_import!(
+        gm_quote,
+        "# a\n> b\n>c\n>\n> d\ne",
+        "section {
+            head { 0, \"a\" },
+            quote { par { 'b\nc' }, par { 'd' } },
+            par { 'e' },
+        },"
+    );
+
+    test_export!(
+        gx_text,
+        "par { 'a ', em { le, \"b\" }, link { \"u\", \" c\" }, 'x\n# d' },
+        par { link { \"v\", \"e\" }, ' ', link { \"w\", \"w\" } },
+        par { link { \"x\", \"f\" } },",
+        "a b cx\n # d\n=> u c\n\n=> v e\n=> w\n=> x f\n"
+    );
+
+    test_export!(
+        gx_sections,
+        "section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, section { head { 0, \"c\" }, section {
+                head { 0, \"d\" }, par { 'y' }
+            } } },
+        },",
+        "# a\n\nx\n\n## b\n\n### c\n\n### d\n\ny\n"
+    );
+
+    test_export!(
+        gx_lists,
+        "par {
+            list { dl, par { 'a', link { \"u\", \" l\" } }, par { 'b', list { il, par { 'c' } } } },
+            list { cl, par { 'd' }, par { 'e', tags { \"checked\" } } },
+        }",
+        "* 1. a l\n=> u l\n* 2. b\n*   c\n\n* [ ] d\n* [x] e\n"
+    );
+
+    test_export!(
+        gx_table,
+        "par { table {
+            throw { par { 'A' }, par { 'B' } },
+            trow { par { 'aa' }, par { em { le, \"b\" } } },
+            trow { par { 'c' } },
+        } }",
+        "```table\nA  | B\n---+--\naa | b\nc\n```\n"
+    );
+
+    test_export!(
+        gx_quote,
+        "section {
+            head { 0, \"Quote\" },
+            par { 'a\nb' },
+            par { 'c', link { \"u\", \"l\" }, code { \"rust\", \"show\", 'x' } },
+            tags { \"blockquote\" },
+        },
+        section { head { 0, \"Note\" }, par { 'd' }, tags { \"blockquote-typed\" } },",
+        "> a\n> b\n>\n> cl\n\n=> u l\n```rust\nx\n```\n\n> Note\n>\n> d\n"
+    );
+
+    test_export!(
+        gx_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "> Tip\n>\n> a\n> — Ada\n\n=> https://x.org notes\n\nhe said “hi” (Bob).\n"
+    );
+
+    test_export!(
+        gx_nav,
+        "nav { nav { \"Site\", link { \"/\", \"home\" } } }, par { 'a' },",
+        "Site\n=> / home\n\na\n"
+    );
+
+    #[test]
+    fn gm_round_trip() {
+        let gemtext = "# Title\n\nSome text.\n\n=> gemini://a.org A\n=> /b\n\n* one\n* two\n\n\
+            ## Sub\n\n> quoted\n> more\n\n```sh\nls -la\n```\n";
+        let mut output = String::new();
+        doc_to_gemtext(&parse_gemtext(gemtext), &mut output);
+        assert_eq!(output, gemtext);
+    }
+
+    test_export!(
+        gx_footnotes,
+        "par { 'a', fnref { \"x\" }, '.' },
+        footnote { \"x\", par { 'b' }, par { 'c' } }",
+        "a[x].\n\n[x] b\n\nc\n"
+    );
+}
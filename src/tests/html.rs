@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod html {
+    use crate::parsing::parse;
+    use crate::export::html::{ doc_to_html, doc_body_to_html };
+
+    fn body(input: &str) -> String {
+        let mut output = String::new();
+        doc_body_to_html(&parse(input).unwrap(), &mut output);
+        output
+    }
+
+    macro_rules! test_body {
+        ($name:ident, $input:expr, $html:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(body($input), $html);
+            }
+        }
+    }
+
+    test_body!(
+        ht_escape,
+        "par { '<a href=\"x\">&\\'</a>' }",
+        "<p>&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;</p>\n"
+    );
+
+    test_body!(
+        ht_escape_attributes,
+        "par { link { \"a?b=1&c=\\\"2\\\"\", \"l\", props { (\"t\", '<\"') } } }",
+        "<p><a href=\"a?b=1&amp;c=&quot;2&quot;\" data-t=\"&lt;&quot;\">l</a></p>\n"
+    );
+
+    test_body!(
+        ht_image,
+        "par { link { \"cat.png\", \"A cat\", props { (\"type-hint\", 'image') } } }",
+        "<p><img src=\"cat.png\" alt=\"A cat\" data-type-hint=\"image\" /></p>\n"
+    );
+
+    test_body!(
+        ht_meta,
+        "par {
+            'a',
+            tags { \"b\", \"a\" },
+            props {
+                (\"id\", \"top\"),
+                (\"Some Prop\", 1),
+                (\"date\", 2000/01/02),
+                (\"bad\", 2000/13/01),
+            },
+        }",
+        "<p id=\"top\" data-tags=\"a b\" data-some-prop=\"1\" data-date=\"2000-01-02\">a</p>\n"
+    );
+
+    test_body!(
+        ht_sections,
+        "section {
+            head { 0, \"a \", em { me, \"b\" } },
+            par { 'x' },
+            section { head { 1, \"c\" }, par { 'y' }, props { (\"id\", \"c\") } },
+            section { head { 9, \"d\" }, par { 'z' } }
+        }",
+        "\
+<section>
+<h1>a <strong>b</strong></h1>
+<p>x</p>
+<section id=\"c\">
+<h3>c</h3>
+<p>y</p>
+</section>
+<section>
+<h6>d</h6>
+<p>z</p>
+</section>
+</section>
+"
+    );
+
+    test_body!(
+        ht_lists,
+        "par {
+            list { dl, par { 'a' } },
+            list { cl, par { 'b' }, par { 'c', tags { \"checked\" } } },
+        }",
+        "\
+<div>
+<ol>
+<li>a</li>
+</ol>
+<ul>
+<li><input type=\"checkbox\" disabled=\"disabled\" /> b</li>
+<li data-tags=\"checked\">\
+<input type=\"checkbox\" disabled=\"disabled\" checked=\"checked\" /> c</li>
+</ul>
+</div>
+"
+    );
+
+    test_body!(
+        ht_nested_blocks,
+        "par { 'a', list { il, par { 'b', list { il, par { 'c' } }, 'd' } }, 'e' }",
+        "\
+<div>
+<p>a</p>
+<ul>
+<li>
+<p>b</p>
+<ul>
+<li>c</li>
+</ul>
+<p>d</p>
+</li>
+</ul>
+<p>e</p>
+</div>
+"
+    );
+
+    test_body!(
+        ht_table,
+        "par { table { throw { par { 'A' } }, trow { par { 'a' } } } }",
+        "\
+<div>
+<table>
+<tr><th>A</th></tr>
+<tr><td>a</td></tr>
+</table>
+</div>
+"
+    );
+
+    test_body!(
+        ht_code,
+        "par { code { \"rust\", \"replace\", 'a < b' } }",
+        "\
+<div>
+<pre><code class=\"language-rust\" data-mode=\"replace\">a &lt; b</code></pre>
+</div>
+"
+    );
+
+    test_body!(
+        ht_nav,
+        "nav { nav { \"top\", link { \"/\", \"home\" }, nav { \"sub\", link { \"s\", \"s\" } } } }",
+        "\
+<nav>
+<nav>
+<p>top</p>
+<ul>
+<li><a href=\"/\">home</a></li>
+</ul>
+<nav>
+<p>sub</p>
+<ul>
+<li><a href=\"s\">s</a></li>
+</ul>
+</nav>
+</nav>
+</nav>
+"
+    );
+
+    #[test]
+    fn ht_doc() {
+        let mut output = String::new();
+        let doc = parse("
+            props { (\"language\", \"en\"), (\"title\", 'A & B') },
+            par { 'a' },
+        ").unwrap();
+        doc_to_html(&doc, &mut output);
+        assert_eq!(
+            output,
+            "\
+<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\" />
+<title>A &amp; B</title>
+</head>
+<body data-language=\"en\" data-title=\"A &amp; B\">
+<p>a</p>
+</body>
+</html>
+"
+        );
+    }
+
+    #[test]
+    fn ht_ref_doc() {
+        let mut output = String::new();
+        doc_to_html(&parse(crate::reference_doc::REF_DOC).unwrap(), &mut output);
+        for element in ["section", "nav", "table", "ul", "ol", "div", "p", "li", "tr", "pre"] {
+            assert_eq!(
+                output.matches(&format!("<{element}>")).count()
+                    + output.matches(&format!("<{element} ")).count(),
+                output.matches(&format!("</{element}>")).count(),
+                "{element}",
+            );
+        }
+    }
+
+    test_body!(
+        ht_quotes,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "\
+<figure>
+<blockquote cite=\"https://x.org\" data-kind=\"tip\">
+<p>a</p>
+</blockquote>
+<figcaption>— Ada, <cite><a href=\"https://x.org\">notes</a></cite></figcaption>
+</figure>
+<p>he said <q>hi</q> (Bob).</p>
+"
+    );
+
+    test_body!(
+        ht_footnotes,
+        "par { 'a', fnref { \"x\" }, '.' },
+        footnote { \"x\", par { 'b' }, par { 'c' } }",
+        "\
+<p>a<sup><a href=\"#footnote-x\" role=\"doc-noteref\">x</a></sup>.</p>
+<aside id=\"footnote-x\" role=\"doc-footnote\">
+<p><sup>x</sup></p>
+<p>b</p>
+<p>c</p>
+</aside>
+"
+    );
+
+    test_body!(
+        ht_data,
+        "par { data { cols { \"x\" }, [1.5], props { (\"type-hint\", \"line-graph\") } } }",
+        "\
+<div>
+<table>
+<tr><th>x</th></tr>
+<tr><td>1.5</td></tr>
+</table>
+</div>
+"
+    );
+}
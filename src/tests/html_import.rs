@@ -0,0 +1,193 @@
+#[cfg(all(test, feature = "html"))]
+mod html_import {
+    use crate::parsing::parse;
+    use crate::import::html::*;
+
+    macro_rules! test_html {
+        ($name:ident, $html:expr, $incodoc:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse_html($html, &HtmlOptions::default()), parse($incodoc).unwrap());
+            }
+        }
+    }
+
+    test_html!(
+        hi_paragraphs,
+        "<p>a\n  b</p><p>c<br>\nd</p><div>e <span>f</span></div>",
+        "par { 'a b' }, par { 'c\nd' }, par { 'e f' },"
+    );
+
+    test_html!(
+        hi_emphasis,
+        "<p>a <em>b c</em> <strong>d</strong> <mark>e</mark> <small>f</small> <s>g</s> \
+        <del>h</del> <code>i  j</code></p>",
+        "par {
+            'a ',
+            em { le, \"b c\" },
+            ' ',
+            em { me, \"d\" },
+            ' ',
+            em { se, \"e\" },
+            ' ',
+            em { ld, \"f\" },
+            ' ',
+            em { md, \"g\" },
+            ' ',
+            em { sd, \"h\" },
+            ' ',
+            'i j' { tags { \"code\" } },
+        },"
+    );
+
+    test_html!(
+        hi_sections,
+        "<p>p</p><h1 id=\"top\">a</h1><p>x</p><h3>b</h3><p>y</p><h2>c</h2><p>z</p><h1>d</h1>\
+        <p>w</p>",
+        "par { 'p' },
+        section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, par { 'y' } },
+            section { head { 0, \"c\" }, par { 'z' } },
+            props { (\"id\", \"top\") },
+        },
+        section { head { 0, \"d\" }, par { 'w' } },"
+    );
+
+    test_html!(
+        hi_lists,
+        "<ol><li>a</li><li><p>b</p><ul><li>c</li></ul></li></ol>
+        <ul>
+            <li><input type=\"checkbox\"> d</li>
+            <li><input type=\"checkbox\" checked> <em>e</em></li>
+        </ul>",
+        "par { list { dl, par { 'a' }, par { 'b', list { il, par { 'c' } } } } },
+        par { list { cl, par { 'd' }, par { em { le, \"e\" }, tags { \"checked\" } } } },"
+    );
+
+    test_html!(
+        hi_table,
+        "<table>
+            <thead><tr><td>a</td><td>b</td></tr></thead>
+            <tbody><tr><th>c</th><td>d</td></tr><tr><th>e</th><th>f</th></tr></tbody>
+        </table>",
+        "par { table {
+            throw { par { 'a' }, par { 'b' } },
+            trow { par { 'c' }, par { 'd' } },
+            trow { par { 'e' }, par { 'f' } },
+        } },"
+    );
+
+    #[test]
+    fn hi_empty_cells() {
+        let doc = parse_html(
+            "<table><tr><th></th><th>a</th></tr><tr><td></td><td> </td></tr></table>\
+            <ul><li></li></ul>",
+            &HtmlOptions::default(),
+        );
+        assert_eq!(
+            doc,
+            parse("par { table {
+                throw { par { '' }, par { 'a' } },
+                trow { par { '' }, par { '' } },
+            } },
+            par { list { il, par { '' } } },").unwrap()
+        );
+        let mut output = String::new();
+        crate::output::doc_out(&doc, &mut output);
+        assert_eq!(parse(&output), Ok(doc));
+    }
+
+    #[test]
+    fn hi_empty_sections() {
+        let doc = parse_html("<h1>a</h1><h1>b</h1><h2>c</h2>", &HtmlOptions::default());
+        assert_eq!(
+            doc,
+            parse("section { head { 0, \"a\" }, par { '' } },
+            section { head { 0, \"b\" }, section { head { 0, \"c\" }, par { '' } } },").unwrap()
+        );
+        let mut output = String::new();
+        crate::output::doc_out(&doc, &mut output);
+        assert_eq!(parse(&output), Ok(doc));
+    }
+
+    test_html!(
+        hi_code,
+        "<pre><code class=\"language-rust\">let x = 0;\n</code></pre><pre>a  b</pre>",
+        "par { code { \"rust\", \"show\", 'let x = 0;' } },
+        par { code { \"plain\", \"show\", 'a  b' } },"
+    );
+
+    test_html!(
+        hi_links,
+        "<p><a href=\"u\">a <b>b</b></a> <a>c</a> \
+        <img src=\"i.png\" alt=\"A cat.\"> <a href=\"v\"><img src=\"j.png\" title=\"t\"></a></p>",
+        "par {
+            link { \"u\", \"a \", em { me, \"b\" } },
+            ' c ',
+            link { \"i.png\", \"A cat.\", props {
+                (\"type-hint\", 'image'), (\"bg-text\", 'A cat.')
+            } },
+            ' ',
+            link { \"v\", \"t\", props { (\"type-hint\", 'image') } },
+        },"
+    );
+
+    test_html!(
+        hi_nav,
+        "<nav aria-label=\"Site\"><ul>
+            <li><a href=\"/\">home</a></li>
+            <li><a href=\"/blog\">blog</a><ul><li><a href=\"/blog/1\">one</a></li></ul></li>
+        </ul></nav><p>a</p>",
+        "nav { nav {
+            \"Site\",
+            link { \"/\", \"home\" },
+            link { \"/blog\", \"blog\" },
+            nav { \"blog\", link { \"/blog/1\", \"one\" } },
+        } },
+        par { 'a' },"
+    );
+
+    test_html!(
+        hi_quote,
+        "<h1>a</h1><blockquote><p>b</p><h1>c</h1><p>e</p></blockquote><p>d</p>",
+        "section {
+            head { 0, \"a\" },
+            section {
+                head { 0, \"Quote\" },
+                par { 'b' },
+                section { head { 0, \"c\" }, par { 'e' } },
+                tags { \"blockquote\" },
+            },
+            par { 'd' },
+        },"
+    );
+
+    test_html!(
+        hi_discarded,
+        "<html lang=\"en\"><head><title>T</title><style>p { color: red; }</style></head>
+        <body><script>alert(1)</script><p>a<button>b</button></p>
+        <p hidden>c</p><div aria-hidden=\"true\">d</div><form><input value=\"e\"></form></body>
+        </html>",
+        "props { (\"language\", \"en\"), (\"title\", 'T') }, par { 'a' },"
+    );
+
+    #[test]
+    fn hi_readability() {
+        let html = "<body>
+            <header><p>site</p></header>
+            <nav><a href=\"/\">home</a></nav>
+            <article><p>short</p></article>
+            <article><h1>Title</h1><p>long content</p><aside>ad</aside></article>
+            <footer>footer</footer>
+        </body>";
+        let options = HtmlOptions { readability: true };
+        assert_eq!(
+            parse_html(html, &options),
+            parse("section { head { 0, \"Title\" }, par { 'long content' } },").unwrap()
+        );
+        let main = "<div>menu</div><main><p>a</p><nav><a href=\"/\">home</a></nav></main>";
+        assert_eq!(parse_html(main, &options), parse("par { 'a' },").unwrap());
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(all(test, feature = "json"))]
+mod json {
+    use crate::*;
+    use crate::json::*;
+    use crate::parsing::{ parse, parse_with_comments };
+    use serde_json::Value;
+
+    /// Check a value against the parts of JSON Schema that the incodoc schema uses.
+    fn valid(schema: &Value, root: &Value, value: &Value) -> bool {
+        if let Some(path) = schema.get("$ref").and_then(Value::as_str) {
+            let name = path.trim_start_matches("#/$defs/");
+            return valid(&root["$defs"][name], root, value);
+        }
+        if let Some(options) = schema.get("oneOf").and_then(Value::as_array) {
+            return options.iter().filter(|option| valid(option, root, value)).count() == 1;
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array)
+            && !options.contains(value)
+        {
+            return false;
+        }
+        if let Some(constant) = schema.get("const") && constant != value {
+            return false;
+        }
+        if let Some(min) = schema.get("minimum").and_then(Value::as_i64)
+            && value.as_i64().is_none_or(|v| v < min)
+        {
+            return false;
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_i64)
+            && value.as_i64().is_none_or(|v| v > max)
+        {
+            return false;
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("string") => value.is_string(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("array") => {
+                let Some(items) = value.as_array() else { return false; };
+                if schema.get("uniqueItems") == Some(&Value::Bool(true))
+                    && items.iter().enumerate().any(|(i, a)| items[..i].contains(a))
+                {
+                    return false;
+                }
+                items.iter().all(|item| valid(&schema["items"], root, item))
+            },
+            Some("object") => {
+                let Some(object) = value.as_object() else { return false; };
+                let props = &schema["properties"];
+                let required = schema.get("required").and_then(Value::as_array);
+                if required.is_some_and(|required| {
+                    required.iter().any(|key| !object.contains_key(key.as_str().unwrap()))
+                }) {
+                    return false;
+                }
+                object.iter().all(|(key, val)| match props.get(key) {
+                    Some(prop) => valid(prop, root, val),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(allowed)) => *allowed,
+                        Some(additional) => valid(additional, root, val),
+                        None => true,
+                    },
+                })
+            },
+            _ => true,
+        }
+    }
+
+    fn schema_valid(json: &str) -> bool {
+        let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        valid(&schema, &schema, &serde_json::from_str(json).unwrap())
+    }
+
+    fn full_doc() -> Doc {
+        let input = format!("// comment\n{}", crate::reference_doc::REF_DOC);
+        let mut doc = parse_with_comments(&input).unwrap();
+        doc.props.insert(
+            "bad".to_string(),
+            PropVal::Error(PropValError::Date(DateError::MonthRange(13))),
+        );
+        doc.props.insert(
+            "int".to_string(),
+            PropVal::Error(PropValError::Int("x".parse::<i64>().unwrap_err())),
+        );
+        doc.items.push(DocItem::Paragraph(Paragraph {
+            items: vec![ParagraphItem::Code(Err(CodeIdentError))],
+            ..Default::default()
+        }));
+        doc
+    }
+
+    #[test]
+    fn js_round_trip() {
+        let doc = full_doc();
+        assert_eq!(from_json(&to_json(&doc).unwrap()).unwrap(), doc);
+        assert_eq!(from_json(&to_json_pretty(&doc).unwrap()).unwrap(), doc);
+    }
+
+    #[test]
+    fn js_non_finite_floats() {
+        let doc = parse("par { data { [inf, -inf, NaN, 0.5, -0.0] } }").unwrap();
+        let json = to_json(&doc).unwrap();
+        assert!(json.contains("[{\"Float\":\"inf\"},{\"Float\":\"-inf\"},{\"Float\":\"NaN\"},"));
+        assert_eq!(from_json(&json).unwrap(), doc);
+        assert_eq!(from_json(&json).unwrap(), doc);
+        assert!(from_json(&json.replace("\"NaN\"", "\"nan\"")).is_err());
+    }
+
+    #[test]
+    fn js_envelope() {
+        let json = to_json(&parse("par { 'a' }").unwrap()).unwrap();
+        assert!(json.starts_with("{\"version\":1,\"doc\":{"));
+    }
+
+    #[test]
+    fn js_schema_accepts_output() {
+        assert!(schema_valid(&to_json(&full_doc()).unwrap()));
+        assert!(schema_valid(&to_json(&Doc::default()).unwrap()));
+    }
+
+    macro_rules! test_rejected {
+        ($name:ident, $json:expr, $error:pat) => {
+            #[test]
+            fn $name() {
+                assert!(!schema_valid($json));
+                assert!(matches!(from_json($json), Err($error)));
+            }
+        }
+    }
+
+    test_rejected!(
+        js_version,
+        "{\"version\":2,\"doc\":{}}",
+        JsonError::Version(_)
+    );
+
+    test_rejected!(
+        js_missing_version,
+        "{\"doc\":{}}",
+        JsonError::Json(_)
+    );
+
+    test_rejected!(
+        js_unknown_field,
+        "{\"version\":1,\"doc\":{\"title\":\"a\"}}",
+        JsonError::UnknownField(_)
+    );
+
+    test_rejected!(
+        js_nested_unknown_field,
+        "{\"version\":1,\"doc\":{\"items\":[{\"Paragraph\":{\"items\":[],\"x\":1}}]}}",
+        JsonError::UnknownField(_)
+    );
+
+    test_rejected!(
+        js_wrong_variant,
+        "{\"version\":1,\"doc\":{\"items\":[{\"Par\":{}}]}}",
+        JsonError::Json(_)
+    );
+
+    test_rejected!(
+        js_date,
+        "{\"version\":1,\"doc\":{\"props\":{\"d\":{\"Date\":{\"year\":1,\"month\":13,\"day\":1}}}}}",
+        JsonError::Date(DateError::MonthRange(13))
+    );
+
+    test_rejected!(
+        js_nested_date,
+        "{\"version\":1,\"doc\":{\"navs\":[{\"links\":[{\"url\":\"u\",\"props\":{\
+\"d\":{\"Date\":{\"year\":1,\"month\":1,\"day\":0}}}}]}]}}",
+        JsonError::Date(DateError::DayRange(0))
+    );
+
+    #[test]
+    fn js_not_json() {
+        assert!(matches!(from_json("par { 'a' }"), Err(JsonError::Json(_))));
+    }
+
+    #[test]
+    fn js_minimal() {
+        let json = "{\"version\":1,\"doc\":{\"items\":[{\"Paragraph\":{\"items\":[{\"Text\":\"a\"}]}}]}}";
+        assert!(schema_valid(json));
+        assert_eq!(from_json(json).unwrap(), parse("par { 'a' }").unwrap());
+    }
+}
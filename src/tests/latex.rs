@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod latex {
+    use crate::parsing::parse;
+    use crate::export::latex::{ doc_to_latex, doc_body_to_latex };
+
+    fn body(input: &str) -> String {
+        let mut output = String::new();
+        doc_body_to_latex(&parse(input).unwrap(), &mut output);
+        output
+    }
+
+    macro_rules! test_body {
+        ($name:ident, $input:expr, $latex:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(body($input), $latex);
+            }
+        }
+    }
+
+    test_body!(
+        lx_escape,
+        "par { '100% of $5 & #1_a {b} ~ ^ \\\\\nnext\n' }",
+        "100\\% of \\$5 \\& \\#1\\_a \\{b\\} \\textasciitilde{} \\textasciicircum{} \
+\\textbackslash{}\\\\\nnext\n"
+    );
+
+    test_body!(
+        lx_escape_t1,
+        "par { 'a < b > c | d' }",
+        "a \\textless{} b \\textgreater{} c \\textbar{} d\n"
+    );
+
+    test_body!(
+        lx_code_end_verbatim,
+        "par { code { \"tex\", \"show\",\n'\na\n\\\\end{verbatim}\n|+\\\\end{verbatim}\nb\n' } }",
+        "\
+\\begin{verbatim}
+a
+\\end{verbatim}
+\\noindent\\verb|\\end{verbatim}|\\par
+\\noindent\\verb!|+\\end{verbatim}!\\par
+\\begin{verbatim}
+b
+\\end{verbatim}
+"
+    );
+
+    test_body!(
+        lx_emphasis,
+        "par {
+            em { le, \"a\" }, ' ', em { me, \"b\" }, ' ', em { se, \"c\" }, ' ',
+            em { ld, \"d\" }, ' ', em { sd, \"e\" }, ' ', 'f_g' { tags { \"code\" } }, ' ',
+            link { \"https://a.org/#x\", \"l\" },
+        }",
+        "\\emph{a} \\textbf{b} \\textbf{\\emph{c}} {\\small d} {\\scriptsize e} \\texttt{f\\_g} \
+\\href{https://a.org/\\#x}{l}\n"
+    );
+
+    test_body!(
+        lx_sections,
+        "section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section {
+                head { 1, \"b\" },
+                section { head { 2, \"c\" }, section { head { 3, \"d\" }, par { 'y' } } },
+                props { (\"id\", \"b\") },
+            },
+        }",
+        "\\section{a}\n\nx\n\n\\subsection{b}\\label{b}\n\n\\subsubsection{c}\n\n\
+\\paragraph{d}\n\ny\n"
+    );
+
+    test_body!(
+        lx_lists,
+        "par {
+            'a',
+            list { dl, par { 'b' }, par { 'c', list { il, par { 'd' } } } },
+            list { cl, par { 'e', tags { \"checked\" } }, par { 'f' } },
+        }",
+        "\
+a
+
+\\begin{enumerate}
+\\item b
+\\item c
+
+\\begin{itemize}
+\\item d
+\\end{itemize}
+\\end{enumerate}
+
+\\begin{itemize}
+\\item[$\\boxtimes$] e
+\\item[$\\square$] f
+\\end{itemize}
+"
+    );
+
+    test_body!(
+        lx_table,
+        "par { table { throw { par { 'A' }, par { 'B' } }, trow { par { 'a\nb' } } } }",
+        "\\begin{tabular}{ll}\nA & B \\\\\n\\hline\na b \\\\\n\\end{tabular}\n"
+    );
+
+    test_body!(
+        lx_code,
+        "par {
+            code { \"rust\", \"replace\", 'a {}' },
+            code { \"latex\", \"replace\", '\\\\LaTeX{}' },
+            code { \"latex-formula\", \"replace\", 'x \\\\to y' },
+            code { \"latex-formula\", \"show\", 'x' },
+            code { \"typst-formula\", \"replace\", 'x -> y' },
+        }",
+        "\
+\\begin{verbatim}
+a {}
+\\end{verbatim}
+
+\\LaTeX{}
+
+\\[
+x \\to y
+\\]
+
+\\begin{verbatim}
+x
+\\end{verbatim}
+
+\\begin{verbatim}
+x -> y
+\\end{verbatim}
+"
+    );
+
+    test_body!(
+        lx_quotes,
+        "section { head { 0, \"Quote\" }, par { 'a' }, tags { \"blockquote\" } },
+        section {
+            head { 0, \"Warning\" },
+            par { 'b' },
+            tags { \"blockquote-typed\" },
+            props { (\"blockquote-type\", \"warning\") },
+        }",
+        "\
+\\begin{quote}
+a
+\\end{quote}
+
+\\begin{quote}
+\\textbf{Warning}
+
+b
+\\end{quote}
+"
+    );
+
+    test_body!(
+        lx_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "\
+\\begin{quote}
+\\textbf{Tip}
+
+a
+
+--- Ada, \\href{https://x.org}{notes}
+\\end{quote}
+
+he said ``hi'' (Bob).
+"
+    );
+
+    #[test]
+    fn lx_doc() {
+        let mut output = String::new();
+        let doc = parse("
+            props { (\"title\", 'A & B'), (\"author\", 'Me'), (\"date\", 2024/05/01) },
+            par { 'a' },
+        ").unwrap();
+        doc_to_latex(&doc, &mut output);
+        assert_eq!(
+            output,
+            "\
+\\documentclass{article}
+\\usepackage{amssymb}
+\\usepackage{hyperref}
+\\title{A \\& B}
+\\author{Me}
+\\date{2024-05-01}
+\\begin{document}
+\\maketitle
+
+a
+
+\\end{document}
+"
+        );
+    }
+
+    #[test]
+    fn lx_ref_doc() {
+        let mut output = String::new();
+        doc_to_latex(&parse(crate::reference_doc::REF_DOC).unwrap(), &mut output);
+        let opened = output.matches("\\begin{").count();
+        assert_eq!(opened, output.matches("\\end{").count());
+        assert!(!output.contains("\\\\\n\n"));
+    }
+
+    test_body!(
+        lx_footnotes,
+        "par { 'a', fnref { \"x\" }, '.' },
+        footnote { \"x\", par { 'b' }, par { 'c' } }",
+        "a\\textsuperscript{x}.\n\n\\textsuperscript{x} b\n\nc\n"
+    );
+}
@@ -0,0 +1,190 @@
+#[cfg(all(test, feature = "markdown"))]
+mod markdown {
+    use crate::parsing::parse;
+    use crate::import::markdown::parse_markdown;
+    use crate::output::doc_out;
+
+    macro_rules! test_markdown {
+        ($name:ident, $md:expr, $incodoc:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse_markdown($md), parse($incodoc).unwrap());
+            }
+        }
+    }
+
+    test_markdown!(
+        md_paragraphs,
+        "a\nb\n\nc  \nd",
+        "par { 'a b' }, par { 'c\nd' },"
+    );
+
+    test_markdown!(
+        md_emphasis,
+        "a *b* **c** ***d*** ~~e~~ `f`",
+        "par {
+            'a ',
+            em { le, \"b\" },
+            ' ',
+            em { me, \"c\" },
+            ' ',
+            em { se, \"d\" },
+            ' ',
+            em { md, \"e\" },
+            ' ',
+            'f' { tags { \"code\" } },
+        },"
+    );
+
+    test_markdown!(
+        md_sections,
+        "p\n# a\nx\n### b\ny\n## c\nz\n# d\nw",
+        "par { 'p' },
+        section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, par { 'y' } },
+            section { head { 0, \"c\" }, par { 'z' } },
+        },
+        section { head { 0, \"d\" }, par { 'w' } },"
+    );
+
+    test_markdown!(
+        md_heading_emphasis,
+        "# a *b* [c](d)\nx",
+        "section { head { 0, \"a \", em { le, \"b\" }, \" c\" }, par { 'x' } },"
+    );
+
+    test_markdown!(
+        md_lists,
+        "1. a\n2. b\n   - c\n\n- [ ] d\n- [x] *e*",
+        "par { list { dl, par { 'a' }, par { 'b', list { il, par { 'c' } } } } },
+        par { list {
+            cl,
+            par { 'd' },
+            par { em { le, \"e\" }, tags { \"checked\" } },
+        } },"
+    );
+
+    test_markdown!(
+        md_table,
+        "| A | B |\n|---|---|\n| a | *b* |",
+        "par { table {
+            throw { par { 'A' }, par { 'B' } },
+            trow { par { 'a' }, par { em { le, \"b\" } } },
+        } },"
+    );
+
+    test_markdown!(
+        md_code,
+        "```rust ignore\nlet x = 0;\n```\n\n    indented",
+        "par { code { \"rust\", \"show\", 'let x = 0;' } },
+        par { code { \"plain\", \"show\", 'indented' } },"
+    );
+
+    test_markdown!(
+        md_links,
+        "[a *b*](u \"t\") ![c](i.png)",
+        "par {
+            link { \"u\", \"a \", em { le, \"b\" }, props { (\"title\", \"t\") } },
+            ' ',
+            link { \"i.png\", \"c\", props { (\"type-hint\", 'image') } },
+        },"
+    );
+
+    test_markdown!(
+        md_blockquote,
+        "# a\n> q\n>\n> # h\n> r\n\nx",
+        "section {
+            head { 0, \"a\" },
+            section {
+                head { 0, \"Quote\" },
+                par { 'q' },
+                section { head { 0, \"h\" }, par { 'r' } },
+                tags { \"blockquote\" },
+            },
+            par { 'x' },
+        },"
+    );
+
+    test_markdown!(
+        md_alert,
+        "> [!NOTE]\n> n",
+        "quote { \"Note\", par { 'n' } },"
+    );
+
+    test_markdown!(
+        md_footnotes,
+        "a[^1]\n\n[^1]: b",
+        "par { 'a', link { \"#footnote-1\", \"1\", tags { \"footnote-ref\" } } },
+        section {
+            head { 0, \"1\" },
+            par { 'b' },
+            tags { \"footnote-def\" },
+            props { (\"id\", \"footnote-1\") },
+        },"
+    );
+
+    test_markdown!(
+        md_empty,
+        "- \n- a\n\n| a | b |\n|---|---|\n|  | x |\n\n[](http://x) [a]()\n\n#\n\ntext\n\n# a\n# b",
+        "par { list { il, par { '' }, par { 'a' } } },
+        par { table {
+            throw { par { 'a' }, par { 'b' } },
+            trow { par { '' }, par { 'x' } },
+        } },
+        par { link { \"http://x\", \"http://x\" }, ' a' },
+        par { 'text' },
+        section { head { 0, \"a\" }, par { '' } },
+        section { head { 0, \"b\" }, par { '' } },"
+    );
+
+    #[test]
+    fn md_empty_round_trip() {
+        for input in [
+            "# a\n# b",
+            "| a | b |\n|---|---|\n|  | x |",
+            "- \n- a",
+            "[](http://x)",
+            "#\n\ntext",
+            "# a\n#\n\ntext",
+            ">",
+            "- [ ] ",
+        ] {
+            let doc = parse_markdown(input);
+            let mut output = String::new();
+            doc_out(&doc, &mut output);
+            assert_eq!(parse(&output), Ok(doc), "{input}");
+        }
+    }
+
+    #[test]
+    fn md_round_trip() {
+        let doc = parse_markdown("\
+intro *a*
+
+# A
+
+> quote
+>
+> - [x] done
+>   more
+
+## B
+
+| x | y |
+|---|---|
+| `1` | ~~2~~ |
+
+    code
+
+> [!TIP]
+> tip[^n]
+
+[^n]: note
+");
+        let mut output = String::new();
+        doc_out(&doc, &mut output);
+        assert_eq!(parse(&output).unwrap(), doc);
+    }
+}
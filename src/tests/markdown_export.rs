@@ -0,0 +1,237 @@
+#[cfg(test)]
+mod markdown_export {
+    use crate::parsing::{ parse, NodeStep };
+    use crate::export::markdown::*;
+
+    fn md(input: &str) -> String {
+        md_with(input, &MarkdownOptions::default()).0
+    }
+
+    fn md_with(input: &str, options: &MarkdownOptions) -> (String, Vec<Unrepresented>) {
+        let mut output = String::new();
+        let unrepresented = doc_to_markdown(&parse(input).unwrap(), options, &mut output);
+        (output, unrepresented)
+    }
+
+    macro_rules! test_md {
+        ($name:ident, $input:expr, $md:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(md($input), $md);
+            }
+        }
+    }
+
+    test_md!(
+        mx_emphasis,
+        "par {
+            'a ', em { le, \"b\" }, ' ', em { me, \"c\" }, ' ', em { se, \"d\" }, ' ',
+            em { ld, \"e\" }, ' ', 'f' { tags { \"code\" } },
+        }",
+        "a *b* **c** ***d*** ~~e~~ `f`\n"
+    );
+
+    test_md!(
+        mx_escape,
+        "par { '# *a* [b] `c` 1. d\nline' }, par { 'x' }",
+        "\\# \\*a\\* \\[b\\] \\`c\\` 1. d\\\nline\n\nx\n"
+    );
+
+    test_md!(
+        mx_sections,
+        "section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 5, \"b\" }, par { 'y' } },
+        }",
+        "# a\n\nx\n\n## b\n\ny\n"
+    );
+
+    test_md!(
+        mx_lists,
+        "par {
+            list { dl, par { 'a' }, par { 'b', list { il, par { 'c' } } } },
+            list { cl, par { 'd' }, par { 'e', tags { \"checked\" } } },
+        }",
+        "\
+1. a
+2. b
+
+   - c
+
+- [ ] d
+- [x] e
+"
+    );
+
+    test_md!(
+        mx_table,
+        "par { table {
+            throw { par { 'A' }, par { 'B' } },
+            trow { par { 'a|' }, par { em { le, \"b\" } } },
+            trow { par { 'c' } },
+        } }",
+        "\
+| A | B |
+| --- | --- |
+| a\\| | *b* |
+| c |  |
+"
+    );
+
+    test_md!(
+        mx_table_without_header,
+        "par { table { trow { par { 'a' } } } }",
+        "|  |\n| --- |\n| a |\n"
+    );
+
+    test_md!(
+        mx_code,
+        "par {
+            'a',
+            code { \"rust\", \"show\", 'let x = 0;' },
+            code { \"plain\", \"show\", '```' },
+        }",
+        "a\n\n```rust\nlet x = 0;\n```\n\n````\n```\n````\n"
+    );
+
+    test_md!(
+        mx_links,
+        "par {
+            link { \"u\", \"a\", props { (\"title\", \"t\") } },
+            link { \"i j.png\", \"b\", props { (\"type-hint\", 'image') } },
+            link { \"#footnote-1\", \"1\", tags { \"footnote-ref\" } },
+        }",
+        "[a](u \"t\")![b](<i j.png>)[^1]\n"
+    );
+
+    test_md!(
+        mx_quotes,
+        "section {
+            head { 0, \"Quote\" },
+            par { 'a' },
+            par { 'b' },
+            tags { \"blockquote\" },
+        },
+        section {
+            head { 0, \"Tip\" },
+            par { 'c' },
+            tags { \"blockquote-typed\" },
+            props { (\"blockquote-type\", \"Tip\") },
+        },
+        section {
+            head { 0, \"1\" },
+            par { 'd' },
+            par { 'e' },
+            tags { \"footnote-def\" },
+            props { (\"id\", \"footnote-1\") },
+        },",
+        "> a\n>\n> b\n\n> [!TIP]\n> c\n\n[^1]: d\n\n    e\n"
+    );
+
+    test_md!(
+        mx_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "> [!TIP]\n> a\n>\n> — Ada, [notes](https://x.org)\n\nhe said “hi” (Bob).\n"
+    );
+
+    test_md!(
+        mx_nav,
+        "nav {
+            nav { \"a\", link { \"u\", \"l\" }, nav { \"b\", link { \"v\", \"m\" } } },
+        }, par { 'x' }",
+        "- a\n  - [l](u)\n  - b\n    - [m](v)\n\nx\n"
+    );
+
+    #[test]
+    fn mx_deemphasis() {
+        let input = "par { em { sd, \"a\" } }";
+        let small = MarkdownOptions { deemphasis: DeemphasisStyle::Small };
+        let plain = MarkdownOptions { deemphasis: DeemphasisStyle::Plain };
+        assert_eq!(md_with(input, &small).0, "<small>a</small>\n");
+        assert_eq!(md_with(input, &plain).0, "a\n");
+    }
+
+    #[test]
+    fn mx_unrepresented() {
+        let (_, unrepresented) = md_with(
+            "tags { \"t\" },
+            par { list { cl, par { 'a', tags { \"checked\", \"u\" } } } },
+            section {
+                head { 0, \"h\", props { (\"p\", 1) } },
+                par { link { \"u\", \"l\", props { (\"title\", \"t\"), (\"bg-text\", 'b') } } },
+                props { (\"id\", \"s\") },
+            },",
+            &MarkdownOptions::default(),
+        );
+        assert_eq!(
+            unrepresented,
+            vec![
+                Unrepresented::Tag { path: vec![], tag: "t".to_string() },
+                Unrepresented::Tag {
+                    path: vec![NodeStep::Item(0), NodeStep::Item(0), NodeStep::Item(0)],
+                    tag: "u".to_string(),
+                },
+                Unrepresented::Prop { path: vec![NodeStep::Item(1)], key: "id".to_string() },
+                Unrepresented::Prop {
+                    path: vec![NodeStep::Item(1), NodeStep::Heading],
+                    key: "p".to_string(),
+                },
+                Unrepresented::Prop {
+                    path: vec![NodeStep::Item(1), NodeStep::Item(0), NodeStep::Item(0)],
+                    key: "bg-text".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mx_unrepresented_code() {
+        let (output, unrepresented) = md_with(
+            "par { 'a', code { \"rust\", \"show\", '\n    let x = 0;\n        ' } }",
+            &MarkdownOptions::default(),
+        );
+        assert_eq!(output, "a\n");
+        assert_eq!(
+            unrepresented,
+            vec![Unrepresented::Code { path: vec![NodeStep::Item(0), NodeStep::Item(1)] }]
+        );
+    }
+
+    #[cfg(feature = "markdown")]
+    #[test]
+    fn mx_round_trip() {
+        use crate::import::markdown::parse_markdown;
+        let markdown = "\
+intro *a* **b** ***c*** ~~d~~ `e`
+
+# A
+
+> quote
+>
+> - [x] done
+> - [ ] todo
+
+## B
+
+| x | y |
+| --- | --- |
+| 1 | [l](u \"t\") |
+
+```rust
+let x = 0;
+```
+
+> [!TIP]
+> tip[^n]
+
+[^n]: note
+";
+        let doc = parse_markdown(markdown);
+        let mut output = String::new();
+        assert_eq!(doc_to_markdown(&doc, &MarkdownOptions::default(), &mut output), vec![]);
+        assert_eq!(output, markdown);
+        assert_eq!(parse_markdown(&output), doc);
+    }
+}
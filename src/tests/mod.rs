@@ -1,3 +1,6 @@
+// each file wraps its tests in a module of its own name
+#![allow(clippy::module_inception)]
+
 #[allow(unused_macros)]
 macro_rules! props {
     ($slice:expr) => {
@@ -13,10 +16,34 @@ macro_rules! hset {
 }
 
 pub mod parse;
+pub mod parse_error;
+pub mod spans;
+pub mod tolerant;
+pub mod output;
+pub mod format;
+pub mod comments;
+pub mod serialize;
+pub mod json;
+pub mod html;
+pub mod markdown;
+pub mod markdown_export;
+pub mod html_import;
+pub mod epub;
+pub mod gemtext;
+pub mod org;
+pub mod ansi;
+pub mod plain;
+pub mod roff;
+pub mod latex;
+pub mod typst;
+pub mod ssml;
 pub mod squash;
 pub mod prune;
 pub mod toc;
 pub mod deemphasise;
 pub mod query;
 pub mod collect;
+pub mod quote;
 
+pub mod footnote;
+pub mod data;
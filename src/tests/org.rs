@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod org {
+    use crate::parsing::parse;
+    use crate::import::org::parse_org;
+    use crate::output::doc_out;
+
+    macro_rules! test_org {
+        ($name:ident, $org:expr, $incodoc:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse_org($org), parse($incodoc).unwrap());
+            }
+        }
+    }
+
+    test_org!(
+        or_paragraphs,
+        "a\nb\n\n# comment\nc",
+        "par { 'a b' }, par { 'c' },"
+    );
+
+    test_org!(
+        or_markup,
+        "a /b/ *c* +d+ =e= ~f~ g*h i/j 1 + 2",
+        "par {
+            'a ',
+            em { le, \"b\" },
+            ' ',
+            em { me, \"c\" },
+            ' ',
+            em { md, \"d\" },
+            ' ',
+            'e' { tags { \"code\" } },
+            ' ',
+            'f' { tags { \"code\" } },
+            ' g*h i/j 1 + 2',
+        },"
+    );
+
+    test_org!(
+        or_headlines,
+        "p\n* a   :x:y:\nx\n*** b\ny\n** c\n:PROPERTIES:\n:CUSTOM_ID: see\n:END:\nz\n* d /e/\nw",
+        "par { 'p' },
+        section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, par { 'y' } },
+            section { head { 0, \"c\" }, par { 'z' }, props { (\"id\", \"see\") } },
+            tags { \"x\", \"y\" },
+        },
+        section { head { 0, \"d \", em { le, \"e\" } }, par { 'w' } },"
+    );
+
+    test_org!(
+        or_lists,
+        "1. a\n2. b\n   continued\n   - c\n\n- [ ] d\n- [X] *e*",
+        "par { list { dl, par { 'a' }, par { 'b continued', list { il, par { 'c' } } } } },
+        par { list { cl, par { 'd' }, par { em { me, \"e\" }, tags { \"checked\" } } } },"
+    );
+
+    test_org!(
+        or_table,
+        "| a | b |\n|---+---|\n| c | /d/ |\n| e |",
+        "par { table {
+            throw { par { 'a' }, par { 'b' } },
+            trow { par { 'c' }, par { em { le, \"d\" } } },
+            trow { par { 'e' } },
+        } },"
+    );
+
+    test_org!(
+        or_table_without_header,
+        "| a | b |\n| c | d |\n|---+---|",
+        "par { table {
+            trow { par { 'a' }, par { 'b' } },
+            trow { par { 'c' }, par { 'd' } },
+        } },"
+    );
+
+    test_org!(
+        or_blocks,
+        "#+BEGIN_SRC rust :results output\n  let x = 0;\n#+END_SRC\n\
+        #+begin_example\n* not a headline\n#+end_example\n\
+        #+BEGIN_QUOTE\nq\n#+END_QUOTE",
+        "par { code { \"rust\", \"show\", 'let x = 0;' } },
+        par { code { \"plain\", \"show\", '* not a headline' } },
+        quote { par { 'q' } },"
+    );
+
+    test_org!(
+        or_links,
+        "[[https://a.org][the /a/ site]] and [[file:cat.png]] and [[#b]]",
+        "par {
+            link { \"https://a.org\", \"the \", em { le, \"a\" }, \" site\" },
+            ' and ',
+            link { \"file:cat.png\", \"cat.png\", props { (\"type-hint\", 'image') } },
+            ' and ',
+            link { \"#b\", \"#b\" },
+        },"
+    );
+
+    test_org!(
+        or_keywords,
+        "#+TITLE: The title\n#+DATE: <2024-05-01 Wed>\n#+AUTHOR: Me\n#+LANGUAGE: en\n\
+        #+OPTIONS: toc:nil\ntext",
+        "props {
+            (\"title\", 'The title'),
+            (\"date\", 2024/05/01),
+            (\"author\", 'Me'),
+            (\"language\", \"en\"),
+        },
+        par { 'text' },"
+    );
+
+    test_org!(
+        or_bad_date,
+        "#+DATE: someday",
+        "props { (\"date\", 'someday') },"
+    );
+
+    test_org!(
+        or_empty,
+        "||x|\n|  | y |\n\n- [ ] \n- [X] a\n\n[[a][]]\n\n* a\n* \ntext\n* b",
+        "par { table {
+            trow { par { '' }, par { 'x' } },
+            trow { par { '' }, par { 'y' } },
+        } },
+        par { list { cl, par { '' }, par { 'a', tags { \"checked\" } } } },
+        par { link { \"a\", \"a\" } },
+        section { head { 0, \"a\" }, par { '' } },
+        par { 'text' },
+        section { head { 0, \"b\" }, par { '' } },"
+    );
+
+    #[test]
+    fn or_empty_round_trip() {
+        for input in [
+            "||x|",
+            "|  |",
+            "- [ ]",
+            "* ",
+            "* \n:PROPERTIES:\n:CUSTOM_ID: x\n:END:",
+            "* a\n* b",
+            "#+BEGIN_QUOTE\n#+END_QUOTE",
+        ] {
+            let doc = parse_org(input);
+            let mut output = String::new();
+            doc_out(&doc, &mut output);
+            assert_eq!(parse(&output), Ok(doc), "{input}");
+        }
+    }
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod output {
+    use crate::*;
+    use crate::parsing::parse;
+    use crate::output::{ doc_out, doc_out_with, OutputOptions };
+    use std::collections::{ HashMap, HashSet };
+
+    #[test]
+    fn ou_canonical_meta() {
+        let doc = Doc {
+            tags: hset!(["d", "a", "c", "b"]),
+            props: props!([
+                ("z".to_string(), PropVal::Int(1)),
+                ("a".to_string(), PropVal::String("x".to_string())),
+                ("m".to_string(), PropVal::Text("y".to_string())),
+            ]),
+            ..Default::default()
+        };
+        let mut output = String::new();
+        doc_out(&doc, &mut output);
+        assert_eq!(
+            output,
+            "\
+tags {
+    \"a\",
+    \"b\",
+    \"c\",
+    \"d\",
+},
+props {
+    (\"a\", \"x\"),
+    (\"m\", 'y'),
+    (\"z\", 1),
+},
+"
+        );
+    }
+
+    #[test]
+    fn ou_canonical_ref_doc() {
+        let mut first = String::new();
+        doc_out(&parse(crate::reference_doc::REF_DOC).unwrap(), &mut first);
+        for _ in 0..8 {
+            let mut output = String::new();
+            doc_out(&parse(crate::reference_doc::REF_DOC).unwrap(), &mut output);
+            assert_eq!(first, output);
+        }
+    }
+
+    #[test]
+    fn ou_canonical_whitespace() {
+        let doc = parse("\
+par {
+    code {
+        \"rust\",
+        \"show\",
+        '
+        fn f() {
+
+            let x = 0;
+        }
+        ',
+    },
+},
+").unwrap();
+        for options in [
+            OutputOptions::default(),
+            OutputOptions { tabs: true, ..Default::default() },
+            OutputOptions { compact: true, ..Default::default() },
+        ] {
+            let mut output = String::new();
+            doc_out_with(&doc, &options, &mut output).unwrap();
+            assert!(output.lines().all(|line| !line.ends_with([' ', '\t'])), "{output}");
+            assert_eq!(parse(&output), Ok(doc.clone()));
+        }
+    }
+
+    #[test]
+    fn ou_mtext_error_props() {
+        let error = PropVal::Error(PropValError::Int("x".parse::<i64>().unwrap_err()));
+        let mtext = |props| ParagraphItem::MText(TextWithMeta {
+            text: "x".to_string(),
+            props,
+            ..Default::default()
+        });
+        let doc = Doc {
+            items: vec![DocItem::Paragraph(Paragraph {
+                items: vec![
+                    mtext(props!([("a".to_string(), error.clone())])),
+                    mtext(Props::default()),
+                ],
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+        let compact = OutputOptions { compact: true, ..Default::default() };
+        for options in [OutputOptions::default(), compact] {
+            let mut output = String::new();
+            doc_out_with(&doc, &options, &mut output).unwrap();
+            assert!(!output.contains("'x' {"), "{output}");
+            let parsed = parse(&output).unwrap();
+            let DocItem::Paragraph(par) = &parsed.items[0] else { panic!() };
+            assert_eq!(par.items, vec![ParagraphItem::Text("x".to_string()); 2]);
+        }
+    }
+
+    fn ref_doc_out(options: &OutputOptions) -> String {
+        let mut output = String::new();
+        doc_out_with(&parse(crate::reference_doc::REF_DOC).unwrap(), options, &mut output).unwrap();
+        output
+    }
+
+    macro_rules! test_options_round_trip {
+        ($name:ident, $options:expr) => {
+            #[test]
+            fn $name() {
+                let output = ref_doc_out(&$options);
+                assert_eq!(parse(&output), parse(crate::reference_doc::REF_DOC));
+            }
+        }
+    }
+
+    test_options_round_trip!(ou_rt_default, OutputOptions::default());
+    test_options_round_trip!(ou_rt_narrow, OutputOptions { indent_width: 2, ..Default::default() });
+    test_options_round_trip!(ou_rt_tabs, OutputOptions { tabs: true, ..Default::default() });
+    test_options_round_trip!(ou_rt_compact, OutputOptions { compact: true, ..Default::default() });
+    test_options_round_trip!(
+        ou_rt_compact_tabs,
+        OutputOptions { compact: true, tabs: true, max_width: 60, ..Default::default() }
+    );
+
+    #[test]
+    fn ou_default_is_doc_out() {
+        let mut output = String::new();
+        doc_out(&parse(crate::reference_doc::REF_DOC).unwrap(), &mut output);
+        assert_eq!(output, ref_doc_out(&OutputOptions::default()));
+    }
+
+    const SHORT: &str = "\
+par {
+    'short',
+    em { le, \"em\", tags { \"b\", \"a\" }, },
+    link { \"url\", \"text\", em { se, \"strong\" }, props { (\"k\", 1) }, },
+},
+section {
+    head { 0, \"heading\" },
+    par { 'a', list { dl, par { 'item', }, }, },
+},
+";
+
+    #[test]
+    fn ou_compact() {
+        let options = OutputOptions {
+            compact: true,
+            tabs: true,
+            max_width: 120,
+            ..Default::default()
+        };
+        let mut output = String::new();
+        doc_out_with(&parse(SHORT).unwrap(), &options, &mut output).unwrap();
+        assert_eq!(
+            output,
+            "\
+par { 'short', em { le, \"em\", tags { \"a\", \"b\" } }, link { \"url\", \"text\", em { se, \"strong\" }, props { (\"k\", 1) } } },
+section {
+\thead {
+\t\t0,
+\t\t\"heading\",
+\t},
+\tpar {
+\t\t'a',
+\t\tlist {
+\t\t\tdl,
+\t\t\tpar { 'item' },
+\t\t},
+\t},
+},
+"
+        );
+        assert_eq!(parse(&output), parse(SHORT));
+    }
+
+    #[test]
+    fn ou_compact_max_width() {
+        let options = OutputOptions {
+            compact: true,
+            indent_width: 2,
+            max_width: 40,
+            ..Default::default()
+        };
+        let mut output = String::new();
+        doc_out_with(&parse(SHORT).unwrap(), &options, &mut output).unwrap();
+        assert_eq!(
+            output,
+            "\
+par {
+  'short',
+  em { le, \"em\", tags { \"a\", \"b\" } },
+  link {
+    \"url\",
+    \"text\",
+    em { se, \"strong\" },
+    props {
+      (\"k\", 1),
+    },
+  },
+},
+section {
+  head {
+    0,
+    \"heading\",
+  },
+  par {
+    'a',
+    list {
+      dl,
+      par { 'item' },
+    },
+  },
+},
+"
+        );
+        assert_eq!(parse(&output), parse(SHORT));
+    }
+}
@@ -238,6 +238,50 @@ mod parse {
         }
     );
 
+    test_par!(
+        po_text_escape_c0,
+        "'don\\'t'",
+        Paragraph {
+            items: vec![
+                ParagraphItem::Text("don't".to_string())
+            ],
+            ..Default::default()
+        }
+    );
+
+    test_par!(
+        po_text_escape_c1,
+        "'back\\\\slash'",
+        Paragraph {
+            items: vec![
+                ParagraphItem::Text("back\\slash".to_string())
+            ],
+            ..Default::default()
+        }
+    );
+
+    test_par!(
+        po_text_escape_c2,
+        "'C:\\dir \"quoted\"'",
+        Paragraph {
+            items: vec![
+                ParagraphItem::Text("C:\\dir \"quoted\"".to_string())
+            ],
+            ..Default::default()
+        }
+    );
+
+    test_par!(
+        po_text_escape_c3,
+        "'it\\'s\\\\\\'s'",
+        Paragraph {
+            items: vec![
+                ParagraphItem::Text("it's\\'s".to_string())
+            ],
+            ..Default::default()
+        }
+    );
+
     test_par!(
         po_text_meta0,
         "
@@ -1230,6 +1274,26 @@ props { (
         }
     );
 
+    test_par!(
+        po_code_escape,
+        "code {
+            \"rust\",
+            \"show\",
+            '
+            let c = \\'\\\\\\'\\';
+            let s = \"\\\\n\";
+            '
+        }",
+        Paragraph {
+            items: vec![ParagraphItem::Code(Ok(CodeBlock {
+                language: "rust".to_string(),
+                code: "let c = '\\'';\nlet s = \"\\n\";".to_string(),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        }
+    );
+
     test_par!(
         po_code_meta0,
         "
@@ -1734,7 +1798,6 @@ props { (
                             ("a".to_string(), PropVal::Int(0)),
                             ("b".to_string(), PropVal::Int(1))
                         ]),
-                        ..Default::default()
                     },
                     items: vec![
                         SectionItem::Paragraph(Paragraph {
@@ -1959,6 +2022,7 @@ props { (
                         items: vec![ParagraphItem::Text("item".to_string())],
                         tags: hset!(["tag"]),
                         props: props!([("prop".to_string(), PropVal::Int(0))]),
+                        comments: Vec::new(),
                     }
                 ],
                 tags: hset!(["a", "b"]),
@@ -1985,6 +2049,7 @@ props { (
                         items: vec![ParagraphItem::Text("item".to_string())],
                         tags: hset!(["tag"]),
                         props: props!([("prop".to_string(), PropVal::Int(0))]),
+                        comments: Vec::new(),
                     }
                 ],
                 props: props!([
@@ -2015,6 +2080,7 @@ props { (
                         items: vec![ParagraphItem::Text("item".to_string())],
                         tags: hset!(["tag"]),
                         props: props!([("prop".to_string(), PropVal::Int(0))]),
+                        comments: Vec::new(),
                     }
                 ],
                 tags: hset!(["a", "b"]),
@@ -2345,6 +2411,7 @@ props { (
                         ("b".to_string(), PropVal::Int(1)),
                         ("c".to_string(), PropVal::Int(2)),
                     ]),
+                    comments: Vec::new(),
                 }),
             ],
             ..Default::default()
@@ -2414,6 +2481,21 @@ props { (
         }
     );
 
+    test_par!(
+        po_link_escape,
+        "link { \"say \\\"hi\\\"\", \"don't \\\\ \\\"quote\\\"\" }",
+        Paragraph {
+            items: vec![
+                ParagraphItem::Link(Link {
+                    url: "say \"hi\"".to_string(),
+                    items: vec![EmOrText::Text("don't \\ \"quote\"".to_string())],
+                    ..Default::default()
+                })
+            ],
+            ..Default::default()
+        }
+    );
+
     test_par!(
         po_link_c1,
         "link { \"url\", em { le, \"em\" }, \"string\" }",
@@ -3063,6 +3145,7 @@ props { (
                                 ("a".to_string(), PropVal::Int(0)),
                                 ("b".to_string(), PropVal::Int(0)),
                             ]),
+                            comments: Vec::new(),
                         },
                         TableRow {
                             items: vec![
@@ -3145,4 +3228,24 @@ props { (
             ..Default::default()
         }
     );
+
+    #[test]
+    fn po_node_parsers() {
+        use pest::Parser;
+        use crate::parsing::{ IncodocParser, Rule };
+        let pair = |rule, input| IncodocParser::parse(rule, input).unwrap().next().unwrap();
+        let section = pair(Rule::section, "section { head { 1, \"a\" }, par { 'x' } }");
+        let section = parse_section(1, section);
+        assert_eq!(section.unwrap().heading.level, 2);
+        let mut level = 3;
+        let heading = parse_heading(&mut level, pair(Rule::heading, "head { 0, \"b\" }"));
+        assert_eq!(heading.unwrap().items, vec![EmOrText::Text("b".to_string())]);
+        assert_eq!(level, 3);
+        let par = parse_paragraph(pair(Rule::paragraph, "par { 'c' }")).unwrap();
+        assert_eq!(par.items, vec![ParagraphItem::Text("c".to_string())]);
+        let em = parse_emphasis(pair(Rule::emphasis, "em { se, \"d\" }")).unwrap();
+        assert_eq!(em.strength, EmStrength::Strong);
+        let list = parse_list(pair(Rule::list, "list { dl, par { 'e' } }")).unwrap();
+        assert_eq!(list.ltype, ListType::Distinct);
+    }
 }
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod parse_error {
+    use crate::parsing::*;
+
+    macro_rules! test_error {
+        ($name:ident, $string:expr, $line:expr, $col:expr, $snippet:expr) => {
+            #[test]
+            fn $name() {
+                let error = parse($string).expect_err("test_error: input should not parse");
+                assert_eq!(error.span.start.line, $line);
+                assert_eq!(error.span.start.col, $col);
+                assert_eq!(&error.snippet, $snippet);
+                assert!(matches!(error.kind, ParseErrorKind::Syntax { .. }));
+            }
+        }
+    }
+
+    test_error!(
+        pe_double_comma,
+        "par { 'a',, }",
+        1, 11,
+        "par { 'a',, }"
+    );
+
+    test_error!(
+        pe_missing_comma,
+        "par {\n    'a'\n    'b'\n}",
+        3, 5,
+        "    'b'"
+    );
+
+    test_error!(
+        pe_missing_top_comma,
+        "par { 'a' }\npar { 'b' }",
+        2, 1,
+        "par { 'b' }"
+    );
+
+    test_error!(
+        pe_emphasis_type,
+        "par { em { xe, \"a\" } }",
+        1, 12,
+        "par { em { xe, \"a\" } }"
+    );
+
+    test_error!(
+        pe_unclosed_text,
+        "par {\n    'a\n}",
+        3, 2,
+        "}"
+    );
+
+    #[test]
+    fn pe_expected_tokens() {
+        let error = parse("section { head { 0, \"h\" } }").expect_err("should not parse");
+        assert_eq!(error.span.start.offset, 26);
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::Syntax {
+                expected: vec![Rule::section],
+                expected_tokens: vec![",".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn pe_display() {
+        let error = parse("par {\n    'a'\n    'b'\n}").expect_err("should not parse");
+        assert_eq!(
+            error.to_string(),
+            "3:5: expected one of: `,`, `{`, `}`\n    'b'\n    ^"
+        );
+    }
+}
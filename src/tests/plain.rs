@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod plain {
+    use crate::parsing::parse;
+    use crate::export::plain::*;
+
+    fn linear(input: &str, options: &PlainTextOptions) -> String {
+        let mut output = String::new();
+        plain_text_out(&to_plain_text(&parse(input).unwrap(), options), &mut output);
+        output
+    }
+
+    macro_rules! test_plain {
+        ($name:ident, $input:expr, $text:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(linear($input, &PlainTextOptions::default()), $text);
+            }
+        }
+    }
+
+    test_plain!(
+        pl_sections,
+        "section {
+            head { 0, \"Title\", em { le, \" here\" } },
+            par { 'some ', em { se, \"strong\" }, ' text' },
+            section { head { 0, \"Sub\" }, par { 'more' } },
+        }",
+        "Heading level 1: Title here\n  some strong text\n  Heading level 2: Sub\n    more\n"
+    );
+
+    test_plain!(
+        pl_lists,
+        "par {
+            list { dl, par { 'a' }, par { 'b', list { il, par { 'c' } } } },
+            list { cl, par { 'd' }, par { 'e', tags { \"checked\" } } },
+        }",
+        "Numbered list of 2 items.
+  Item 1: a
+  Item 2: b
+    List of 1 item.
+      Item 1: c
+Checklist of 2 items, 1 checked.
+  Item 1, not checked: d
+  Item 2, checked: e
+"
+    );
+
+    test_plain!(
+        pl_table,
+        "par { table {
+            throw { par { 'Name' }, par { 'Price' } },
+            trow { par { 'apple' }, par { '3' } },
+            trow { par { 'pear' } },
+        } }",
+        "Table of 3 rows and 2 columns. The first row is a header.
+  Row 1, column A: Name
+  Row 1, column B: Price
+  Row 2, column A, Name: apple
+  Row 2, column B, Price: 3
+  Row 3, column A, Name: pear
+"
+    );
+
+    test_plain!(
+        pl_links,
+        "par {
+            link { \"u\", \"site\" }, ', ',
+            link { \"i.png\", \"cat\", props {
+                (\"type-hint\", 'image'), (\"bg-text\", 'A cat on a mat.')
+            } },
+        }",
+        "site, image: cat (A cat on a mat.)\n"
+    );
+
+    test_plain!(
+        pl_footnotes,
+        "par { 'a', link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } } },
+        section {
+            head { 0, \"Notes\" },
+            section {
+                head { 0, \"x\" },
+                par { 'The note.' },
+                tags { \"footnote-def\" }, props { (\"id\", \"footnote-x\") },
+            },
+            section {
+                head { 0, \"y\" },
+                par { 'Unused.' },
+                tags { \"footnote-def\" }, props { (\"id\", \"footnote-y\") },
+            },
+        }",
+        "afootnote 1
+Heading level 1: Notes
+  Footnote 1: The note.
+  Footnote 2: Unused.
+"
+    );
+
+    test_plain!(
+        pl_code,
+        "par {
+            code { \"rust\", \"show\", 'let x = 0;' },
+            code { \"plain\", \"replace\", 'hidden' },
+        }",
+        "Code in rust:\n  let x = 0;\n"
+    );
+
+    #[test]
+    fn pl_replace_code() {
+        let options = PlainTextOptions { include_replace_code: true };
+        assert_eq!(
+            linear("par { code { \"plain\", \"replace\", 'shown' } }", &options),
+            "Code:\n  shown\n"
+        );
+    }
+
+    test_plain!(
+        pl_nav,
+        "nav { nav {
+            \"Contents\", link { \"#a\", \"A\" }, nav { \"More\", link { \"#b\", \"B\" } },
+        } }",
+        "Navigation:\n  Navigation, Contents:\n    A\n    Navigation, More:\n      B\n"
+    );
+
+    #[test]
+    fn pl_structure() {
+        let doc = parse("section { head { 1, \"T\" }, par { 'a', list { il, par { 'b' } } } }")
+            .unwrap();
+        assert_eq!(
+            to_plain_text(&doc, &PlainTextOptions::default()),
+            vec![PlainBlock::Section {
+                level: 1,
+                heading: "T".to_string(),
+                blocks: vec![
+                    PlainBlock::Text("a".to_string()),
+                    PlainBlock::List {
+                        announcement: "List of 1 item.".to_string(),
+                        items: vec![PlainItem {
+                            label: "Item 1".to_string(),
+                            blocks: vec![PlainBlock::Text("b".to_string())],
+                        }],
+                    },
+                ],
+            }]
+        );
+    }
+
+    test_plain!(
+        pl_quotes,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "Tip by Ada, from notes: a\nhe said “hi” (Bob).\n"
+    );
+}
@@ -92,6 +92,7 @@ mod prune {
                 ("ok".to_string(), PropVal::Int(0)),
                 (" ".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         },
         Section {
             heading: Heading {
@@ -111,6 +112,7 @@ mod prune {
             ],
             tags: hset!(["ok".to_string()]),
             props: props!([("ok".to_string(), PropVal::Int(0))]),
+            comments: Vec::new(),
         }
     );
 
@@ -155,6 +157,7 @@ mod prune {
                 ("ok".to_string(), PropVal::Int(0)),
                 (" ".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         }),
         SectionItem::Paragraph(Paragraph {
             items: vec![
@@ -167,6 +170,7 @@ mod prune {
             props: props!([
                 ("ok".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         })
     );
 
@@ -254,6 +258,7 @@ mod prune {
                 ("ok".to_string(), PropVal::Int(0)),
                 (" ".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         },
         List {
             ltype: ListType::Identical,
@@ -271,6 +276,7 @@ mod prune {
             props: props!([
                 ("ok".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         }
     );
 
@@ -462,6 +468,7 @@ mod prune {
                                 ("ok".to_string(), PropVal::Int(0)),
                                 (" ".to_string(), PropVal::Int(0)),
                             ]),
+                            comments: Vec::new(),
                         },
                         Paragraph {
                             items: vec![
@@ -480,6 +487,7 @@ mod prune {
                         ("ok".to_string(), PropVal::Int(0)),
                         (" ".to_string(), PropVal::Int(0)),
                     ]),
+                    comments: Vec::new(),
                 },
             ],
             tags: hset!([
@@ -490,6 +498,7 @@ mod prune {
                 ("ok".to_string(), PropVal::Int(0)),
                 (" ".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         },
         Table {
             rows: vec![
@@ -505,6 +514,7 @@ mod prune {
                             props: props!([
                                 ("ok".to_string(), PropVal::Int(0)),
                             ]),
+                            comments: Vec::new(),
                         },
                     ],
                     is_header: false,
@@ -514,6 +524,7 @@ mod prune {
                     props: props!([
                         ("ok".to_string(), PropVal::Int(0)),
                     ]),
+                    comments: Vec::new(),
                 },
             ],
             tags: hset!([
@@ -522,6 +533,7 @@ mod prune {
             props: props!([
                 ("ok".to_string(), PropVal::Int(0)),
             ]),
+            comments: Vec::new(),
         }
     );
 }
@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod quote {
+    use crate::*;
+    use crate::parsing::*;
+    use crate::output::doc_out;
+    use crate::actions::{ toc::*, prune::* };
+    use std::collections::{ HashMap, HashSet };
+
+    fn text_par(text: &str) -> Paragraph {
+        Paragraph {
+            items: vec![ParagraphItem::Text(text.to_string())],
+            ..Default::default()
+        }
+    }
+
+    fn out(doc: &Doc) -> String {
+        let mut output = String::new();
+        doc_out(doc, &mut output);
+        output
+    }
+
+    const BLOCK: &str = "\
+quote {
+    \"note\",
+    par {
+        'a',
+    },
+    quote {
+        par {
+            'b',
+        },
+    },
+    by {
+        \"Ada \",
+        em {
+            le,
+            \"Lovelace\",
+        },
+    },
+    link {
+        \"https://example.com\",
+        \"notes\",
+    },
+    tags {
+        \"t\",
+    },
+    props {
+        (\"id\", \"q\"),
+    },
+},
+";
+
+    const INLINE: &str = "\
+par {
+    'he said ',
+    quote {
+        'hi',
+        by {
+            \"Bob\",
+        },
+    },
+},
+";
+
+    #[test]
+    fn qu_parse_block() {
+        let doc = parse(BLOCK).unwrap();
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Quote(Quote {
+                kind: Some("note".to_string()),
+                items: vec![
+                    QuoteItem::Paragraph(text_par("a")),
+                    QuoteItem::Quote(Quote {
+                        items: vec![QuoteItem::Paragraph(text_par("b"))],
+                        ..Default::default()
+                    }),
+                ],
+                attribution: vec![
+                    EmOrText::Text("Ada ".to_string()),
+                    EmOrText::Em(Emphasis {
+                        strength: EmStrength::Light,
+                        etype: EmType::Emphasis,
+                        text: "Lovelace".to_string(),
+                        ..Default::default()
+                    }),
+                ],
+                source: Some(Link {
+                    url: "https://example.com".to_string(),
+                    items: vec![EmOrText::Text("notes".to_string())],
+                    ..Default::default()
+                }),
+                tags: hset!(["t"]),
+                props: props!([("id".to_string(), PropVal::String("q".to_string()))]),
+                ..Default::default()
+            })]
+        );
+    }
+
+    #[test]
+    fn qu_parse_inline() {
+        let doc = parse(INLINE).unwrap();
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Paragraph(Paragraph {
+                items: vec![
+                    ParagraphItem::Text("he said ".to_string()),
+                    ParagraphItem::Quote(InlineQuote {
+                        items: vec![EmOrText::Text("hi".to_string())],
+                        attribution: vec![EmOrText::Text("Bob".to_string())],
+                        ..Default::default()
+                    }),
+                ],
+                ..Default::default()
+            })]
+        );
+    }
+
+    #[test]
+    fn qu_parse_in_section() {
+        let doc = parse("section { head { 0, \"s\" }, quote { par { 'a' } } }").unwrap();
+        let DocItem::Section(section) = &doc.items[0] else { panic!("expected a section") };
+        assert_eq!(
+            section.items,
+            vec![SectionItem::Quote(Quote {
+                items: vec![QuoteItem::Paragraph(text_par("a"))],
+                ..Default::default()
+            })]
+        );
+    }
+
+    #[test]
+    fn qu_parse_errors() {
+        assert!(parse("quote { }").is_err());
+        assert!(parse("quote { \"kind\" }").is_err());
+        assert!(parse("quote { section { head { 0, \"s\" } } }").is_err());
+        assert!(parse("par { quote { par { 'a' } } }").is_err());
+        for input in [
+            "quote { par { 'a' }, link { \"https://a\", \"a\" }, link { \"https://b\", \"b\" } }",
+            "par { quote { 'a', link { \"https://a\", \"a\" }, link { \"https://b\", \"b\" } } }",
+        ] {
+            let error = parse(input).unwrap_err();
+            assert!(matches!(error.kind, ParseErrorKind::DuplicatePart { part: "source", .. }));
+        }
+    }
+
+    #[test]
+    fn qu_round_trip() {
+        assert_eq!(out(&parse(BLOCK).unwrap()), BLOCK);
+        assert_eq!(out(&parse(INLINE).unwrap()), INLINE);
+    }
+
+    #[test]
+    fn qu_spans() {
+        let (_, spans) = parse_with_spans(BLOCK).unwrap();
+        let span = |path: &[NodeStep]| {
+            let span = spans.get(path).expect("qu_spans: path should be recorded");
+            &BLOCK[span.start.offset..span.end.offset]
+        };
+        assert!(span(&[NodeStep::Item(0), NodeStep::Attribution]).starts_with("by {"));
+        assert!(span(&[NodeStep::Item(0), NodeStep::Source]).starts_with("link {"));
+        assert!(span(&[NodeStep::Item(0), NodeStep::Item(1)]).starts_with("quote {"));
+    }
+
+    macro_rules! test_migrate {
+        ($name:ident, $input:expr, $output:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(parse($input).unwrap(), parse($output).unwrap());
+                assert_eq!(out(&parse($input).unwrap()), out(&parse($output).unwrap()));
+            }
+        }
+    }
+
+    test_migrate!(
+        qu_migrate_untyped,
+        "section { head { 0, \"Quote\" }, par { 'a' }, tags { \"blockquote\", \"x\" } }",
+        "quote { par { 'a' }, tags { \"x\" } }"
+    );
+
+    test_migrate!(
+        qu_migrate_typed_prop,
+        "section {
+            head { 0, \"warning\" },
+            par { 'a' },
+            tags { \"blockquote-typed\" },
+            props { (\"blockquote-type\", \"warning\"), (\"id\", \"w\") },
+        }",
+        "quote { \"warning\", par { 'a' }, props { (\"id\", \"w\") } }"
+    );
+
+    test_migrate!(
+        qu_migrate_typed_heading,
+        "section { head { 0, \"Note \", em { le, \"this\" } }, par { 'a' }, \
+            tags { \"blockquote-typed\" } }",
+        "quote { \"Note this\", par { 'a' } }"
+    );
+
+    test_migrate!(
+        qu_migrate_nested,
+        "section {
+            head { 0, \"s\" },
+            section {
+                head { 0, \"Quote\" },
+                par { 'a' },
+                section { head { 0, \"Quote\" }, par { 'b' }, tags { \"blockquote\" } },
+                tags { \"blockquote\" },
+            },
+        }",
+        "section { head { 0, \"s\" }, quote { par { 'a' }, quote { par { 'b' } } } }"
+    );
+
+    #[test]
+    fn qu_migrate_keeps_sub_sections() {
+        let doc = parse(
+            "section { head { 0, \"Quote\" }, section { head { 0, \"s\" }, par { 'a' } }, \
+                tags { \"blockquote\" } }"
+        ).unwrap();
+        assert!(matches!(doc.items[0], DocItem::Section(_)));
+    }
+
+    #[test]
+    fn qu_migrate_keeps_empty() {
+        let doc = parse("section { head { 0, \"Quote\" }, tags { \"blockquote\" } }").unwrap();
+        assert!(matches!(doc.items[0], DocItem::Section(_)));
+        assert_eq!(parse(&out(&doc)).unwrap(), doc);
+    }
+
+    #[test]
+    fn qu_migrate_keeps_headings() {
+        for input in [
+            "section { head { 0, \"Einstein on time\" }, par { 'a' }, tags { \"blockquote\" } }",
+            "section {
+                head { 0, \"Quote\", tags { \"t\" } },
+                par { 'a' },
+                tags { \"blockquote\" },
+            }",
+            "section {
+                head { 0, \"Careful\" },
+                par { 'a' },
+                tags { \"blockquote-typed\" },
+                props { (\"blockquote-type\", \"warning\") },
+            }",
+            "section {
+                head { 0, \"Quote\" },
+                par { 'a' },
+                footnote { \"x\", par { 'b' } },
+                tags { \"blockquote\" },
+            }",
+        ] {
+            let doc = parse(input).unwrap();
+            assert!(matches!(doc.items[0], DocItem::Section(_)), "{input}");
+        }
+    }
+
+    #[test]
+    fn qu_migrate_comments() {
+        let doc = parse_with_comments("\
+section {
+    head { 0, \"Quote\" },
+    // first
+    par { 'a' },
+    // last
+    tags { \"blockquote\" },
+}
+").unwrap();
+        let DocItem::Quote(quote) = &doc.items[0] else { panic!("expected a quote") };
+        assert_eq!(
+            quote.comments,
+            vec![
+                Comment { text: " first".to_string(), block: false, position: 0 },
+                Comment { text: " last".to_string(), block: false, position: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn qu_migrate_spans() {
+        let input = "section { head { 0, \"Quote\" }, par { 'a' }, tags { \"blockquote\" } }";
+        let (doc, spans) = parse_with_spans(input).unwrap();
+        assert!(matches!(doc.items[0], DocItem::Quote(_)));
+        assert!(spans.contains_key([NodeStep::Item(0), NodeStep::Item(0)].as_slice()));
+        assert!(!spans.contains_key([NodeStep::Item(0), NodeStep::Heading].as_slice()));
+    }
+
+    #[test]
+    fn qu_prune() {
+        let mut doc = parse("\
+quote { \"kind\", par { '  ' }, by { \"x\" } },
+quote { par { 'a' }, quote { par { ' ' } } },
+par { 'a', quote { ' ' } },
+").unwrap();
+        doc.prune_contentless();
+        assert_eq!(doc, parse("quote { par { 'a' } }, par { 'a' }").unwrap());
+    }
+
+    #[test]
+    fn qu_squash() {
+        let mut doc = parse("\
+quote {
+    par { 'a', 'b' },
+    by { \"c\", \"d\" },
+},
+par { quote { 'e', 'f', by { \"g\", \"h\" } } },
+").unwrap();
+        doc.squash();
+        assert_eq!(
+            doc,
+            parse("quote { par { 'ab' }, by { \"cd\" } }, par { quote { 'ef', by { \"gh\" } } }")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn qu_toc() {
+        let doc = parse("\
+quote { \"warning\", par { 'a' }, props { (\"id\", \"w\") } },
+quote { par { 'b' }, by { \"Ada\" } },
+par { quote { 'c', props { (\"id\", \"c\") } }, quote { 'd' } },
+").unwrap();
+        let toc = doc.get_table_of_contents(&None).unwrap();
+        let quotes = Some((
+            HashSet::from([TableOfContentsItemType::Quote]),
+            TableOfContentsFilterType::IncludeWithChildren,
+        ));
+        assert_eq!(doc.get_table_of_contents(&quotes).unwrap().children.len(), 3);
+        let titles: Vec<(&str, &str)> = toc.children.iter()
+            .map(|item| (item.title.as_str(), item.link.as_str()))
+            .collect();
+        assert_eq!(titles, vec![("Quote: warning", "#w"), ("Quote: Ada", ""), ("paragraph", "")]);
+        assert_eq!(toc.children[2].children[0].title, "c");
+        assert_eq!(toc.children[2].children[0].link, "#c");
+        assert_eq!(toc.children[2].children.len(), 1);
+    }
+}
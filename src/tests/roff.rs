@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod roff {
+    use crate::parsing::parse;
+    use crate::export::roff::doc_to_roff;
+
+    fn roff(input: &str) -> String {
+        let mut output = String::new();
+        doc_to_roff(&parse(input).unwrap(), &mut output);
+        output
+    }
+
+    macro_rules! test_roff {
+        ($name:ident, $input:expr, $roff:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(roff($input), concat!(".TH \"\" \"1\"\n", $roff));
+            }
+        }
+    }
+
+    #[test]
+    fn rf_header() {
+        assert_eq!(
+            roff("
+                props {
+                    (\"name\", \"incodoc\"),
+                    (\"section\", 5),
+                    (\"date\", 2024/05/01),
+                    (\"manual\", 'File \"Formats\"'),
+                },
+                par { 'a' },
+            "),
+            ".TH \"INCODOC\" \"5\" \"2024\\-05\\-01\" \"\" \"File \\(dqFormats\\(dq\"\n.PP\na\n"
+        );
+        assert_eq!(
+            roff("props { (\"title\", 'tool'), (\"date\", 'soon') }, par { 'a' }"),
+            ".TH \"TOOL\" \"1\"\n.PP\na\n"
+        );
+    }
+
+    test_roff!(
+        rf_escape,
+        "par { '.start \\\\ -v \"q\"\n \\'quote' }",
+        ".PP\n\\&.start \\e \\-v \\(dqq\\(dq\n.br\n\\&'quote\n"
+    );
+
+    test_roff!(
+        rf_emphasis,
+        "par {
+            em { le, \"a\" }, ' ', em { me, \"b\" }, ' ', em { se, \"c\" }, ' ',
+            em { md, \"d\" }, ' ', 'e' { tags { \"code\" } },
+        }",
+        ".PP\n\\fIa\\fR \\fBb\\fR \\f(BIc\\fR d \\fBe\\fR\n"
+    );
+
+    test_roff!(
+        rf_sections,
+        "section {
+            head { 0, \"Name\" },
+            par { 'x' },
+            par { 'y' },
+            section { head { 1, em { me, \"Sub\" }, \" section\" }, par { 'z' } },
+        }",
+        ".SH Name\n.PP\nx\n.PP\ny\n.SS \\fBSub\\fR section\n.PP\nz\n"
+    );
+
+    test_roff!(
+        rf_links,
+        "par {
+            'see ', link { \"https://a.org\", \"a\" }, ', then ', link { \"b\", \"b\" }, ' now',
+            link { \"#footnote-1\", \"1\", tags { \"footnote-ref\" } },
+        }",
+        ".PP\nsee\n.UR https://a.org\na\n.UE ,\nthen\n.UR b\nb\n.UE\nnow[1]\n"
+    );
+
+    test_roff!(
+        rf_lists,
+        "par {
+            list { il, par { 'a' }, par { 'b', list { dl, par { 'c' } }, 'd' } },
+            list { cl, par { 'e', tags { \"checked\" } }, par { 'f' } },
+        }",
+        "\
+.IP \\(bu 2
+a
+.IP \\(bu 2
+b
+.RS
+.IP 1. 4
+c
+.RE
+.IP
+d
+.IP [x] 4
+e
+.IP [\\ ] 4
+f
+"
+    );
+
+    test_roff!(
+        rf_tagged_list,
+        "par { list { il, par { em { me, \"-v\" }, ', --verbose\nPrint more.' } } }",
+        ".TP\n\\fB\\-v\\fR, \\-\\-verbose\nPrint more.\n"
+    );
+
+    test_roff!(
+        rf_code,
+        "par { 'run', code { \"sh\", \"show\", '.x \\\\ -y' }, 'done' }",
+        ".PP\nrun\n.PP\n.RS 4\n.EX\n\\&.x \\e \\-y\n.EE\n.RE\n.PP\ndone\n"
+    );
+
+    #[test]
+    fn rf_table() {
+        assert_eq!(
+            roff("par { table {
+                throw { par { 'A' }, par { 'B' } },
+                trow { par { '_' }, par { link { \"u\", \"l\" } } },
+            } }"),
+            "\
+'\\\" t
+.TH \"\" \"1\"
+.PP
+.TS
+lb lb
+l l.
+A\tB
+_
+\\&_\tT{
+.UR u
+l
+.UE
+T}
+.TE
+"
+        );
+    }
+
+    test_roff!(
+        rf_quotes,
+        "section { head { 0, \"Quote\" }, par { 'a' }, tags { \"blockquote\" } },
+        section {
+            head { 0, \"Note\" },
+            par { 'b' },
+            tags { \"blockquote-typed\" },
+            props { (\"blockquote-type\", \"note\") },
+        },
+        section {
+            head { 0, \"Footnote\" },
+            par { 'c' },
+            par { 'd' },
+            tags { \"footnote-def\" },
+            props { (\"id\", \"footnote-1\") },
+        }",
+        "\
+.RS 4
+.PP
+a
+.RE
+.RS 4
+.PP
+\\fBNote\\fR
+.PP
+b
+.RE
+.IP [1] 4
+c
+.IP
+d
+"
+    );
+
+    test_roff!(
+        rf_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        ".RS 4\n.PP\n\\fBTip\\fR\n.PP\na\n.PP\n\\(em Ada,\n.UR https://x.org\nnotes\n.UE\n.RE\n\
+.PP\nhe said \\(lqhi\\(rq (Bob).\n"
+    );
+
+    #[test]
+    fn rf_ref_doc() {
+        let output = roff(crate::reference_doc::REF_DOC);
+        assert_eq!(output.matches("\n.RS").count(), output.matches("\n.RE").count());
+        assert_eq!(output.matches("\n.EX").count(), output.matches("\n.EE").count());
+        assert_eq!(output.matches("\n.UR").count(), output.matches("\n.UE").count());
+        assert_eq!(output.matches("\n.TS").count(), output.matches("\n.TE").count());
+        assert!(output.lines().all(|line| !line.starts_with(' ')));
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(all(test, feature = "serde"))]
+mod serialize {
+    use crate::*;
+    use crate::parsing::parse;
+    use std::collections::{ HashMap, HashSet };
+
+    fn round_trip(doc: &Doc) -> Doc {
+        serde_json::from_str(&serde_json::to_string(doc).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn se_ref_doc() {
+        let doc = parse(crate::reference_doc::REF_DOC).unwrap();
+        assert_eq!(round_trip(&doc), doc);
+    }
+
+    #[test]
+    fn se_encoding() {
+        let mut doc = parse("\
+tags { \"b\", \"a\" },
+props { (\"z\", 1), (\"date\", 2000/01/11), (\"text\", 'x'), (\"bad\", 2000/13/01) },
+par {
+    'text',
+    em { le, \"em\" },
+},
+").unwrap();
+        let DocItem::Paragraph(par) = &mut doc.items[0] else { panic!() };
+        par.items.push(ParagraphItem::Code(Ok(CodeBlock {
+            language: "rust".to_string(),
+            code: "let x = 0;".to_string(),
+            ..Default::default()
+        })));
+        par.items.push(ParagraphItem::Code(Err(CodeIdentError)));
+        assert_eq!(
+            serde_json::to_string(&doc).unwrap(),
+            "{\
+\"tags\":[\"a\",\"b\"],\
+\"props\":{\
+\"bad\":{\"Error\":{\"Date\":{\"MonthRange\":13}}},\
+\"date\":{\"Date\":{\"year\":2000,\"month\":1,\"day\":11}},\
+\"text\":{\"Text\":\"x\"},\
+\"z\":{\"Int\":1}\
+},\
+\"navs\":[],\
+\"items\":[{\"Paragraph\":{\"items\":[\
+{\"Text\":\"text\"},\
+{\"Em\":{\"strength\":\"Light\",\"etype\":\"Emphasis\",\"text\":\"em\",\"tags\":[],\"props\":{}}},\
+{\"Code\":{\"Ok\":{\"language\":\"rust\",\"mode\":\"Show\",\"code\":\"let x = 0;\",\"tags\":[],\"props\":{}}}},\
+{\"Code\":{\"Err\":null}}\
+],\"tags\":[],\"props\":{},\"comments\":[]}}],\
+\"comments\":[]\
+}"
+        );
+        assert_eq!(round_trip(&doc), doc);
+    }
+
+    #[test]
+    fn se_int_errors() {
+        for input in ["", "x", "99999999999999999999", "-99999999999999999999"] {
+            let error = input.parse::<i64>().unwrap_err();
+            let val = PropVal::Error(PropValError::Int(error));
+            let json = serde_json::to_string(&val).unwrap();
+            assert_eq!(serde_json::from_str::<PropVal>(&json).unwrap(), val);
+        }
+        assert_eq!(
+            serde_json::to_string(&PropVal::Error(PropValError::Int(
+                "-99999999999999999999".parse::<i64>().unwrap_err()
+            ))).unwrap(),
+            "{\"Error\":{\"Int\":\"NegOverflow\"}}"
+        );
+        assert!(serde_json::from_str::<PropVal>("{\"Error\":{\"Int\":\"Other\"}}").is_err());
+    }
+
+    #[test]
+    fn se_defaults() {
+        let json = "{\"items\":[{\"Paragraph\":{\"items\":[{\"Text\":\"a\"}]}}]}";
+        let doc: Doc = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            doc,
+            Doc {
+                items: vec![DocItem::Paragraph(Paragraph {
+                    items: vec![ParagraphItem::Text("a".to_string())],
+                    ..Default::default()
+                })],
+                ..Default::default()
+            }
+        );
+        let props: Props = serde_json::from_str("{\"a\":{\"Int\":1}}").unwrap();
+        assert_eq!(props, props!([("a".to_string(), PropVal::Int(1))]));
+        let tags: Tags = serde_json::from_str("[\"a\"]").unwrap();
+        assert_eq!(tags, hset!(["a"]));
+    }
+
+    #[test]
+    fn se_unknown_fields() {
+        let json = "{\"title\":\"a\",\"navs\":[{\"description\":\"d\",\"x\":0}]}";
+        let doc: Doc = serde_json::from_str(json).unwrap();
+        assert_eq!(doc.navs, vec![Nav { description: "d".to_string(), ..Default::default() }]);
+        let date: Date = serde_json::from_str("{\"year\":1,\"month\":2,\"day\":3,\"x\":0}")
+            .unwrap();
+        assert_eq!(date, Date { year: 1, month: 2, day: 3 });
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod spans {
+    use crate::parsing::*;
+    use NodeStep::*;
+
+    const INPUT: &str = "\
+nav {
+    link { \"#a\", \"A\", },
+},
+par {
+    'hello',
+    em { le, \"there\", },
+},
+section {
+    head { 0, \"title\", },
+    par { 'body', },
+},
+";
+
+    fn span_str<'a>(spans: &SpanTable, path: &[NodeStep], input: &'a str) -> &'a str {
+        let span = spans.get(path).expect("span_str: path should be recorded");
+        &input[span.start.offset..span.end.offset]
+    }
+
+    #[test]
+    fn sp_doc() {
+        let (doc, spans) = parse_with_spans(INPUT).unwrap();
+        assert_eq!(Ok(doc), parse(INPUT));
+        assert_eq!(span_str(&spans, &[], INPUT), INPUT);
+    }
+
+    #[test]
+    fn sp_nav() {
+        let (_, spans) = parse_with_spans(INPUT).unwrap();
+        assert_eq!(span_str(&spans, &[Nav(0), Link(0)], INPUT), "link { \"#a\", \"A\", }");
+        assert_eq!(span_str(&spans, &[Nav(0), Link(0), Item(0)], INPUT), "\"A\"");
+    }
+
+    #[test]
+    fn sp_paragraph() {
+        let (_, spans) = parse_with_spans(INPUT).unwrap();
+        assert!(span_str(&spans, &[Item(0)], INPUT).starts_with("par {\n    'hello',"));
+        assert_eq!(span_str(&spans, &[Item(0), Item(0)], INPUT), "'hello'");
+        assert_eq!(span_str(&spans, &[Item(0), Item(1)], INPUT), "em { le, \"there\", }");
+    }
+
+    #[test]
+    fn sp_section() {
+        let (_, spans) = parse_with_spans(INPUT).unwrap();
+        assert_eq!(span_str(&spans, &[Item(1), Heading], INPUT), "head { 0, \"title\", }");
+        assert_eq!(span_str(&spans, &[Item(1), Heading, Item(0)], INPUT), "\"title\"");
+        assert_eq!(span_str(&spans, &[Item(1), Item(0)], INPUT), "par { 'body', }");
+        let par = spans[&vec![Item(1), Item(0)]];
+        assert_eq!((par.start.line, par.start.col), (10, 5));
+        assert_eq!((par.end.line, par.end.col), (10, 20));
+    }
+
+    #[test]
+    fn sp_table() {
+        let input = "par { table { throw { par { 'h', }, }, trow { par { 'c', }, }, }, },";
+        let (_, spans) = parse_with_spans(input).unwrap();
+        assert!(span_str(&spans, &[Item(0), Item(0), Row(0)], input).starts_with("throw"));
+        assert_eq!(span_str(&spans, &[Item(0), Item(0), Row(1), Item(0)], input), "par { 'c', }");
+    }
+
+    #[test]
+    fn sp_list() {
+        let input = "par { list { dl, par { 'a', }, par { 'b', }, }, },";
+        let (_, spans) = parse_with_spans(input).unwrap();
+        assert_eq!(span_str(&spans, &[Item(0), Item(0), Item(1)], input), "par { 'b', }");
+        assert_eq!(span_str(&spans, &[Item(0), Item(0), Item(1), Item(0)], input), "'b'");
+    }
+}
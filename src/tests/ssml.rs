@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod ssml {
+    use crate::parsing::parse;
+    use crate::export::ansi::ProfileError;
+    use crate::export::ssml::*;
+
+    const HEAD: &str = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<speak version=\"1.1\" xmlns=\"http://www.w3.org/2001/10/synthesis\">
+";
+
+    fn quiet() -> SpeechProfile {
+        SpeechProfile {
+            emphasis: Default::default(),
+            deemphasis: Default::default(),
+            heading: Voice::default(),
+            link: Voice::default(),
+            code: Voice::default(),
+            heading_pause: 0,
+            item_pause: 0,
+            announce_levels: true,
+        }
+    }
+
+    fn ssml(input: &str, profile: &SpeechProfile) -> String {
+        let mut output = String::new();
+        doc_to_ssml(&parse(input).unwrap(), profile, &mut output);
+        let body = output.strip_prefix(HEAD).unwrap();
+        body.strip_suffix("</speak>\n").unwrap().to_string()
+    }
+
+    macro_rules! test_ssml {
+        ($name:ident, $input:expr, $ssml:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(ssml($input, &quiet()), $ssml);
+            }
+        }
+    }
+
+    #[test]
+    fn ss_emphasis() {
+        let profile = SpeechProfile {
+            emphasis: [
+                Voice::from_words("pitch:high").unwrap(),
+                Voice::from_words("plain").unwrap(),
+                Voice::from_words("voice:Ann volume:x-loud rate:slow").unwrap(),
+            ],
+            ..quiet()
+        };
+        assert_eq!(
+            ssml(
+                "par { em { le, \"a\" }, ' & ', em { me, \"b\" }, ' ', em { se, \"c\" }, ' <d>' }",
+                &profile,
+            ),
+            "<p><prosody pitch=\"high\">a</prosody> &amp; b \
+<voice name=\"Ann\"><prosody rate=\"slow\" volume=\"x-loud\">c</prosody></voice> &lt;d&gt;</p>\n"
+        );
+    }
+
+    #[test]
+    fn ss_headings() {
+        let input = "section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 0, \"b\" }, par { 'y' } },
+        }";
+        let profile = SpeechProfile {
+            heading: Voice::from_words("rate:slow").unwrap(),
+            heading_pause: 500,
+            ..quiet()
+        };
+        assert_eq!(
+            ssml(input, &profile),
+            "\
+<break time=\"500ms\"/>
+<p><s>Heading level 1.</s> <prosody rate=\"slow\">a</prosody></p>
+<break time=\"500ms\"/>
+<p>x</p>
+<break time=\"500ms\"/>
+<p><s>Heading level 2.</s> <prosody rate=\"slow\">b</prosody></p>
+<break time=\"500ms\"/>
+<p>y</p>
+"
+        );
+        let profile = SpeechProfile { announce_levels: false, ..quiet() };
+        assert_eq!(ssml(input, &profile), "<p>a</p>\n<p>x</p>\n<p>b</p>\n<p>y</p>\n");
+    }
+
+    #[test]
+    fn ss_lists() {
+        let profile = SpeechProfile { item_pause: 200, ..quiet() };
+        assert_eq!(
+            ssml(
+                "par {
+                    'a',
+                    list {
+                        cl,
+                        par { 'b', tags { \"checked\" } },
+                        par { list { dl, par { 'c' } } },
+                    },
+                }",
+                &profile,
+            ),
+            "\
+<p>a</p>
+<p>Checklist of 2 items, 1 checked.</p>
+<break time=\"200ms\"/>
+<p><s>Item 1, checked.</s> b</p>
+<break time=\"200ms\"/>
+<p><s>Item 2, not checked.</s></p>
+<p>Numbered list of 1 item.</p>
+<break time=\"200ms\"/>
+<p><s>Item 1.</s> c</p>
+"
+        );
+    }
+
+    test_ssml!(
+        ss_table,
+        "par { table {
+            throw { par { 'Name' }, par { 'Price' } },
+            trow { par { 'a' }, par { '1' } },
+        } }",
+        "\
+<p>Table of 2 rows and 2 columns. The first row is a header.</p>
+<p><s>Row 1, column A.</s> Name</p>
+<p><s>Row 1, column B.</s> Price</p>
+<p><s>Row 2, column A, Name.</s> a</p>
+<p><s>Row 2, column B, Price.</s> 1</p>
+"
+    );
+
+    test_ssml!(
+        ss_links,
+        "par {
+            link {
+                \"u\",
+                \"a cat\",
+                props { (\"type-hint\", 'image'), (\"bg-text\", 'it sleeps') },
+            },
+            ' ', link { \"#footnote-x\", \"x\", tags { \"footnote-ref\" } },
+        },
+        section {
+            head { 0, \"Footnote\" },
+            par { 'note' },
+            tags { \"footnote-def\" },
+            props { (\"id\", \"footnote-x\") },
+        }",
+        "\
+<p>image: a cat<break strength=\"weak\"/>it sleeps footnote 1</p>
+<p>Footnote 1.</p>
+<p>note</p>
+"
+    );
+
+    test_ssml!(
+        ss_code_and_quotes,
+        "par {
+            code { \"rust\", \"show\", 'a < b' },
+            code { \"typst-formula\", \"replace\", 'x' },
+        },
+        section { head { 0, \"Quote\" }, par { 'q' }, tags { \"blockquote\" } }",
+        "\
+<p>Code in rust.</p>
+<p>a &lt; b</p>
+<p>Quote.</p>
+<p>q</p>
+<p>End of quote.</p>
+"
+    );
+
+    test_ssml!(
+        ss_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "\
+<p>Tip.</p>
+<p>a</p>
+<p>By Ada, from notes.</p>
+<p>End of quote.</p>
+<p>he said quote hi unquote by Bob.</p>
+"
+    );
+
+    #[test]
+    fn ss_language() {
+        let mut output = String::new();
+        let doc = parse("props { (\"language\", \"nl\") }, par { 'a' }").unwrap();
+        doc_to_ssml(&doc, &SpeechProfile::default(), &mut output);
+        assert!(output.contains("<speak version=\"1.1\" \
+xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"nl\">\n"));
+    }
+
+    #[test]
+    fn ss_profile() {
+        let profile = SpeechProfile::from_incodoc("props {
+            (\"medium-deemphasis\", \"voice:Bob pitch:-10%\"),
+            (\"heading-pause\", 800),
+            (\"announce-levels\", \"off\"),
+        }").unwrap();
+        assert_eq!(profile.deemphasis[1].name.as_deref(), Some("Bob"));
+        assert_eq!(profile.deemphasis[1].pitch.as_deref(), Some("-10%"));
+        assert_eq!(profile.deemphasis[1].rate, None);
+        assert_eq!(profile.heading_pause, 800);
+        assert!(!profile.announce_levels);
+        assert_eq!(profile.emphasis, SpeechProfile::default().emphasis);
+        assert_eq!(
+            SpeechProfile::from_incodoc("props { (\"link\", \"loud\") }"),
+            Err(ProfileError::InvalidValue("link".to_string()))
+        );
+        assert_eq!(
+            SpeechProfile::from_incodoc("props { (\"item-pause\", -1) }"),
+            Err(ProfileError::InvalidValue("item-pause".to_string()))
+        );
+        assert_eq!(
+            SpeechProfile::from_incodoc("props { (\"colours\", \"on\") }"),
+            Err(ProfileError::UnknownKey("colours".to_string()))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tolerant {
+    use crate::*;
+    use crate::parsing::*;
+    use std::collections::{ HashMap, HashSet };
+
+    fn text_par(text: &str) -> Paragraph {
+        Paragraph {
+            items: vec![ParagraphItem::Text(text.to_string())],
+            ..Default::default()
+        }
+    }
+
+    fn positions(errors: &[ParseError]) -> Vec<(usize, usize)> {
+        errors.iter().map(|error| (error.span.start.line, error.span.start.col)).collect()
+    }
+
+    #[test]
+    fn to_valid() {
+        let input = crate::reference_doc::REF_DOC;
+        let (doc, errors) = parse_tolerant(input);
+        assert_eq!(Ok(doc), parse(input));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn to_missing_top_comma() {
+        let (doc, errors) = parse_tolerant("par { 'a' }\npar { 'b' }");
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Paragraph(text_par("a")), DocItem::Paragraph(text_par("b"))]
+        );
+        assert_eq!(positions(&errors), vec![(2, 1)]);
+        assert_eq!(&errors[0].snippet, "par { 'b' }");
+    }
+
+    #[test]
+    fn to_missing_comma() {
+        let (doc, errors) = parse_tolerant("par {\n    'a'\n    em { le, \"b\" },\n}");
+        assert_eq!(doc.items.len(), 1);
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        assert_eq!(par.items.len(), 2);
+        assert_eq!(positions(&errors), vec![(3, 5)]);
+    }
+
+    #[test]
+    fn to_broken_paragraph() {
+        let (doc, errors) = parse_tolerant("\
+par { 'a', },
+par { em { xx, \"b\" }, },
+par { 'c', },
+");
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Paragraph(text_par("a")), DocItem::Paragraph(text_par("c"))]
+        );
+        assert_eq!(positions(&errors), vec![(2, 12)]);
+    }
+
+    #[test]
+    fn to_broken_table_row() {
+        let (doc, errors) = parse_tolerant("\
+par {
+    table {
+        throw { par { 'h', }, },
+        trow { par { em { xx, \"b\" }, }, },
+        trow { par { 'c', }, },
+    },
+},
+");
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        let ParagraphItem::Table(table) = &par.items[0] else { panic!() };
+        assert_eq!(table.rows.len(), 3);
+        assert!(table.rows[0].is_header);
+        assert!(table.rows[1].items.is_empty());
+        assert_eq!(table.rows[2].items, vec![text_par("c")]);
+        assert_eq!(positions(&errors), vec![(4, 27)]);
+    }
+
+    #[test]
+    fn to_broken_section() {
+        let (doc, errors) = parse_tolerant("\
+section {
+    head { 0, \"a\", },
+    section {
+        par { 'no heading', },
+    },
+    par { 'kept', },
+},
+");
+        let DocItem::Section(section) = &doc.items[0] else { panic!() };
+        assert_eq!(section.items, vec![SectionItem::Paragraph(text_par("kept"))]);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn to_repaired_list_item() {
+        let (doc, errors) = parse_tolerant("par { list { dl, par { 'a', }, par { 'b' 'c', }, }, },");
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        let ParagraphItem::List(list) = &par.items[0] else { panic!() };
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[0], text_par("a"));
+        assert_eq!(
+            list.items[1].items,
+            vec![ParagraphItem::Text("b".to_string()), ParagraphItem::Text("c".to_string())]
+        );
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn to_broken_list_item_emptied() {
+        let (doc, errors) = parse_tolerant("par { list { dl, par { 'a', }, par { em { xx, \"b\" }, }, }, },");
+        let DocItem::Paragraph(par) = &doc.items[0] else { panic!() };
+        let ParagraphItem::List(list) = &par.items[0] else { panic!() };
+        assert_eq!(list.items, vec![text_par("a"), text_par("")]);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn to_unclosed_text() {
+        let (doc, errors) = parse_tolerant("par { 'a', },\npar { 'b, },\n");
+        assert_eq!(doc.items, vec![DocItem::Paragraph(text_par("a"))]);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn to_top_level_garbage() {
+        let (doc, errors) = parse_tolerant("par { 'a', },\ngarbage,\npar { 'b', },,\npar { 'c', },");
+        assert_eq!(doc.items.len(), 3);
+        assert_eq!(positions(&errors), vec![(2, 1), (3, 14)]);
+    }
+
+    #[test]
+    fn to_repaired_meta() {
+        let (doc, errors) = parse_tolerant("tags { \"a\" \"b\" },\nprops { (\"x\" 1) },\npar { 'c', },");
+        assert_eq!(doc.tags, hset!(["a", "b"]));
+        assert_eq!(doc.props, props!([("x".to_string(), PropVal::Int(1))]));
+        assert_eq!(doc.items, vec![DocItem::Paragraph(text_par("c"))]);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn to_garbage() {
+        let (doc, errors) = parse_tolerant("}}{{ ,, '\"");
+        assert_eq!(doc, Doc::default());
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn to_many_errors() {
+        let broken = "par { em { xx, \"b\" }, },\n";
+        let input = format!("par {{ 'a' }},\n{}par {{ 'c' }}", broken.repeat(200));
+        let (doc, errors) = parse_tolerant(&input);
+        assert_eq!(
+            doc.items,
+            vec![DocItem::Paragraph(text_par("a")), DocItem::Paragraph(text_par("c"))]
+        );
+        assert_eq!(errors.len(), 200);
+        assert_eq!(positions(&errors)[199], (201, 12));
+    }
+}
@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod typst {
+    use crate::parsing::parse;
+    use crate::export::typst::doc_to_typst;
+
+    fn typst(input: &str) -> String {
+        let mut output = String::new();
+        doc_to_typst(&parse(input).unwrap(), &mut output);
+        output
+    }
+
+    macro_rules! test_typst {
+        ($name:ident, $input:expr, $typst:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!(typst($input), $typst);
+            }
+        }
+    }
+
+    test_typst!(
+        ty_escape,
+        "par { '= #a *b* _c_ $d$ <e> @f [g] ~ // \\\\ a--b -? 1. x\n2. y\n- z\n' }",
+        "\\= \\#a \\*b\\* \\_c\\_ \\$d\\$ \\<e\\> \\@f \\[g\\] \\~ \\// \\\\ a\\--b \\-? 1. x \\\n\
+2\\. y \\\n\\- z\n"
+    );
+
+    test_typst!(
+        ty_emphasis,
+        "par {
+            em { le, \"a\" }, '.b ', em { me, \"b\" }, ' ', em { se, \"c\" }, ' ',
+            em { ld, \"d\" }, ' ', 'f`g' { tags { \"code\" } }, ' ', 'h' { tags { \"code\" } },
+            ' ', link { \"https://a.org/\\\"x\", \"l\" },
+        }",
+        "#emph[a]\\.b #strong[b] #strong[#emph[c]] #text(size: 0.9em)[d] #raw(\"f`g\") `h` \
+#link(\"https://a.org/\\\"x\")[l]\n"
+    );
+
+    test_typst!(
+        ty_sections,
+        "section {
+            head { 0, \"a\" },
+            par { 'x' },
+            section { head { 1, \"b c\" }, par { 'y' }, props { (\"id\", \"b c\") } },
+        }",
+        "= a\n\nx\n\n== b c <b-c>\n\ny\n"
+    );
+
+    test_typst!(
+        ty_lists,
+        "par {
+            list { dl, par { 'a' }, par { 'b', list { il, par { 'c' } }, 'd' } },
+            list { cl, par { 'e', tags { \"checked\" } }, par { 'f\ng' } },
+        }",
+        "\
++ a
++ b
+
+  - c
+
+  d
+
+- ☒ e
+- ☐ f \\
+  g
+"
+    );
+
+    test_typst!(
+        ty_table,
+        "par { table { throw { par { 'A' }, par { 'B' } }, trow { par { 'a]' } } } }",
+        "#table(\n  columns: 2,\n  table.header([A], [B]),\n  [a\\]], [],\n)\n"
+    );
+
+    test_typst!(
+        ty_code,
+        "par {
+            code { \"rust\", \"show\", 'let a = `b`;' },
+            code { \"plain\", \"show\", 'a' },
+            code { \"typst\", \"replace\", '#lorem(2)' },
+            code { \"typst-formula\", \"replace\", 'x -> y' },
+            code { \"latex\", \"replace\", 'x' },
+        }",
+        "\
+```rust
+let a = `b`;
+```
+
+```
+a
+```
+
+#lorem(2)
+
+$ x -> y $
+
+```latex
+x
+```
+"
+    );
+
+    test_typst!(
+        ty_quotes,
+        "section { head { 0, \"Quote\" }, par { 'a' }, tags { \"blockquote\" } },
+        section {
+            head { 0, \"Tip\" },
+            par { 'b' },
+            tags { \"blockquote-typed\" },
+            props { (\"blockquote-type\", \"tip\") },
+        }",
+        "#quote(block: true)[\na\n]\n\n#quote(block: true)[\n#strong[Tip]\n\nb\n]\n"
+    );
+
+    test_typst!(
+        ty_quote_credit,
+        "quote { \"tip\", par { 'a' }, by { \"Ada\" }, link { \"https://x.org\", \"notes\" } },
+        par { 'he said ', quote { 'hi', by { \"Bob\" } }, '.' }",
+        "\
+#quote(block: true, attribution: [Ada, #link(\"https://x.org\")[notes]])[
+#strong[Tip]
+
+a
+]
+
+he said #quote(attribution: [Bob])[hi]\\.
+"
+    );
+
+    test_typst!(
+        ty_doc,
+        "props {
+            (\"title\", 'A \"B\"'),
+            (\"author\", 'Me'),
+            (\"date\", 2024/05/01),
+            (\"language\", \"en-GB\"),
+        },
+        par { 'a' }",
+        "\
+#set document(title: \"A \\\"B\\\"\", author: \"Me\", date: datetime(year: 2024, month: 5, day: 1))
+#set text(lang: \"en\")
+
+a
+"
+    );
+
+    test_typst!(
+        ty_footnotes,
+        "par { 'a', fnref { \"x\" }, '.' },
+        footnote { \"x\", par { 'b' }, par { 'c' } }",
+        "a#super[x]\\.\n\n#super[x] b\n\nc\n"
+    );
+
+    test_typst!(
+        ty_data,
+        "par { data { ['a', 2], [true, -0.5] } }",
+        "#table(\n  columns: 2,\n  [a], [2],\n  [true], [\\-0.5],\n)\n"
+    );
+}